@@ -0,0 +1,186 @@
+//! Draws a noise field to an image, to tune seeds, persistance and octaves without starting the game
+//!
+//! ```text
+//! cargo run --example noise-preview --features png -- perlin --octaves 6 --scale 4 --sphere --colormap terrain out.png
+//! ```
+//!
+//! Prints the value range and a histogram, and writes a PGM/PPM, or a PNG when built with the `png` feature
+
+extern crate sapiens_rs;
+extern crate sapiens_sys;
+
+use sapiens_rs::sp::noise::preview::{Colormap, Grid, PlaneSlice, ValueRange};
+use sapiens_rs::sp::noise::{Noise, NoiseSource, OpenSimplex, Perlin, Worley, WorleyFeature};
+use sapiens_sys::SPVec3;
+use std::env;
+use std::error::Error;
+use std::fs::File;
+use std::io::BufWriter;
+use std::process;
+
+const USAGE: &str =
+    "usage: noise-preview <perlin|open-simplex|worley|worley-edge|sapiens> [options] <output>
+
+options:
+    --seed <n>             noise seed (default 1234)
+    --persistance <p>      amplitude falloff per octave (default 0.5)
+    --octaves <n>          end octave (default 4)
+    --scale <s>            sphere radius or plane size in noise space (default 4)
+    --size <w>x<h>         image size (default 512x256)
+    --plane | --sphere     sample a flat slice or the whole planet (default sphere)
+    --colormap <name>      gray, terrain or heat (default gray)
+    --fixed-range          map [-1, 1] to the colormap instead of the sampled min/max
+
+<output> ends in .png (needs the png feature), .pgm or .ppm";
+
+struct Options {
+    generator: String,
+    seed: i32,
+    persistance: f64,
+    octaves: i32,
+    scale: f64,
+    width: usize,
+    height: usize,
+    sphere: bool,
+    colormap: Colormap,
+    range: ValueRange,
+    output: String,
+}
+
+fn parse_options() -> Result<Options, Box<dyn Error>> {
+    let mut args = env::args().skip(1);
+    let mut options = Options {
+        generator: args.next().ok_or("missing generator")?,
+        seed: 1234,
+        persistance: 0.5,
+        octaves: 4,
+        scale: 4.0,
+        width: 512,
+        height: 256,
+        sphere: true,
+        colormap: Colormap::Grayscale,
+        range: ValueRange::Auto,
+        output: String::new(),
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+
+        match arg.as_str() {
+            "--seed" => options.seed = value()?.parse()?,
+            "--persistance" => options.persistance = value()?.parse()?,
+            "--octaves" => options.octaves = value()?.parse()?,
+            "--scale" => options.scale = value()?.parse()?,
+            "--size" => {
+                let size = value()?;
+                let (width, height) = size
+                    .split_once('x')
+                    .ok_or("size should look like 512x256")?;
+                options.width = width.parse()?;
+                options.height = height.parse()?;
+            }
+            "--plane" => options.sphere = false,
+            "--sphere" => options.sphere = true,
+            "--colormap" => {
+                options.colormap = match value()?.as_str() {
+                    "gray" => Colormap::Grayscale,
+                    "terrain" => Colormap::Terrain,
+                    "heat" => Colormap::Heat,
+                    other => return Err(format!("unknown colormap {}", other).into()),
+                }
+            }
+            "--fixed-range" => options.range = ValueRange::Fixed(-1.0, 1.0),
+            _ if arg.starts_with("--") => return Err(format!("unknown option {}", arg).into()),
+            _ => options.output = arg,
+        }
+    }
+
+    if options.output.is_empty() {
+        return Err("missing output path".into());
+    }
+
+    Ok(options)
+}
+
+fn sample<N: NoiseSource>(noise: &N, options: &Options) -> Grid {
+    if options.sphere {
+        Grid::sample_lat_long(
+            noise,
+            options.octaves,
+            options.width,
+            options.height,
+            options.scale,
+        )
+    } else {
+        let slice = PlaneSlice::xz(SPVec3::default(), options.scale);
+        Grid::sample_plane(
+            noise,
+            options.octaves,
+            options.width,
+            options.height,
+            &slice,
+        )
+    }
+}
+
+fn run() -> Result<(), Box<dyn Error>> {
+    let options = parse_options()?;
+    let (seed, persistance) = (options.seed, options.persistance);
+
+    let grid = match options.generator.as_str() {
+        "perlin" => sample(&Perlin::new(seed, persistance), &options),
+        "open-simplex" => sample(&OpenSimplex::new(seed, persistance), &options),
+        "worley" => sample(&Worley::new(seed, persistance), &options),
+        "worley-edge" => sample(
+            &Worley::new(seed, persistance).with_feature(WorleyFeature::Edge),
+            &options,
+        ),
+        "sapiens" => sample(&Noise::new(seed, persistance), &options),
+        other => return Err(format!("unknown generator {}", other).into()),
+    };
+
+    let stats = grid.stats(10);
+    println!(
+        "min {:.4}  max {:.4}  mean {:.4}",
+        stats.min, stats.max, stats.mean
+    );
+    let largest_bin = stats.histogram.iter().copied().max().unwrap_or(1).max(1);
+    for (i, count) in stats.histogram.iter().enumerate() {
+        let low = stats.min + (stats.max - stats.min) * i as f64 / stats.histogram.len() as f64;
+        println!("{:>8.3} {}", low, "#".repeat(count * 50 / largest_bin));
+    }
+
+    let image = grid.to_image(options.range, options.colormap);
+    let mut file = BufWriter::new(File::create(&options.output)?);
+    if options.output.ends_with(".png") {
+        write_png(&image, &mut file)?;
+    } else {
+        image.write_pnm(&mut file)?;
+    }
+
+    println!("wrote {}", options.output);
+    Ok(())
+}
+
+#[cfg(feature = "png")]
+fn write_png(
+    image: &sapiens_rs::sp::noise::preview::Image,
+    file: &mut BufWriter<File>,
+) -> Result<(), Box<dyn Error>> {
+    Ok(image.write_png(file)?)
+}
+
+#[cfg(not(feature = "png"))]
+fn write_png(
+    _image: &sapiens_rs::sp::noise::preview::Image,
+    _file: &mut BufWriter<File>,
+) -> Result<(), Box<dyn Error>> {
+    Err("PNG output needs the png feature; use a .pgm or .ppm path instead".into())
+}
+
+fn main() {
+    if let Err(error) = run() {
+        eprintln!("{}\n\n{}", error, USAGE);
+        process::exit(1);
+    }
+}
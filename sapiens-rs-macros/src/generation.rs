@@ -5,6 +5,7 @@ use crate::particles::{
     generate_get_render_group_types, generate_get_render_group_types_count,
     generate_update_emitter,
 };
+use crate::worldgen::{generate_biome_get_surface_type, generate_height_get};
 use proc_macro::TokenStream;
 use std::convert::TryFrom;
 
@@ -17,6 +18,8 @@ pub(crate) enum SapiensApiFunctions {
     spGetRenderGroupTypes,
     spEmitterWasAdded,
     spUpdateEmitter,
+    spHeightGet,
+    spBiomeGetSurfaceType,
 }
 
 impl TryFrom<String> for SapiensApiFunctions {
@@ -35,6 +38,10 @@ impl TryFrom<String> for SapiensApiFunctions {
             Ok(SapiensApiFunctions::spEmitterWasAdded)
         } else if &value == "update_emitter" {
             Ok(SapiensApiFunctions::spUpdateEmitter)
+        } else if &value == "height_get" {
+            Ok(SapiensApiFunctions::spHeightGet)
+        } else if &value == "biome_get_surface_type" {
+            Ok(SapiensApiFunctions::spBiomeGetSurfaceType)
         } else {
             Err(())
         }
@@ -52,6 +59,8 @@ impl TryFrom<String> for SapiensApiFunctions {
 /// - emitter_was_added -> spEmitterWasAdded
 /// - update_emitter -> spUpdateEmitter
 /// - update_particle -> spUpdateParticle
+/// - height_get -> spHeightGet
+/// - biome_get_surface_type -> spBiomeGetSurfaceType
 pub fn generate_binding(func: syn::ItemFn) -> TokenStream {
     let func_name = SapiensApiFunctions::try_from(format!("{}", func.sig.ident)).unwrap();
     match func_name {
@@ -63,5 +72,7 @@ pub fn generate_binding(func: syn::ItemFn) -> TokenStream {
         SapiensApiFunctions::spGetRenderGroupTypes => generate_get_render_group_types(func),
         SapiensApiFunctions::spEmitterWasAdded => generate_emitter_was_added(func),
         SapiensApiFunctions::spUpdateEmitter => generate_update_emitter(func),
+        SapiensApiFunctions::spHeightGet => generate_height_get(func),
+        SapiensApiFunctions::spBiomeGetSurfaceType => generate_biome_get_surface_type(func),
     }
 }
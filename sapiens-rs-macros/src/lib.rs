@@ -7,6 +7,7 @@ use quote::quote;
 
 mod generation;
 mod particles;
+mod worldgen;
 
 /// Generates code to allow Sapiens to call your function
 ///
@@ -22,6 +23,8 @@ mod particles;
 /// - emitter_was_added -> spEmitterWasAdded
 /// - update_emitter -> spUpdateEmitter
 /// - update_particle -> spUpdateParticle
+/// - height_get -> spHeightGet
+/// - biome_get_surface_type -> spBiomeGetSurfaceType
 ///
 /// TODO: Look at the enum types used for the emitter type and render group type to determine how to convert from
 /// Sapiens ints to your enums
@@ -53,7 +53,10 @@ pub fn generate_get_render_group_types(func: syn::ItemFn) -> TokenStream {
     let ast = quote! {
     #[no_mangle]
     pub extern "C" fn spGetRenderGroupTypes() -> *mut SPParticleRenderGroupInfo {
-        let mut render_group_types: Vec<SPParticleRenderGroupInfo> = get_render_group_types()
+        let mut render_group_types = get_render_group_types();
+        ::sapiens_rs::sp::particles::register_render_groups(&render_group_types);
+
+        let mut render_group_types: Vec<SPParticleRenderGroupInfo> = render_group_types
             .drain(::std::ops::RangeFull)
             .map(|emitter_type| ::std::convert::TryInto::try_into(emitter_type).unwrap())
             .collect();
@@ -81,9 +84,9 @@ pub fn generate_emitter_was_added(func: syn::ItemFn) -> TokenStream {
     ) -> bool {
         let mut emitter_state = unsafe { &mut *emitterState };
 
-        let mut thread_state: ::sapiens_rs::sp::particles::ThreadState = ::std::convert::TryFrom::try_from(unsafe { *threadState }).unwrap();
+        let mut thread_state = unsafe { ::sapiens_rs::sp::particles::ThreadState::from_sp(&mut *threadState) };
 
-        emitter_was_added(&thread_state, &mut emitter_state, ::num_traits::FromPrimitive::from_u32(localEmitterTypeID).unwrap())
+        emitter_was_added(&mut thread_state, &mut emitter_state, ::num_traits::FromPrimitive::from_u32(localEmitterTypeID).unwrap())
     }
 
     #func
@@ -101,7 +104,7 @@ pub fn generate_update_emitter(func: syn::ItemFn) -> TokenStream {
         dt: f64
     ) {
 
-        let mut thread_state: ::sapiens_rs::sp::particles::ThreadState = ::std::convert::TryFrom::try_from(unsafe { *sp_thread_state }).unwrap();
+        let mut thread_state = unsafe { ::sapiens_rs::sp::particles::ThreadState::from_sp(&mut *sp_thread_state) };
         let mut emitter_state = unsafe { &mut *sp_emitter_state };
 
         update_emitter(
@@ -0,0 +1,72 @@
+use proc_macro::TokenStream;
+use quote::quote;
+
+pub fn generate_height_get(func: syn::ItemFn) -> TokenStream {
+    let ast = quote! {
+    #[no_mangle]
+    #[allow(unused_unsafe)]
+    pub unsafe extern "C" fn spHeightGet(
+        noise1: *mut ::sapiens_sys::SPNoise,
+        noise2: *mut ::sapiens_sys::SPNoise,
+        point_normal: ::sapiens_sys::SPVec3,
+        noise_loc: ::sapiens_sys::SPVec3,
+        world_gen_options: ::sapiens_sys::SPWorldGenOptions,
+        river_value: f64,
+        river_distance: f64,
+    ) -> ::sapiens_sys::SPVec4 {
+        let noise1 = unsafe { ::sapiens_rs::sp::noise::NoiseRef::from_ptr(noise1) };
+        let noise2 = unsafe { ::sapiens_rs::sp::noise::NoiseRef::from_ptr(noise2) };
+        let query = ::sapiens_rs::sp::height::HeightQuery::from_sp(point_normal, noise_loc, river_value, river_distance);
+        let options = ::sapiens_rs::sp::common::WorldGenOptions::from_sp(world_gen_options);
+
+        height_get(&noise1, &noise2, &query, &options)
+    }
+
+    #func
+    };
+
+    ast.into()
+}
+
+pub fn generate_biome_get_surface_type(func: syn::ItemFn) -> TokenStream {
+    let ast = quote! {
+    #[no_mangle]
+    #[allow(unused_unsafe)]
+    pub unsafe extern "C" fn spBiomeGetSurfaceType(
+        noise: *mut ::sapiens_sys::SPNoise,
+        biome_types: *mut ::sapiens_sys::SPBiomeType,
+        point_normal: ::sapiens_sys::SPVec3,
+        noise_loc: ::sapiens_sys::SPVec3,
+        biome_type: ::std::os::raw::c_int,
+        vegetation_state: ::std::os::raw::c_int,
+        altitude: f64,
+        steepness: f32,
+        river_distance: f32,
+    ) -> ::std::os::raw::c_int {
+        let noise = unsafe { ::sapiens_rs::sp::noise::NoiseRef::from_ptr(noise) };
+        let query = ::sapiens_rs::sp::biome::SurfaceQuery::from_sp(
+            point_normal,
+            noise_loc,
+            biome_type,
+            vegetation_state,
+            altitude,
+            steepness,
+            river_distance,
+        );
+        let biome_types = unsafe { ::sapiens_rs::sp::common::BiomeTypes::from_raw(biome_types, biome_type) };
+        let surface_type = biome_get_surface_type(&noise, &biome_types, &query);
+
+        // Sapiens wants the terrain type ID the point's biome uses for that part of its surface, as in the SDK's
+        // SPBiome.h, not the index of the field. Without a biome there's nothing to pick from, so the point gets
+        // terrain type 0 rather than an ID the game can't look up
+        match biome_types.own() {
+            Some(biome) => surface_type.terrain_type(biome).id() as ::std::os::raw::c_int,
+            None => 0,
+        }
+    }
+
+    #func
+    };
+
+    ast.into()
+}
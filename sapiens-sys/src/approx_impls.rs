@@ -0,0 +1,97 @@
+//! Approximate equality for the Sapiens math types
+//!
+//! Every component is compared with the same tolerance, so two vectors or matrices are approximately equal when all
+//! of their components are
+
+use crate::{SPMat3, SPMat4, SPVec2, SPVec3, SPVec4};
+use approx::{AbsDiffEq, RelativeEq, UlpsEq};
+
+macro_rules! impl_approx {
+    ($sp_type:ty, $($field:ident),+) => {
+        impl AbsDiffEq for $sp_type {
+            type Epsilon = f64;
+
+            fn default_epsilon() -> f64 {
+                f64::default_epsilon()
+            }
+
+            fn abs_diff_eq(&self, other: &Self, epsilon: f64) -> bool {
+                $(f64::abs_diff_eq(&self.$field, &other.$field, epsilon))&&+
+            }
+        }
+
+        impl RelativeEq for $sp_type {
+            fn default_max_relative() -> f64 {
+                f64::default_max_relative()
+            }
+
+            fn relative_eq(&self, other: &Self, epsilon: f64, max_relative: f64) -> bool {
+                $(f64::relative_eq(&self.$field, &other.$field, epsilon, max_relative))&&+
+            }
+        }
+
+        impl UlpsEq for $sp_type {
+            fn default_max_ulps() -> u32 {
+                f64::default_max_ulps()
+            }
+
+            fn ulps_eq(&self, other: &Self, epsilon: f64, max_ulps: u32) -> bool {
+                $(f64::ulps_eq(&self.$field, &other.$field, epsilon, max_ulps))&&+
+            }
+        }
+    };
+}
+
+impl_approx!(SPVec2, x, y);
+impl_approx!(SPVec3, x, y, z);
+impl_approx!(SPVec4, x, y, z, w);
+impl_approx!(SPMat3, m0, m1, m2, m3, m4, m5, m6, m7, m8);
+impl_approx!(SPMat4, m0, m1, m2, m3, m4, m5, m6, m7, m8, m9, m10, m11, m12, m13, m14, m15);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::{assert_abs_diff_eq, assert_abs_diff_ne, assert_relative_eq, assert_ulps_eq};
+
+    #[test]
+    fn vectors_within_epsilon_are_equal() {
+        let a = SPVec3 {
+            x: 1.0,
+            y: 2.0,
+            z: 3.0,
+        };
+        let b = SPVec3 {
+            x: 1.0 + 1e-10,
+            y: 2.0,
+            z: 3.0 - 1e-10,
+        };
+
+        assert_abs_diff_eq!(a, b, epsilon = 1e-9);
+        assert_relative_eq!(a, b, max_relative = 1e-9);
+    }
+
+    #[test]
+    fn one_component_outside_epsilon_is_not_equal() {
+        let a = SPVec2 { x: 1.0, y: 2.0 };
+        let b = SPVec2 { x: 1.0, y: 2.1 };
+
+        assert_abs_diff_ne!(a, b, epsilon = 1e-3);
+    }
+
+    #[test]
+    fn matrices_compare_every_component() {
+        let a = SPMat3 {
+            m0: 1.0,
+            m4: 1.0,
+            m8: 1.0,
+            ..Default::default()
+        };
+        let b = SPMat3 {
+            m0: 0.1 + 0.2 + 0.7,
+            ..a
+        };
+
+        assert_ulps_eq!(a, b);
+        assert_abs_diff_ne!(a, SPMat3 { m8: 1.5, ..a });
+    }
+}
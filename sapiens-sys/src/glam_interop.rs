@@ -0,0 +1,229 @@
+//! Conversions between Sapiens' math types and `glam`'s double-precision types
+//!
+//! Sapiens stores matrices row by row and multiplies vectors from the left (`v * M`). glam stores matrices column by
+//! column and multiplies vectors from the right (`M * v`). Row `i` of an `SPMat3` therefore becomes column `i` of a
+//! `DMat3`, which keeps the memory layout identical and means `DMat3::from(m) * v` gives the same result as
+//! `spVec3xMat3(v, m)`
+
+use crate::{SPMat3, SPMat4, SPVec2, SPVec3, SPVec4};
+use glam::{DMat3, DMat4, DQuat, DVec2, DVec3, DVec4};
+
+impl From<SPVec2> for DVec2 {
+    fn from(v: SPVec2) -> Self {
+        DVec2::new(v.x, v.y)
+    }
+}
+
+impl From<DVec2> for SPVec2 {
+    fn from(v: DVec2) -> Self {
+        SPVec2 { x: v.x, y: v.y }
+    }
+}
+
+impl From<SPVec3> for DVec3 {
+    fn from(v: SPVec3) -> Self {
+        DVec3::new(v.x, v.y, v.z)
+    }
+}
+
+impl From<DVec3> for SPVec3 {
+    fn from(v: DVec3) -> Self {
+        SPVec3 {
+            x: v.x,
+            y: v.y,
+            z: v.z,
+        }
+    }
+}
+
+impl From<SPVec4> for DVec4 {
+    fn from(v: SPVec4) -> Self {
+        DVec4::new(v.x, v.y, v.z, v.w)
+    }
+}
+
+impl From<DVec4> for SPVec4 {
+    fn from(v: DVec4) -> Self {
+        SPVec4 {
+            x: v.x,
+            y: v.y,
+            z: v.z,
+            w: v.w,
+        }
+    }
+}
+
+/// Sapiens quaternions are `SPVec4`s laid out as `(x, y, z, w)`, with `w` as the scalar part
+impl From<SPVec4> for DQuat {
+    fn from(q: SPVec4) -> Self {
+        DQuat::from_xyzw(q.x, q.y, q.z, q.w)
+    }
+}
+
+impl From<DQuat> for SPVec4 {
+    fn from(q: DQuat) -> Self {
+        SPVec4 {
+            x: q.x,
+            y: q.y,
+            z: q.z,
+            w: q.w,
+        }
+    }
+}
+
+impl From<SPMat3> for DMat3 {
+    fn from(m: SPMat3) -> Self {
+        DMat3::from_cols_array(&[m.m0, m.m1, m.m2, m.m3, m.m4, m.m5, m.m6, m.m7, m.m8])
+    }
+}
+
+impl From<DMat3> for SPMat3 {
+    fn from(m: DMat3) -> Self {
+        let [m0, m1, m2, m3, m4, m5, m6, m7, m8] = m.to_cols_array();
+        SPMat3 {
+            m0,
+            m1,
+            m2,
+            m3,
+            m4,
+            m5,
+            m6,
+            m7,
+            m8,
+        }
+    }
+}
+
+impl From<SPMat4> for DMat4 {
+    fn from(m: SPMat4) -> Self {
+        DMat4::from_cols_array(&[
+            m.m0, m.m1, m.m2, m.m3, m.m4, m.m5, m.m6, m.m7, m.m8, m.m9, m.m10, m.m11, m.m12, m.m13,
+            m.m14, m.m15,
+        ])
+    }
+}
+
+impl From<DMat4> for SPMat4 {
+    fn from(m: DMat4) -> Self {
+        let [m0, m1, m2, m3, m4, m5, m6, m7, m8, m9, m10, m11, m12, m13, m14, m15] =
+            m.to_cols_array();
+        SPMat4 {
+            m0,
+            m1,
+            m2,
+            m3,
+            m4,
+            m5,
+            m6,
+            m7,
+            m8,
+            m9,
+            m10,
+            m11,
+            m12,
+            m13,
+            m14,
+            m15,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sp_mat3_from_rows(rows: [[f64; 3]; 3]) -> SPMat3 {
+        SPMat3 {
+            m0: rows[0][0],
+            m1: rows[0][1],
+            m2: rows[0][2],
+            m3: rows[1][0],
+            m4: rows[1][1],
+            m5: rows[1][2],
+            m6: rows[2][0],
+            m7: rows[2][1],
+            m8: rows[2][2],
+        }
+    }
+
+    #[test]
+    fn vec_round_trip() {
+        let v = SPVec3 {
+            x: 1.0,
+            y: 2.0,
+            z: 3.0,
+        };
+
+        let glam_v: DVec3 = v.into();
+
+        assert_eq!(glam_v, DVec3::new(1.0, 2.0, 3.0));
+        assert_eq!(SPVec3::from(glam_v), v);
+    }
+
+    #[test]
+    fn quat_uses_xyzw_layout() {
+        let q = SPVec4 {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+            w: 1.0,
+        };
+
+        assert_eq!(DQuat::from(q), DQuat::IDENTITY);
+        assert_eq!(SPVec4::from(DQuat::IDENTITY), q);
+    }
+
+    #[test]
+    fn sapiens_rows_become_glam_columns() {
+        let m = sp_mat3_from_rows([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0], [7.0, 8.0, 9.0]]);
+
+        let glam_m = DMat3::from(m);
+
+        assert_eq!(glam_m.col(0), DVec3::new(1.0, 2.0, 3.0));
+        assert_eq!(glam_m.col(1), DVec3::new(4.0, 5.0, 6.0));
+        assert_eq!(glam_m.col(2), DVec3::new(7.0, 8.0, 9.0));
+        assert_eq!(SPMat3::from(glam_m), m);
+    }
+
+    #[test]
+    fn glam_matrix_times_vector_matches_sapiens_vector_times_matrix() {
+        let m = sp_mat3_from_rows([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0], [7.0, 8.0, 9.0]]);
+        let v = DVec3::new(1.0, 10.0, 100.0);
+
+        // Sapiens' `v * M` is `v.x * row0 + v.y * row1 + v.z * row2`
+        let expected = DVec3::new(
+            1.0 + 40.0 + 700.0,
+            2.0 + 50.0 + 800.0,
+            3.0 + 60.0 + 900.0,
+        );
+
+        assert_eq!(DMat3::from(m) * v, expected);
+    }
+
+    #[test]
+    fn mat4_round_trip() {
+        let m = SPMat4 {
+            m0: 1.0,
+            m1: 2.0,
+            m2: 3.0,
+            m3: 4.0,
+            m4: 5.0,
+            m5: 6.0,
+            m6: 7.0,
+            m7: 8.0,
+            m8: 9.0,
+            m9: 10.0,
+            m10: 11.0,
+            m11: 12.0,
+            m12: 13.0,
+            m13: 14.0,
+            m14: 15.0,
+            m15: 16.0,
+        };
+
+        let glam_m = DMat4::from(m);
+
+        assert_eq!(glam_m.col(3), DVec4::new(13.0, 14.0, 15.0, 16.0));
+        assert_eq!(SPMat4::from(glam_m), m);
+    }
+}
@@ -3,3 +3,25 @@
 #![allow(non_snake_case)]
 
 include!("bindings.rs");
+
+#[cfg(feature = "approx")]
+pub use approx;
+#[cfg(feature = "glam")]
+pub use glam;
+#[cfg(feature = "mint")]
+pub use mint;
+#[cfg(feature = "nalgebra")]
+pub use nalgebra;
+#[cfg(feature = "serde")]
+pub use serde;
+
+#[cfg(feature = "approx")]
+mod approx_impls;
+#[cfg(feature = "glam")]
+mod glam_interop;
+#[cfg(feature = "mint")]
+mod mint_interop;
+#[cfg(feature = "nalgebra")]
+mod nalgebra_interop;
+#[cfg(feature = "serde")]
+mod serde_impls;
@@ -0,0 +1,184 @@
+//! Conversions between Sapiens' math types and `mint`'s interchange types
+//!
+//! Sapiens stores matrices row by row and multiplies vectors from the left (`v * M`). Row `i` of an `SPMat3` becomes
+//! column `i` of a `ColumnMatrix3`, so libraries which multiply column vectors from the right (glam, nalgebra, cgmath)
+//! produce the same result as `spVec3xMat3`
+
+use crate::{SPMat3, SPMat4, SPVec2, SPVec3, SPVec4};
+use mint::{ColumnMatrix3, ColumnMatrix4, Quaternion, Vector2, Vector3, Vector4};
+
+impl From<SPVec2> for Vector2<f64> {
+    fn from(v: SPVec2) -> Self {
+        Vector2 { x: v.x, y: v.y }
+    }
+}
+
+impl From<Vector2<f64>> for SPVec2 {
+    fn from(v: Vector2<f64>) -> Self {
+        SPVec2 { x: v.x, y: v.y }
+    }
+}
+
+impl From<SPVec3> for Vector3<f64> {
+    fn from(v: SPVec3) -> Self {
+        Vector3 {
+            x: v.x,
+            y: v.y,
+            z: v.z,
+        }
+    }
+}
+
+impl From<Vector3<f64>> for SPVec3 {
+    fn from(v: Vector3<f64>) -> Self {
+        SPVec3 {
+            x: v.x,
+            y: v.y,
+            z: v.z,
+        }
+    }
+}
+
+impl From<SPVec4> for Vector4<f64> {
+    fn from(v: SPVec4) -> Self {
+        Vector4 {
+            x: v.x,
+            y: v.y,
+            z: v.z,
+            w: v.w,
+        }
+    }
+}
+
+impl From<Vector4<f64>> for SPVec4 {
+    fn from(v: Vector4<f64>) -> Self {
+        SPVec4 {
+            x: v.x,
+            y: v.y,
+            z: v.z,
+            w: v.w,
+        }
+    }
+}
+
+/// Sapiens quaternions are `SPVec4`s laid out as `(x, y, z, w)`, with `w` as the scalar part
+impl From<SPVec4> for Quaternion<f64> {
+    fn from(q: SPVec4) -> Self {
+        Quaternion {
+            v: Vector3 {
+                x: q.x,
+                y: q.y,
+                z: q.z,
+            },
+            s: q.w,
+        }
+    }
+}
+
+impl From<Quaternion<f64>> for SPVec4 {
+    fn from(q: Quaternion<f64>) -> Self {
+        SPVec4 {
+            x: q.v.x,
+            y: q.v.y,
+            z: q.v.z,
+            w: q.s,
+        }
+    }
+}
+
+impl From<SPMat3> for ColumnMatrix3<f64> {
+    fn from(m: SPMat3) -> Self {
+        ColumnMatrix3::from([m.m0, m.m1, m.m2, m.m3, m.m4, m.m5, m.m6, m.m7, m.m8])
+    }
+}
+
+impl From<ColumnMatrix3<f64>> for SPMat3 {
+    fn from(m: ColumnMatrix3<f64>) -> Self {
+        SPMat3 {
+            m0: m.x.x,
+            m1: m.x.y,
+            m2: m.x.z,
+            m3: m.y.x,
+            m4: m.y.y,
+            m5: m.y.z,
+            m6: m.z.x,
+            m7: m.z.y,
+            m8: m.z.z,
+        }
+    }
+}
+
+impl From<SPMat4> for ColumnMatrix4<f64> {
+    fn from(m: SPMat4) -> Self {
+        ColumnMatrix4::from([
+            m.m0, m.m1, m.m2, m.m3, m.m4, m.m5, m.m6, m.m7, m.m8, m.m9, m.m10, m.m11, m.m12, m.m13,
+            m.m14, m.m15,
+        ])
+    }
+}
+
+impl From<ColumnMatrix4<f64>> for SPMat4 {
+    fn from(m: ColumnMatrix4<f64>) -> Self {
+        SPMat4 {
+            m0: m.x.x,
+            m1: m.x.y,
+            m2: m.x.z,
+            m3: m.x.w,
+            m4: m.y.x,
+            m5: m.y.y,
+            m6: m.y.z,
+            m7: m.y.w,
+            m8: m.z.x,
+            m9: m.z.y,
+            m10: m.z.z,
+            m11: m.z.w,
+            m12: m.w.x,
+            m13: m.w.y,
+            m14: m.w.z,
+            m15: m.w.w,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quat_uses_xyzw_layout() {
+        let q = SPVec4 {
+            x: 1.0,
+            y: 2.0,
+            z: 3.0,
+            w: 4.0,
+        };
+
+        let mint_q = Quaternion::from(q);
+
+        assert_eq!(mint_q.s, 4.0);
+        assert_eq!(mint_q.v, Vector3::from([1.0, 2.0, 3.0]));
+        assert_eq!(SPVec4::from(mint_q), q);
+    }
+
+    #[test]
+    fn sapiens_rows_become_mint_columns() {
+        let m = SPMat3 {
+            m0: 1.0,
+            m1: 2.0,
+            m2: 3.0,
+            m3: 4.0,
+            m4: 5.0,
+            m5: 6.0,
+            m6: 7.0,
+            m7: 8.0,
+            m8: 9.0,
+        };
+
+        let mint_m = ColumnMatrix3::from(m);
+
+        assert_eq!(mint_m.x, Vector3::from([1.0, 2.0, 3.0]));
+        assert_eq!(mint_m.y, Vector3::from([4.0, 5.0, 6.0]));
+        assert_eq!(mint_m.z, Vector3::from([7.0, 8.0, 9.0]));
+        assert_eq!(SPMat3::from(mint_m), m);
+    }
+}
@@ -0,0 +1,189 @@
+//! Conversions between Sapiens' math types and `nalgebra`'s `f64` types
+//!
+//! Sapiens stores matrices row by row and multiplies vectors from the left (`v * M`). nalgebra stores matrices column
+//! by column and multiplies vectors from the right (`M * v`). Row `i` of an `SPMat3` therefore becomes column `i` of a
+//! `Matrix3`, which keeps the memory layout identical and means `Matrix3::from(m) * v` gives the same result as
+//! `spVec3xMat3(v, m)`
+
+use crate::{SPMat3, SPMat4, SPVec2, SPVec3, SPVec4};
+use nalgebra::{Matrix3, Matrix4, Quaternion, Vector2, Vector3, Vector4};
+
+impl From<SPVec2> for Vector2<f64> {
+    fn from(v: SPVec2) -> Self {
+        Vector2::new(v.x, v.y)
+    }
+}
+
+impl From<Vector2<f64>> for SPVec2 {
+    fn from(v: Vector2<f64>) -> Self {
+        SPVec2 { x: v.x, y: v.y }
+    }
+}
+
+impl From<SPVec3> for Vector3<f64> {
+    fn from(v: SPVec3) -> Self {
+        Vector3::new(v.x, v.y, v.z)
+    }
+}
+
+impl From<Vector3<f64>> for SPVec3 {
+    fn from(v: Vector3<f64>) -> Self {
+        SPVec3 {
+            x: v.x,
+            y: v.y,
+            z: v.z,
+        }
+    }
+}
+
+impl From<SPVec4> for Vector4<f64> {
+    fn from(v: SPVec4) -> Self {
+        Vector4::new(v.x, v.y, v.z, v.w)
+    }
+}
+
+impl From<Vector4<f64>> for SPVec4 {
+    fn from(v: Vector4<f64>) -> Self {
+        SPVec4 {
+            x: v.x,
+            y: v.y,
+            z: v.z,
+            w: v.w,
+        }
+    }
+}
+
+/// Sapiens quaternions are `SPVec4`s laid out as `(x, y, z, w)`, with `w` as the scalar part
+impl From<SPVec4> for Quaternion<f64> {
+    fn from(q: SPVec4) -> Self {
+        Quaternion::new(q.w, q.x, q.y, q.z)
+    }
+}
+
+impl From<Quaternion<f64>> for SPVec4 {
+    fn from(q: Quaternion<f64>) -> Self {
+        SPVec4 {
+            x: q.i,
+            y: q.j,
+            z: q.k,
+            w: q.w,
+        }
+    }
+}
+
+impl From<SPMat3> for Matrix3<f64> {
+    fn from(m: SPMat3) -> Self {
+        Matrix3::from_column_slice(&[m.m0, m.m1, m.m2, m.m3, m.m4, m.m5, m.m6, m.m7, m.m8])
+    }
+}
+
+impl From<Matrix3<f64>> for SPMat3 {
+    fn from(m: Matrix3<f64>) -> Self {
+        let s = m.as_slice();
+        SPMat3 {
+            m0: s[0],
+            m1: s[1],
+            m2: s[2],
+            m3: s[3],
+            m4: s[4],
+            m5: s[5],
+            m6: s[6],
+            m7: s[7],
+            m8: s[8],
+        }
+    }
+}
+
+impl From<SPMat4> for Matrix4<f64> {
+    fn from(m: SPMat4) -> Self {
+        Matrix4::from_column_slice(&[
+            m.m0, m.m1, m.m2, m.m3, m.m4, m.m5, m.m6, m.m7, m.m8, m.m9, m.m10, m.m11, m.m12, m.m13,
+            m.m14, m.m15,
+        ])
+    }
+}
+
+impl From<Matrix4<f64>> for SPMat4 {
+    fn from(m: Matrix4<f64>) -> Self {
+        let s = m.as_slice();
+        SPMat4 {
+            m0: s[0],
+            m1: s[1],
+            m2: s[2],
+            m3: s[3],
+            m4: s[4],
+            m5: s[5],
+            m6: s[6],
+            m7: s[7],
+            m8: s[8],
+            m9: s[9],
+            m10: s[10],
+            m11: s[11],
+            m12: s[12],
+            m13: s[13],
+            m14: s[14],
+            m15: s[15],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vec_round_trip() {
+        let v = SPVec3 {
+            x: 1.0,
+            y: 2.0,
+            z: 3.0,
+        };
+
+        let na_v: Vector3<f64> = v.into();
+
+        assert_eq!(na_v, Vector3::new(1.0, 2.0, 3.0));
+        assert_eq!(SPVec3::from(na_v), v);
+    }
+
+    #[test]
+    fn quat_uses_xyzw_layout() {
+        let q = SPVec4 {
+            x: 1.0,
+            y: 2.0,
+            z: 3.0,
+            w: 4.0,
+        };
+
+        let na_q = Quaternion::from(q);
+
+        assert_eq!(na_q.w, 4.0);
+        assert_eq!(na_q.imag(), Vector3::new(1.0, 2.0, 3.0));
+        assert_eq!(SPVec4::from(na_q), q);
+    }
+
+    #[test]
+    fn sapiens_rows_become_nalgebra_columns() {
+        let m = SPMat3 {
+            m0: 1.0,
+            m1: 2.0,
+            m2: 3.0,
+            m3: 4.0,
+            m4: 5.0,
+            m5: 6.0,
+            m6: 7.0,
+            m7: 8.0,
+            m8: 9.0,
+        };
+
+        let na_m = Matrix3::from(m);
+
+        assert_eq!(na_m.column(0).into_owned(), Vector3::new(1.0, 2.0, 3.0));
+        assert_eq!(na_m.column(2).into_owned(), Vector3::new(7.0, 8.0, 9.0));
+        // Sapiens' `v * M` is `v.x * row0 + v.y * row1 + v.z * row2`
+        assert_eq!(
+            na_m * Vector3::new(1.0, 10.0, 100.0),
+            Vector3::new(741.0, 852.0, 963.0)
+        );
+        assert_eq!(SPMat3::from(na_m), m);
+    }
+}
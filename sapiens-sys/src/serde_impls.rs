@@ -0,0 +1,143 @@
+//! `Serialize` and `Deserialize` implementations for the plain-data Sapiens types
+//!
+//! The bindings are generated, so we can't derive on them directly. Instead each type gets a mirror definition using
+//! serde's remote derive, and the real type forwards to it. Fields are renamed to snake_case so that config files
+//! read like the rest of a Rust mod
+
+use crate::{
+    SPMat3, SPMat4, SPParticleEmitterState, SPParticleState, SPVec2, SPVec3, SPVec4,
+    SPWorldGenOptions,
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+macro_rules! forward_to_remote {
+    ($sp_type:ty, $remote:ident) => {
+        impl Serialize for $sp_type {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                $remote::serialize(self, serializer)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $sp_type {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                $remote::deserialize(deserializer)
+            }
+        }
+    };
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "SPVec2")]
+struct SPVec2Def {
+    x: f64,
+    y: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "SPVec3")]
+struct SPVec3Def {
+    x: f64,
+    y: f64,
+    z: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "SPVec4")]
+struct SPVec4Def {
+    x: f64,
+    y: f64,
+    z: f64,
+    w: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "SPMat3")]
+struct SPMat3Def {
+    m0: f64,
+    m1: f64,
+    m2: f64,
+    m3: f64,
+    m4: f64,
+    m5: f64,
+    m6: f64,
+    m7: f64,
+    m8: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "SPMat4")]
+struct SPMat4Def {
+    m0: f64,
+    m1: f64,
+    m2: f64,
+    m3: f64,
+    m4: f64,
+    m5: f64,
+    m6: f64,
+    m7: f64,
+    m8: f64,
+    m9: f64,
+    m10: f64,
+    m11: f64,
+    m12: f64,
+    m13: f64,
+    m14: f64,
+    m15: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "SPWorldGenOptions")]
+struct SPWorldGenOptionsDef {
+    #[serde(rename = "height_offset")]
+    heightOffset: f64,
+    #[serde(rename = "rainfall_multiplier")]
+    rainfallMultiplier: f64,
+    #[serde(rename = "temperature_offset")]
+    temperatureOffset: f64,
+    scales: SPVec3,
+    influences: SPVec3,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "SPParticleEmitterState")]
+struct SPParticleEmitterStateDef {
+    p: SPVec3,
+    rot: SPMat3,
+    #[serde(rename = "time_accumulator_a")]
+    timeAccumulatorA: f64,
+    #[serde(rename = "time_accumulator_b")]
+    timeAccumulatorB: f64,
+    #[serde(rename = "user_data")]
+    userData: SPVec4,
+    #[serde(rename = "global_type")]
+    globalType: u32,
+    counters: [u8; 4],
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "SPParticleState")]
+struct SPParticleStateDef {
+    p: SPVec3,
+    v: SPVec3,
+    gravity: SPVec3,
+    #[serde(rename = "life_left")]
+    lifeLeft: f64,
+    scale: f64,
+    #[serde(rename = "random_value_a")]
+    randomValueA: f64,
+    #[serde(rename = "random_value_b")]
+    randomValueB: f64,
+    #[serde(rename = "user_data")]
+    userData: SPVec4,
+    #[serde(rename = "particle_texture_type")]
+    particleTextureType: u32,
+}
+
+forward_to_remote!(SPVec2, SPVec2Def);
+forward_to_remote!(SPVec3, SPVec3Def);
+forward_to_remote!(SPVec4, SPVec4Def);
+forward_to_remote!(SPMat3, SPMat3Def);
+forward_to_remote!(SPMat4, SPMat4Def);
+forward_to_remote!(SPWorldGenOptions, SPWorldGenOptionsDef);
+forward_to_remote!(SPParticleEmitterState, SPParticleEmitterStateDef);
+forward_to_remote!(SPParticleState, SPParticleStateDef);
@@ -0,0 +1,14 @@
+//! Runs a world-gen mod's hooks over the whole planet and writes what they generate as equirectangular images
+//!
+//! ```text
+//! cargo run --bin sapiens-preview --features preview,png -- target/release/libmy_mod.so --preset arid --out previews
+//! ```
+//!
+//! Hooks the mod doesn't export are filled in with stand-ins, or with the game's own with `--vanilla`. Mods linked in
+//! as an rlib get the same command line from a binary of their own that calls `sp::worldgen::cli::main`
+
+extern crate sapiens_rs;
+
+fn main() {
+    sapiens_rs::sp::worldgen::cli::main(None);
+}
@@ -1,3 +1,9 @@
 pub mod sp;
 
 extern crate num_derive;
+
+/// Re-exported so that mods can use the same tolerances as `assert_vec_near!` without depending on `approx` directly
+pub use sapiens_sys::approx;
+
+#[cfg(feature = "rand_core")]
+pub use rand_core;
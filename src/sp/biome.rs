@@ -0,0 +1,772 @@
+//! Typed inputs and outputs for the biome hooks
+//!
+//! `spBiomeGetSurfaceType` asks which terrain type a point shows. As declared in the SDK's `SPlugins/include/SPBiome.h`,
+//! and as its example implementation does, the hook answers with a terrain type ID taken from one of the
+//! `*TerrainType` fields of the point's entry in the biome type array, not with the index of the field. `SurfaceQuery`
+//! gathers what Sapiens passes in, and `SurfaceType` picks the field, so biome rules can be written as
+//!
+//! ```ignore
+//! if query.is_cliff(0.6) {
+//!     SurfaceType::Rock
+//! } else if query.is_beach_band(sp_meters_to_prerender!(2.0)) {
+//!     SurfaceType::Beach
+//! } else {
+//!     SurfaceType::Normal
+//! }
+//! ```
+//!
+//! and `SurfaceType::terrain_type` turns the answer into the ID Sapiens expects. `SurfaceRules` writes the same thing as
+//! data, `BiomeMap` paints it in an image, and `Vanilla` calls the game's own
+//! biome functions, so a mod can start from their answer
+
+#[cfg(feature = "vanilla")]
+use crate::sp::common::{self, LoadVanillaError};
+use crate::sp::common::{BiomeType, TerrainType};
+use crate::sp::noise::{NoiseRef, NoiseSource};
+use crate::sp::sphere_image::{ImageError, Projection, SphereImage};
+use sapiens_sys::{
+    SPBiomeGetBiomeTypeFunc, SPBiomeGetSurfaceTypeFunc, SPBiomeType, SPNoise, SPVec3,
+};
+use std::convert::TryFrom;
+use std::os::raw::c_int;
+use std::path::Path;
+
+/// Which of a biome's terrain types a point shows
+///
+/// Sapiens doesn't take this directly: `spBiomeGetSurfaceType` returns the terrain type that `terrain_type` picks from
+/// the point's biome type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(sapiens_sys::serde::Serialize, sapiens_sys::serde::Deserialize),
+    serde(crate = "sapiens_sys::serde", rename_all = "snake_case")
+)]
+pub enum SurfaceType {
+    Normal,
+    Secondary,
+    Rock,
+    Beach,
+    VegetationStripped,
+}
+
+impl SurfaceType {
+    /// The terrain type that `biome_type` uses for this part of its surface
+    pub fn terrain_type(self, biome_type: &BiomeType) -> TerrainType {
+        match self {
+            SurfaceType::Normal => biome_type.normal(),
+            SurfaceType::Secondary => biome_type.secondary(),
+            SurfaceType::Rock => biome_type.rock(),
+            SurfaceType::Beach => biome_type.beach(),
+            SurfaceType::VegetationStripped => biome_type.vegetation_stripped(),
+        }
+    }
+}
+
+/// Everything `spBiomeGetSurfaceType` is told about a point, apart from the noise and biome type array
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SurfaceQuery {
+    /// Unit vector from the planet's center to the point
+    pub point_normal: SPVec3,
+
+    /// Where to sample noise for this point
+    pub noise_loc: SPVec3,
+
+    /// Index of the point's biome in the biome type array
+    pub biome_type: c_int,
+
+    /// Passed through as Sapiens gives it. The SDK headers don't say what the values mean, so this crate doesn't name
+    /// them
+    pub vegetation_state: c_int,
+
+    /// Height above sea level, in prerender units. Negative under the sea
+    pub altitude: f64,
+
+    /// How steep the ground is, as Sapiens measures it. Flat ground is `0`
+    pub steepness: f32,
+
+    /// Distance to the nearest river, in prerender units
+    pub river_distance: f32,
+}
+
+impl SurfaceQuery {
+    /// Gathers the arguments of `spBiomeGetSurfaceType`, in the same order
+    pub fn from_sp(
+        point_normal: SPVec3,
+        noise_loc: SPVec3,
+        biome_type: c_int,
+        vegetation_state: c_int,
+        altitude: f64,
+        steepness: f32,
+        river_distance: f32,
+    ) -> Self {
+        SurfaceQuery {
+            point_normal,
+            noise_loc,
+            biome_type,
+            vegetation_state,
+            altitude,
+            steepness,
+            river_distance,
+        }
+    }
+
+    /// Looks up this point's biome in the array Sapiens passed alongside it
+    pub fn biome_type<'a>(&self, biome_types: &'a [BiomeType]) -> Option<&'a BiomeType> {
+        if self.biome_type < 0 {
+            None
+        } else {
+            biome_types.get(self.biome_type as usize)
+        }
+    }
+
+    /// Whether the point is below sea level
+    pub fn is_underwater(&self) -> bool {
+        self.altitude < 0.0
+    }
+
+    /// Whether the point is above the water but less than `max_altitude` above sea level
+    pub fn is_beach_band(&self, max_altitude: f64) -> bool {
+        self.altitude >= 0.0 && self.altitude < max_altitude
+    }
+
+    /// Whether the ground is at least `min_steepness` steep
+    pub fn is_cliff(&self, min_steepness: f32) -> bool {
+        self.steepness >= min_steepness
+    }
+}
+
+/// Noise that a `SurfaceRule` checks, sampled at `noise_loc * frequency`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NoiseThreshold {
+    pub frequency: f64,
+    pub octaves: i32,
+
+    /// The lowest value that passes
+    pub min: f64,
+}
+
+/// A surface type, and the conditions a point has to meet to get it
+///
+/// Conditions left as `None` always pass. Minimums are inclusive and maximums exclusive, like the `SurfaceQuery`
+/// helpers
+#[derive(Debug, Clone, PartialEq)]
+pub struct SurfaceRule {
+    pub surface_type: SurfaceType,
+
+    /// In prerender units
+    pub min_altitude: Option<f64>,
+
+    /// In prerender units
+    pub max_altitude: Option<f64>,
+
+    pub min_steepness: Option<f32>,
+
+    pub max_steepness: Option<f32>,
+
+    /// In prerender units
+    pub max_river_distance: Option<f32>,
+
+    /// The raw `SurfaceQuery::vegetation_state` a point must have
+    pub vegetation_state: Option<c_int>,
+
+    pub noise: Option<NoiseThreshold>,
+}
+
+impl SurfaceRule {
+    /// A rule which gives every point `surface_type`
+    pub fn new(surface_type: SurfaceType) -> Self {
+        SurfaceRule {
+            surface_type,
+            min_altitude: None,
+            max_altitude: None,
+            min_steepness: None,
+            max_steepness: None,
+            max_river_distance: None,
+            vegetation_state: None,
+            noise: None,
+        }
+    }
+
+    pub fn min_altitude(mut self, altitude: f64) -> Self {
+        self.min_altitude = Some(altitude);
+        self
+    }
+
+    pub fn max_altitude(mut self, altitude: f64) -> Self {
+        self.max_altitude = Some(altitude);
+        self
+    }
+
+    pub fn min_steepness(mut self, steepness: f32) -> Self {
+        self.min_steepness = Some(steepness);
+        self
+    }
+
+    pub fn max_steepness(mut self, steepness: f32) -> Self {
+        self.max_steepness = Some(steepness);
+        self
+    }
+
+    pub fn max_river_distance(mut self, distance: f32) -> Self {
+        self.max_river_distance = Some(distance);
+        self
+    }
+
+    pub fn vegetation_state(mut self, vegetation_state: c_int) -> Self {
+        self.vegetation_state = Some(vegetation_state);
+        self
+    }
+
+    pub fn noise(mut self, frequency: f64, octaves: i32, min: f64) -> Self {
+        self.noise = Some(NoiseThreshold {
+            frequency,
+            octaves,
+            min,
+        });
+        self
+    }
+
+    /// Whether a point meets every condition of this rule
+    pub fn matches(&self, noise: &dyn NoiseSource, query: &SurfaceQuery) -> bool {
+        let at_least = |min: Option<f64>, value: f64| min.is_none_or(|min| value >= min);
+        let below = |max: Option<f64>, value: f64| max.is_none_or(|max| value < max);
+
+        at_least(self.min_altitude, query.altitude)
+            && below(self.max_altitude, query.altitude)
+            && at_least(self.min_steepness.map(f64::from), query.steepness as f64)
+            && below(self.max_steepness.map(f64::from), query.steepness as f64)
+            && below(
+                self.max_river_distance.map(f64::from),
+                query.river_distance as f64,
+            )
+            && self
+                .vegetation_state
+                .is_none_or(|vegetation_state| vegetation_state == query.vegetation_state)
+            && self.noise.is_none_or(|threshold| {
+                let loc = query.noise_loc;
+                let pos = SPVec3 {
+                    x: loc.x * threshold.frequency,
+                    y: loc.y * threshold.frequency,
+                    z: loc.z * threshold.frequency,
+                };
+                noise.get(&pos, threshold.octaves) >= threshold.min
+            })
+    }
+}
+
+/// An ordered list of `SurfaceRule`s, where the first rule a point matches picks its surface type
+#[derive(Debug, Clone, PartialEq)]
+pub struct SurfaceRules {
+    pub rules: Vec<SurfaceRule>,
+
+    /// The surface type for points that match no rule
+    pub fallback: SurfaceType,
+}
+
+impl SurfaceRules {
+    /// No rules, so every point gets `SurfaceType::Normal`
+    pub fn new() -> Self {
+        SurfaceRules {
+            rules: Vec::new(),
+            fallback: SurfaceType::Normal,
+        }
+    }
+
+    /// Appends a rule, which is only checked if the ones before it don't match
+    pub fn then(mut self, rule: SurfaceRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    pub fn fallback(mut self, surface_type: SurfaceType) -> Self {
+        self.fallback = surface_type;
+        self
+    }
+
+    /// Picks the surface type for a point
+    pub fn get(&self, noise: &dyn NoiseSource, query: &SurfaceQuery) -> SurfaceType {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(noise, query))
+            .map_or(self.fallback, |rule| rule.surface_type)
+    }
+}
+
+impl Default for SurfaceRules {
+    fn default() -> Self {
+        SurfaceRules::new()
+    }
+}
+
+/// What one color of a `BiomeMap` stands for. `None` leaves the choice to the mod's other rules
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BiomeMapEntry {
+    pub biome_type: Option<c_int>,
+    pub surface_type: Option<SurfaceType>,
+}
+
+/// An indexed-color image wrapped around the planet, where each color stands for a biome and surface type
+///
+/// `spBiomeGetBiomeType` is only told the climate type, not where the point is, so Sapiens can't ask the map for a
+/// biome type directly. `biome_type_at` is for offline tools and for hooks that know the position, like
+/// `spBiomeGetSurfaceType`, whose `SurfaceQuery` carries the point. Pixels aren't blended, as indices aren't amounts
+#[derive(Debug, Clone, PartialEq)]
+pub struct BiomeMap {
+    image: SphereImage<u8>,
+    entries: Vec<BiomeMapEntry>,
+}
+
+impl BiomeMap {
+    /// A map where no color stands for anything yet
+    pub fn new(image: SphereImage<u8>) -> Self {
+        BiomeMap {
+            image,
+            entries: vec![BiomeMapEntry::default(); 256],
+        }
+    }
+
+    /// Loads an indexed `.png` with the `png` feature, or an 8 bit `.pgm` where the gray level is the index
+    pub fn load(path: impl AsRef<Path>, projection: Projection) -> Result<Self, ImageError> {
+        Ok(BiomeMap::new(SphereImage::load_indexed(path, projection)?))
+    }
+
+    pub fn image(&self) -> &SphereImage<u8> {
+        &self.image
+    }
+
+    /// Makes color `index` stand for a biome type, as returned by `spBiomeGetBiomeType`
+    pub fn biome_type(mut self, index: u8, biome_type: c_int) -> Self {
+        self.entries[index as usize].biome_type = Some(biome_type);
+        self
+    }
+
+    /// Makes color `index` stand for a surface type
+    pub fn surface_type(mut self, index: u8, surface_type: SurfaceType) -> Self {
+        self.entries[index as usize].surface_type = Some(surface_type);
+        self
+    }
+
+    pub fn entry(&self, index: u8) -> BiomeMapEntry {
+        self.entries[index as usize]
+    }
+
+    /// The color index where `point_normal` points
+    pub fn index_at(&self, point_normal: &SPVec3) -> u8 {
+        self.image.nearest(point_normal)
+    }
+
+    pub fn biome_type_at(&self, point_normal: &SPVec3) -> Option<c_int> {
+        self.entry(self.index_at(point_normal)).biome_type
+    }
+
+    /// The map's surface type for a point, which a mod can fall back from, e.g. to `SurfaceRules::get`
+    pub fn surface_type_at(&self, query: &SurfaceQuery) -> Option<SurfaceType> {
+        self.entry(self.index_at(&query.point_normal)).surface_type
+    }
+}
+
+type BiomeGetBiomeTypeFn = unsafe extern "C" fn(climate_type: c_int) -> c_int;
+
+type BiomeGetSurfaceTypeFn = unsafe extern "C" fn(
+    noise: *mut SPNoise,
+    biome_types: *mut SPBiomeType,
+    point_normal: SPVec3,
+    noise_loc: SPVec3,
+    biome_type: c_int,
+    vegetation_state: c_int,
+    altitude: f64,
+    steepness: f32,
+    river_distance: f32,
+) -> c_int;
+
+/// The game's own `spBiomeGetBiomeType` and `spBiomeGetSurfaceType`, or stand-ins for them
+#[derive(Debug, Clone, Copy)]
+pub struct Vanilla {
+    get_biome_type: BiomeGetBiomeTypeFn,
+    get_surface_type: BiomeGetSurfaceTypeFn,
+}
+
+impl Vanilla {
+    /// Finds the game's biome functions in SPCommon, with the `vanilla` feature
+    #[cfg(feature = "vanilla")]
+    pub fn load() -> Result<Self, LoadVanillaError> {
+        unsafe {
+            Ok(Vanilla {
+                get_biome_type: common::load_vanilla("spBiomeGetBiomeType")?,
+                get_surface_type: common::load_vanilla("spBiomeGetSurfaceType")?,
+            })
+        }
+    }
+
+    /// Wraps biome functions the mod got some other way, or `None` if either is null
+    ///
+    /// # Safety
+    ///
+    /// The functions must behave like `spBiomeGetBiomeType` and `spBiomeGetSurfaceType`, and be safe to call with any
+    /// valid noise and biome type array
+    pub unsafe fn from_sp(
+        get_biome_type: SPBiomeGetBiomeTypeFunc,
+        get_surface_type: SPBiomeGetSurfaceTypeFunc,
+    ) -> Option<Self> {
+        Some(Vanilla {
+            get_biome_type: get_biome_type?,
+            get_surface_type: get_surface_type?,
+        })
+    }
+
+    /// Simple biome functions for tests and offline tools
+    ///
+    /// The biome type is the climate type. The surface is `Rock` on cliffs at least `0.6` steep, `Beach` up to 2m above
+    /// sea level and `Normal` everywhere else, and the terrain type is the one the point's biome type uses for that
+    /// surface. A point without a biome type gets terrain type `0`, like the generated `spBiomeGetSurfaceType`
+    pub fn stand_in() -> Self {
+        Vanilla {
+            get_biome_type: stand_in_biome_type,
+            get_surface_type: stand_in_surface_type,
+        }
+    }
+
+    /// Picks the biome for a climate type, the same way Sapiens would call `spBiomeGetBiomeType`
+    pub fn biome_type(&self, climate_type: c_int) -> c_int {
+        unsafe { (self.get_biome_type)(climate_type) }
+    }
+
+    /// Picks the terrain type a point shows, the same way Sapiens would call `spBiomeGetSurfaceType`
+    ///
+    /// The function reads the point's entry in `biome_types` without knowing the array's length, so it isn't called
+    /// unless `query.biome_type` is an index into `biome_types`. Returns `None` if it isn't, or if the function answers
+    /// with a negative ID or one that doesn't fit a terrain type field
+    pub fn terrain_type(
+        &self,
+        noise: &NoiseRef,
+        biome_types: &mut [BiomeType],
+        query: &SurfaceQuery,
+    ) -> Option<TerrainType> {
+        query.biome_type(biome_types)?;
+
+        let terrain_type = unsafe {
+            (self.get_surface_type)(
+                noise.as_sp_noise(),
+                biome_types.as_mut_ptr() as *mut SPBiomeType,
+                query.point_normal,
+                query.noise_loc,
+                query.biome_type,
+                query.vegetation_state,
+                query.altitude,
+                query.steepness,
+                query.river_distance,
+            )
+        };
+
+        i16::try_from(terrain_type)
+            .ok()
+            .filter(|id| *id >= 0)
+            .map(TerrainType::from_id)
+    }
+
+    pub fn as_sp_biome_get_biome_type_func(&self) -> SPBiomeGetBiomeTypeFunc {
+        Some(self.get_biome_type)
+    }
+
+    pub fn as_sp_biome_get_surface_type_func(&self) -> SPBiomeGetSurfaceTypeFunc {
+        Some(self.get_surface_type)
+    }
+}
+
+unsafe extern "C" fn stand_in_biome_type(climate_type: c_int) -> c_int {
+    climate_type
+}
+
+#[allow(clippy::too_many_arguments)]
+unsafe extern "C" fn stand_in_surface_type(
+    _noise: *mut SPNoise,
+    biome_types: *mut SPBiomeType,
+    point_normal: SPVec3,
+    noise_loc: SPVec3,
+    biome_type: c_int,
+    vegetation_state: c_int,
+    altitude: f64,
+    steepness: f32,
+    river_distance: f32,
+) -> c_int {
+    let query = SurfaceQuery::from_sp(
+        point_normal,
+        noise_loc,
+        biome_type,
+        vegetation_state,
+        altitude,
+        steepness,
+        river_distance,
+    );
+
+    let surface_type = if query.is_cliff(0.6) {
+        SurfaceType::Rock
+    } else if query.is_beach_band(crate::sp_meters_to_prerender!(2.0)) {
+        SurfaceType::Beach
+    } else {
+        SurfaceType::Normal
+    };
+
+    if biome_types.is_null() || biome_type < 0 {
+        return 0;
+    }
+    let biome_type = BiomeType::from(*biome_types.offset(biome_type as isize));
+    surface_type.terrain_type(&biome_type).id() as c_int
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sp::mock::ConstantNoise;
+    use crate::sp::noise::Noise;
+
+    fn query(altitude: f64, steepness: f32) -> SurfaceQuery {
+        SurfaceQuery::from_sp(
+            SPVec3 {
+                x: 0.0,
+                y: 1.0,
+                z: 0.0,
+            },
+            SPVec3::default(),
+            1,
+            0,
+            altitude,
+            steepness,
+            0.5,
+        )
+    }
+
+    /// A biome type with a different terrain type for each part of its surface, numbered like `SurfaceType`'s
+    /// variants from `1`
+    fn layered() -> BiomeType {
+        BiomeType::new(
+            TerrainType::from_id(1),
+            TerrainType::from_id(2),
+            TerrainType::from_id(3),
+            TerrainType::from_id(4),
+            TerrainType::from_id(5),
+        )
+    }
+
+    #[test]
+    fn surface_types_pick_terrain() {
+        let biome_type = layered();
+
+        assert_eq!(
+            SurfaceType::Normal.terrain_type(&biome_type),
+            TerrainType::from_id(1)
+        );
+        assert_eq!(
+            SurfaceType::Secondary.terrain_type(&biome_type),
+            TerrainType::from_id(2)
+        );
+        assert_eq!(
+            SurfaceType::Rock.terrain_type(&biome_type),
+            TerrainType::from_id(3)
+        );
+        assert_eq!(
+            SurfaceType::Beach.terrain_type(&biome_type),
+            TerrainType::from_id(4)
+        );
+        assert_eq!(
+            SurfaceType::VegetationStripped.terrain_type(&biome_type),
+            TerrainType::from_id(5)
+        );
+    }
+
+    #[test]
+    fn altitude_rules() {
+        let beach = crate::sp_meters_to_prerender!(2.0);
+
+        assert!(query(-0.001, 0.0).is_underwater());
+        assert!(!query(-0.001, 0.0).is_beach_band(beach));
+        assert!(query(0.0, 0.0).is_beach_band(beach));
+        assert!(!query(0.0, 0.0).is_underwater());
+        assert!(!query(beach, 0.0).is_beach_band(beach));
+    }
+
+    #[test]
+    fn steepness_rules() {
+        assert!(query(0.1, 0.7).is_cliff(0.6));
+        assert!(query(0.1, 0.6).is_cliff(0.6));
+        assert!(!query(0.1, 0.5).is_cliff(0.6));
+    }
+
+    #[test]
+    fn looks_up_biome_type() {
+        let biome_types = [BiomeType::default(), BiomeType::default()];
+
+        assert!(query(0.0, 0.0).biome_type(&biome_types).is_some());
+        assert!(SurfaceQuery {
+            biome_type: 2,
+            ..query(0.0, 0.0)
+        }
+        .biome_type(&biome_types)
+        .is_none());
+        assert!(SurfaceQuery {
+            biome_type: -1,
+            ..query(0.0, 0.0)
+        }
+        .biome_type(&biome_types)
+        .is_none());
+    }
+
+    const SNOW: TerrainType = TerrainType::from_id(6);
+
+    /// Answers with the rock of snowy biomes, and an invalid terrain type anywhere else
+    #[allow(clippy::too_many_arguments)]
+    unsafe extern "C" fn rock_from_array(
+        _noise: *mut SPNoise,
+        biome_types: *mut SPBiomeType,
+        _point_normal: SPVec3,
+        _noise_loc: SPVec3,
+        biome_type: c_int,
+        _vegetation_state: c_int,
+        _altitude: f64,
+        _steepness: f32,
+        _river_distance: f32,
+    ) -> c_int {
+        let biome_type = *biome_types.offset(biome_type as isize);
+        if biome_type.rockTerrainType == SNOW.id() {
+            biome_type.rockTerrainType as c_int
+        } else {
+            -1
+        }
+    }
+
+    unsafe extern "C" fn next_biome(climate_type: c_int) -> c_int {
+        climate_type + 1
+    }
+
+    #[test]
+    fn vanilla_sees_the_biome_types() {
+        let noise = Noise::new(1, 0.5);
+        let vanilla = unsafe { Vanilla::from_sp(Some(next_biome), Some(rock_from_array)) }.unwrap();
+        let mut biome_types = [BiomeType::default(), BiomeType::default()];
+        biome_types[1].set_rock(SNOW);
+
+        assert_eq!(vanilla.biome_type(3), 4);
+        assert_eq!(
+            vanilla.terrain_type(&noise.handle(), &mut biome_types, &query(0.0, 0.0)),
+            Some(SNOW)
+        );
+
+        biome_types[1] = BiomeType::default();
+        assert_eq!(
+            vanilla.terrain_type(&noise.handle(), &mut biome_types, &query(0.0, 0.0)),
+            None
+        );
+        assert!(unsafe { Vanilla::from_sp(Some(next_biome), None) }.is_none());
+    }
+
+    #[test]
+    fn vanilla_is_not_called_with_biome_types_out_of_range() {
+        let noise = Noise::new(1, 0.5);
+        let vanilla = unsafe { Vanilla::from_sp(Some(next_biome), Some(rock_from_array)) }.unwrap();
+        let mut biome_types = [BiomeType::default()];
+        biome_types[0].set_rock(SNOW);
+        let terrain_type = |biome_type: c_int, biome_types: &mut [BiomeType]| {
+            let query = SurfaceQuery {
+                biome_type,
+                ..query(0.0, 0.0)
+            };
+            vanilla.terrain_type(&noise.handle(), biome_types, &query)
+        };
+
+        assert_eq!(terrain_type(0, &mut biome_types), Some(SNOW));
+        assert_eq!(terrain_type(1, &mut biome_types), None);
+        assert_eq!(terrain_type(-1, &mut biome_types), None);
+        assert_eq!(terrain_type(0, &mut []), None);
+    }
+
+    #[test]
+    fn stand_in_surface_rules() {
+        let noise = Noise::new(1, 0.5);
+        let vanilla = Vanilla::stand_in();
+        let mut biome_types = [BiomeType::default(), layered()];
+        let mut terrain_type = |query: SurfaceQuery| {
+            vanilla
+                .terrain_type(&noise.handle(), &mut biome_types, &query)
+                .map(TerrainType::id)
+        };
+
+        assert_eq!(vanilla.biome_type(3), 3);
+        assert_eq!(terrain_type(query(0.1, 0.0)), Some(1));
+        assert_eq!(terrain_type(query(0.1, 0.8)), Some(3));
+        assert_eq!(terrain_type(query(0.0, 0.0)), Some(4));
+    }
+
+    #[test]
+    fn first_matching_rule_wins() {
+        let rules = SurfaceRules::new()
+            .then(SurfaceRule::new(SurfaceType::VegetationStripped).vegetation_state(1))
+            .then(SurfaceRule::new(SurfaceType::Rock).min_steepness(0.6))
+            .then(
+                SurfaceRule::new(SurfaceType::Beach)
+                    .min_altitude(0.0)
+                    .max_altitude(0.01),
+            )
+            .fallback(SurfaceType::Secondary);
+        let noise = ConstantNoise(0.0);
+
+        assert_eq!(rules.get(&noise, &query(0.005, 0.8)), SurfaceType::Rock);
+        assert_eq!(rules.get(&noise, &query(0.005, 0.1)), SurfaceType::Beach);
+        assert_eq!(rules.get(&noise, &query(0.5, 0.1)), SurfaceType::Secondary);
+        assert_eq!(rules.get(&noise, &query(-0.5, 0.1)), SurfaceType::Secondary);
+        assert_eq!(
+            rules.get(
+                &noise,
+                &SurfaceQuery {
+                    vegetation_state: 1,
+                    ..query(0.005, 0.8)
+                }
+            ),
+            SurfaceType::VegetationStripped
+        );
+    }
+
+    #[test]
+    fn rules_check_river_distance_and_noise() {
+        let near_river = SurfaceRule::new(SurfaceType::Secondary).max_river_distance(1.0);
+        let patchy = SurfaceRule::new(SurfaceType::Secondary).noise(2.0, 3, 0.25);
+
+        // query() is 0.5 from a river
+        assert!(near_river.matches(&ConstantNoise(0.0), &query(0.1, 0.0)));
+        assert!(!near_river
+            .max_river_distance(0.5)
+            .matches(&ConstantNoise(0.0), &query(0.1, 0.0)));
+        assert!(patchy.matches(&ConstantNoise(0.25), &query(0.1, 0.0)));
+        assert!(!patchy.matches(&ConstantNoise(0.2), &query(0.1, 0.0)));
+        assert_eq!(
+            SurfaceRules::default().get(&ConstantNoise(0.0), &query(0.1, 0.0)),
+            SurfaceType::Normal
+        );
+    }
+
+    #[test]
+    fn biome_map_looks_up_colors() {
+        // North pole row is color 1, the rest color 2
+        let image = SphereImage::new(
+            3,
+            3,
+            Projection::Equirectangular,
+            vec![1, 1, 1, 2, 2, 2, 2, 2, 2],
+        )
+        .unwrap();
+        let map = BiomeMap::new(image)
+            .biome_type(1, 7)
+            .surface_type(1, SurfaceType::Rock)
+            .biome_type(2, 3);
+
+        let north = query(0.0, 0.0);
+        let mut south = north;
+        south.point_normal.y = -1.0;
+
+        assert_eq!(map.index_at(&north.point_normal), 1);
+        assert_eq!(map.biome_type_at(&north.point_normal), Some(7));
+        assert_eq!(map.surface_type_at(&north), Some(SurfaceType::Rock));
+        assert_eq!(map.biome_type_at(&south.point_normal), Some(3));
+        assert_eq!(map.surface_type_at(&south), None);
+        assert_eq!(map.entry(9), BiomeMapEntry::default());
+    }
+}
@@ -0,0 +1,115 @@
+//! A handle to the game's own climate function
+//!
+//! `spClimateGet` turns each season's temperature and rainfall into a climate type, which `spBiomeGetBiomeType` then
+//! turns into a biome
+
+#[cfg(feature = "vanilla")]
+use crate::sp::common::{self, LoadVanillaError};
+use sapiens_sys::SPClimateGetFunc;
+use std::os::raw::c_int;
+
+type ClimateGetFn = unsafe extern "C" fn(
+    temperature_summer: f32,
+    temperature_winter: f32,
+    rainfall_summer: f32,
+    rainfall_winter: f32,
+) -> c_int;
+
+/// The game's own `spClimateGet`, or a stand-in for it
+#[derive(Debug, Clone, Copy)]
+pub struct Vanilla(ClimateGetFn);
+
+impl Vanilla {
+    /// Finds the game's `spClimateGet` in SPCommon, with the `vanilla` feature
+    #[cfg(feature = "vanilla")]
+    pub fn load() -> Result<Self, LoadVanillaError> {
+        unsafe { common::load_vanilla("spClimateGet").map(Vanilla) }
+    }
+
+    /// Wraps a climate function the mod got some other way, or `None` if it's null
+    ///
+    /// # Safety
+    ///
+    /// `func` must behave like `spClimateGet`, and be safe to call with any temperature and rainfall
+    pub unsafe fn from_sp(func: SPClimateGetFunc) -> Option<Self> {
+        func.map(Vanilla)
+    }
+
+    /// A simple climate function for tests and offline tools
+    ///
+    /// Its climate types aren't the game's. It returns `0` (polar) when the average temperature is below freezing, `1`
+    /// (arid) when the average rainfall is below 25, `2` (temperate) when the average temperature is below 20°C and
+    /// `3` (tropical) otherwise
+    pub fn stand_in() -> Self {
+        Vanilla(stand_in_climate)
+    }
+
+    /// Picks the climate type for a point, the same way Sapiens would call the function
+    pub fn get(
+        &self,
+        temperature_summer: f32,
+        temperature_winter: f32,
+        rainfall_summer: f32,
+        rainfall_winter: f32,
+    ) -> c_int {
+        unsafe {
+            (self.0)(
+                temperature_summer,
+                temperature_winter,
+                rainfall_summer,
+                rainfall_winter,
+            )
+        }
+    }
+
+    pub fn as_sp_climate_get_func(&self) -> SPClimateGetFunc {
+        Some(self.0)
+    }
+}
+
+unsafe extern "C" fn stand_in_climate(
+    temperature_summer: f32,
+    temperature_winter: f32,
+    rainfall_summer: f32,
+    rainfall_winter: f32,
+) -> c_int {
+    let temperature = (temperature_summer + temperature_winter) / 2.0;
+    let rainfall = (rainfall_summer + rainfall_winter) / 2.0;
+
+    if temperature < 0.0 {
+        0
+    } else if rainfall < 25.0 {
+        1
+    } else if temperature < 20.0 {
+        2
+    } else {
+        3
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    unsafe extern "C" fn always_seven(_: f32, _: f32, _: f32, _: f32) -> c_int {
+        7
+    }
+
+    #[test]
+    fn calls_the_wrapped_function() {
+        let vanilla = unsafe { Vanilla::from_sp(Some(always_seven)) }.unwrap();
+
+        assert_eq!(vanilla.get(10.0, 0.0, 50.0, 50.0), 7);
+        assert!(unsafe { Vanilla::from_sp(None) }.is_none());
+    }
+
+    #[test]
+    fn stand_in_bands() {
+        let vanilla = Vanilla::stand_in();
+
+        assert_eq!(vanilla.get(5.0, -20.0, 100.0, 100.0), 0);
+        assert_eq!(vanilla.get(35.0, 25.0, 10.0, 0.0), 1);
+        assert_eq!(vanilla.get(20.0, 0.0, 60.0, 40.0), 2);
+        assert_eq!(vanilla.get(30.0, 24.0, 200.0, 80.0), 3);
+    }
+}
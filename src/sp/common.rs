@@ -1,5 +1,985 @@
-use sapiens_sys::{SPWorldGenOptions, SPBiomeType};
+#[cfg(feature = "vanilla")]
+use libloading::Library;
+use sapiens_sys::{SPBiomeType, SPVec3, SPWorldGenOptions};
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::os::raw::c_int;
+use std::slice;
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(feature = "vanilla")]
+use std::sync::OnceLock;
+use std::sync::{PoisonError, RwLock};
 
+/// The settings a player picks when creating a world, as passed to the world-gen hooks
+///
+/// `scales` and `influences` hold one value per layer of terrain noise. The SDK headers only declare them as `SPVec3`s,
+/// so which axis is which layer is this crate's reading of how the game's sliders behave, not a documented contract:
+///
+/// | Axis | Layer       | Size of features                  |
+/// |------|-------------|-----------------------------------|
+/// | `x`  | continents  | Whole landmasses and ocean basins |
+/// | `y`  | mountains   | Ranges and valleys                |
+/// | `z`  | hills       | Local bumps and detail            |
+///
+/// A layer's scale multiplies the frequency of its noise, so larger scales give smaller, more numerous features. Its
+/// influence multiplies how much it adds to the height, so `0` flattens the layer out entirely. Both are `1` in vanilla
+///
+/// The `*_RANGE` constants are likewise only this crate's idea of typical values, which `unusual_options` reports
+/// leaving. The game doesn't publish the bounds of its sliders, so `validate` only rejects values that aren't numbers
+///
+/// `Default` is the vanilla preset, not the all-zero `SPWorldGenOptions::default()`
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(sapiens_sys::serde::Serialize, sapiens_sys::serde::Deserialize),
+    serde(crate = "sapiens_sys::serde", transparent)
+)]
 pub struct WorldGenOptions(SPWorldGenOptions);
 
-pub struct BiomeType(SPBiomeType);
\ No newline at end of file
+impl WorldGenOptions {
+    /// Typical values for `height_offset`. Like the other ranges, an assumption of this crate rather than the game's limit
+    pub const HEIGHT_OFFSET_RANGE: RangeInclusive<f64> = -1.0..=1.0;
+
+    /// Typical values for `rainfall_multiplier`
+    pub const RAINFALL_MULTIPLIER_RANGE: RangeInclusive<f64> = 0.0..=10.0;
+
+    /// Typical values for `temperature_offset`
+    pub const TEMPERATURE_OFFSET_RANGE: RangeInclusive<f64> = -50.0..=50.0;
+
+    /// Typical values for each scale. Zero would stretch a layer into a single flat value
+    pub const SCALE_RANGE: RangeInclusive<f64> = 0.01..=100.0;
+
+    /// Typical values for each influence
+    pub const INFLUENCE_RANGE: RangeInclusive<f64> = 0.0..=100.0;
+
+    /// No offsets and every multiplier at `1`, which this crate assumes is a world created with every slider left alone
+    pub fn vanilla() -> Self {
+        WorldGenOptions(SPWorldGenOptions {
+            heightOffset: 0.0,
+            rainfallMultiplier: 1.0,
+            temperatureOffset: 0.0,
+            scales: SPVec3 {
+                x: 1.0,
+                y: 1.0,
+                z: 1.0,
+            },
+            influences: SPVec3 {
+                x: 1.0,
+                y: 1.0,
+                z: 1.0,
+            },
+        })
+    }
+
+    /// Lots of small islands in a wide, wet ocean
+    pub fn archipelago() -> Self {
+        WorldGenOptions::builder()
+            .height_offset(-0.2)
+            .rainfall_multiplier(1.3)
+            .temperature_offset(2.0)
+            .continent_scale(3.0)
+            .continent_influence(0.6)
+            .mountain_influence(0.7)
+            .hill_influence(1.2)
+            .build()
+            .unwrap()
+    }
+
+    /// Hot, dry and mostly land, with flat plains between the mountains
+    pub fn arid() -> Self {
+        WorldGenOptions::builder()
+            .height_offset(0.15)
+            .rainfall_multiplier(0.3)
+            .temperature_offset(8.0)
+            .continent_scale(0.7)
+            .hill_influence(0.6)
+            .build()
+            .unwrap()
+    }
+
+    /// Starts from the vanilla options
+    pub fn builder() -> WorldGenOptionsBuilder {
+        WorldGenOptionsBuilder(WorldGenOptions::vanilla())
+    }
+
+    pub fn from_sp(options: SPWorldGenOptions) -> Self {
+        WorldGenOptions(options)
+    }
+
+    pub fn as_sp_world_gen_options(&self) -> &SPWorldGenOptions {
+        &self.0
+    }
+
+    /// Raises or lowers all terrain relative to sea level. Negative values give more ocean
+    pub fn height_offset(&self) -> f64 {
+        self.0.heightOffset
+    }
+
+    pub fn set_height_offset(&mut self, height_offset: f64) {
+        self.0.heightOffset = height_offset;
+    }
+
+    /// Multiplies rainfall everywhere. `1` is vanilla, `0` is a desert planet
+    pub fn rainfall_multiplier(&self) -> f64 {
+        self.0.rainfallMultiplier
+    }
+
+    pub fn set_rainfall_multiplier(&mut self, rainfall_multiplier: f64) {
+        self.0.rainfallMultiplier = rainfall_multiplier;
+    }
+
+    /// Added to the temperature everywhere, in degrees Celsius
+    pub fn temperature_offset(&self) -> f64 {
+        self.0.temperatureOffset
+    }
+
+    pub fn set_temperature_offset(&mut self, temperature_offset: f64) {
+        self.0.temperatureOffset = temperature_offset;
+    }
+
+    /// `scales.x`, the frequency multiplier for continents
+    pub fn continent_scale(&self) -> f64 {
+        self.0.scales.x
+    }
+
+    pub fn set_continent_scale(&mut self, scale: f64) {
+        self.0.scales.x = scale;
+    }
+
+    /// `scales.y`, the frequency multiplier for mountains
+    pub fn mountain_scale(&self) -> f64 {
+        self.0.scales.y
+    }
+
+    pub fn set_mountain_scale(&mut self, scale: f64) {
+        self.0.scales.y = scale;
+    }
+
+    /// `scales.z`, the frequency multiplier for hills
+    pub fn hill_scale(&self) -> f64 {
+        self.0.scales.z
+    }
+
+    pub fn set_hill_scale(&mut self, scale: f64) {
+        self.0.scales.z = scale;
+    }
+
+    /// `influences.x`, how much continents add to the height
+    pub fn continent_influence(&self) -> f64 {
+        self.0.influences.x
+    }
+
+    pub fn set_continent_influence(&mut self, influence: f64) {
+        self.0.influences.x = influence;
+    }
+
+    /// `influences.y`, how much mountains add to the height
+    pub fn mountain_influence(&self) -> f64 {
+        self.0.influences.y
+    }
+
+    pub fn set_mountain_influence(&mut self, influence: f64) {
+        self.0.influences.y = influence;
+    }
+
+    /// `influences.z`, how much hills add to the height
+    pub fn hill_influence(&self) -> f64 {
+        self.0.influences.z
+    }
+
+    pub fn set_hill_influence(&mut self, influence: f64) {
+        self.0.influences.z = influence;
+    }
+
+    /// Checks that every option is a finite number, reporting the first one that isn't
+    ///
+    /// Sapiens doesn't publish the ranges it accepts, so this doesn't check them. See `unusual_options`
+    pub fn validate(&self) -> Result<(), InvalidWorldGenOption> {
+        match self
+            .options()
+            .iter()
+            .find(|(_, value, _)| !value.is_finite())
+        {
+            Some((option, value, _)) => Err(InvalidWorldGenOption {
+                option,
+                value: *value,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// The options outside this crate's `*_RANGE`s, which are worth a warning but may well be what the player picked
+    pub fn unusual_options(&self) -> Vec<UnusualWorldGenOption> {
+        self.options()
+            .iter()
+            .filter(|(_, value, typical)| !typical.contains(value))
+            .cloned()
+            .map(|(option, value, typical)| UnusualWorldGenOption {
+                option,
+                value,
+                typical,
+            })
+            .collect()
+    }
+
+    fn options(&self) -> [(&'static str, f64, RangeInclusive<f64>); 9] {
+        [
+            (
+                "height_offset",
+                self.height_offset(),
+                WorldGenOptions::HEIGHT_OFFSET_RANGE,
+            ),
+            (
+                "rainfall_multiplier",
+                self.rainfall_multiplier(),
+                WorldGenOptions::RAINFALL_MULTIPLIER_RANGE,
+            ),
+            (
+                "temperature_offset",
+                self.temperature_offset(),
+                WorldGenOptions::TEMPERATURE_OFFSET_RANGE,
+            ),
+            (
+                "continent_scale",
+                self.continent_scale(),
+                WorldGenOptions::SCALE_RANGE,
+            ),
+            (
+                "mountain_scale",
+                self.mountain_scale(),
+                WorldGenOptions::SCALE_RANGE,
+            ),
+            (
+                "hill_scale",
+                self.hill_scale(),
+                WorldGenOptions::SCALE_RANGE,
+            ),
+            (
+                "continent_influence",
+                self.continent_influence(),
+                WorldGenOptions::INFLUENCE_RANGE,
+            ),
+            (
+                "mountain_influence",
+                self.mountain_influence(),
+                WorldGenOptions::INFLUENCE_RANGE,
+            ),
+            (
+                "hill_influence",
+                self.hill_influence(),
+                WorldGenOptions::INFLUENCE_RANGE,
+            ),
+        ]
+    }
+}
+
+impl Default for WorldGenOptions {
+    fn default() -> Self {
+        WorldGenOptions::vanilla()
+    }
+}
+
+impl From<SPWorldGenOptions> for WorldGenOptions {
+    fn from(options: SPWorldGenOptions) -> Self {
+        WorldGenOptions(options)
+    }
+}
+
+impl From<WorldGenOptions> for SPWorldGenOptions {
+    fn from(options: WorldGenOptions) -> Self {
+        options.0
+    }
+}
+
+/// Builds a `WorldGenOptions`, checking the values are numbers once they're all set
+///
+/// ```ignore
+/// let options = WorldGenOptions::builder()
+///     .rainfall_multiplier(0.5)
+///     .mountain_scale(2.0)
+///     .build()?;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WorldGenOptionsBuilder(WorldGenOptions);
+
+impl WorldGenOptionsBuilder {
+    pub fn height_offset(mut self, height_offset: f64) -> Self {
+        self.0.set_height_offset(height_offset);
+        self
+    }
+
+    pub fn rainfall_multiplier(mut self, rainfall_multiplier: f64) -> Self {
+        self.0.set_rainfall_multiplier(rainfall_multiplier);
+        self
+    }
+
+    pub fn temperature_offset(mut self, temperature_offset: f64) -> Self {
+        self.0.set_temperature_offset(temperature_offset);
+        self
+    }
+
+    pub fn continent_scale(mut self, scale: f64) -> Self {
+        self.0.set_continent_scale(scale);
+        self
+    }
+
+    pub fn mountain_scale(mut self, scale: f64) -> Self {
+        self.0.set_mountain_scale(scale);
+        self
+    }
+
+    pub fn hill_scale(mut self, scale: f64) -> Self {
+        self.0.set_hill_scale(scale);
+        self
+    }
+
+    pub fn continent_influence(mut self, influence: f64) -> Self {
+        self.0.set_continent_influence(influence);
+        self
+    }
+
+    pub fn mountain_influence(mut self, influence: f64) -> Self {
+        self.0.set_mountain_influence(influence);
+        self
+    }
+
+    pub fn hill_influence(mut self, influence: f64) -> Self {
+        self.0.set_hill_influence(influence);
+        self
+    }
+
+    pub fn build(self) -> Result<WorldGenOptions, InvalidWorldGenOption> {
+        self.0.validate()?;
+        Ok(self.0)
+    }
+}
+
+impl From<WorldGenOptions> for WorldGenOptionsBuilder {
+    /// Starts from existing options, e.g. a preset
+    fn from(options: WorldGenOptions) -> Self {
+        WorldGenOptionsBuilder(options)
+    }
+}
+
+/// A world-gen option which isn't a finite number
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidWorldGenOption {
+    /// The option's name, as used by its getter
+    pub option: &'static str,
+    pub value: f64,
+}
+
+impl fmt::Display for InvalidWorldGenOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} is {}, but must be a finite number",
+            self.option, self.value
+        )
+    }
+}
+
+/// A world-gen option outside the range this crate expects, from `WorldGenOptions::unusual_options`
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnusualWorldGenOption {
+    /// The option's name, as used by its getter
+    pub option: &'static str,
+    pub value: f64,
+    pub typical: RangeInclusive<f64>,
+}
+
+impl fmt::Display for UnusualWorldGenOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} is {}, outside the typical {} to {}",
+            self.option,
+            self.value,
+            self.typical.start(),
+            self.typical.end()
+        )
+    }
+}
+
+impl Error for InvalidWorldGenOption {}
+
+/// A kind of ground, as stored in the terrain type fields of a `BiomeType`
+///
+/// This is just the ID. The game numbers vanilla and modded terrain types alike as it loads them, and the SDK doesn't
+/// publish a fixed numbering, so there are no built-in names. To refer to terrain types by name, register the names and
+/// IDs the game assigned with `register_terrain_type` when the mod loads, then look them up with `from_name`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(sapiens_sys::serde::Serialize, sapiens_sys::serde::Deserialize),
+    serde(crate = "sapiens_sys::serde", transparent)
+)]
+pub struct TerrainType(i16);
+
+static TERRAIN_TYPES: RwLock<Vec<(i16, String)>> = RwLock::new(Vec::new());
+
+impl TerrainType {
+    pub const fn from_id(id: i16) -> Self {
+        TerrainType(id)
+    }
+
+    pub const fn id(self) -> i16 {
+        self.0
+    }
+
+    /// Looks up a terrain type registered with `register_terrain_type`
+    pub fn from_name(name: &str) -> Option<Self> {
+        TERRAIN_TYPES
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .find(|(_, registered)| registered == name)
+            .map(|(id, _)| TerrainType(*id))
+    }
+
+    /// The name registered for this terrain type, or `None` if it hasn't been registered
+    pub fn name(self) -> Option<String> {
+        TERRAIN_TYPES
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .find(|(id, _)| *id == self.0)
+            .map(|(_, name)| name.clone())
+    }
+}
+
+impl From<i16> for TerrainType {
+    fn from(id: i16) -> Self {
+        TerrainType::from_id(id)
+    }
+}
+
+impl From<TerrainType> for i16 {
+    fn from(terrain_type: TerrainType) -> Self {
+        terrain_type.id()
+    }
+}
+
+/// Gives the terrain type the game loaded with `id` a name, so that `TerrainType::from_name` can find it
+///
+/// Registering the same name and ID again does nothing
+pub fn register_terrain_type(name: &str, id: i16) -> Result<TerrainType, TerrainTypeError> {
+    let mut registered = TERRAIN_TYPES
+        .write()
+        .unwrap_or_else(PoisonError::into_inner);
+
+    match registered
+        .iter()
+        .find(|(registered_id, registered_name)| *registered_id == id || registered_name == name)
+    {
+        Some((registered_id, registered_name))
+            if *registered_id == id && registered_name == name => {}
+        Some((registered_id, _)) if *registered_id == id => {
+            return Err(TerrainTypeError::IdTaken(id))
+        }
+        Some(_) => return Err(TerrainTypeError::NameTaken(name.to_string())),
+        None => registered.push((id, name.to_string())),
+    }
+
+    Ok(TerrainType(id))
+}
+
+/// Why `register_terrain_type` refused a terrain type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TerrainTypeError {
+    /// Another terrain type already has this ID
+    IdTaken(i16),
+
+    /// Another terrain type already has this name
+    NameTaken(String),
+}
+
+impl fmt::Display for TerrainTypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TerrainTypeError::IdTaken(id) => {
+                write!(f, "Terrain type ID {} is already registered", id)
+            }
+            TerrainTypeError::NameTaken(name) => {
+                write!(f, "Terrain type {} is already registered", name)
+            }
+        }
+    }
+}
+
+impl Error for TerrainTypeError {}
+
+/// The terrain types a biome uses for the different parts of its surface
+///
+/// This has the same layout as `SPBiomeType`, so the game's arrays of them can be viewed with `BiomeTypes`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct BiomeType(SPBiomeType);
+
+impl BiomeType {
+    pub fn new(
+        normal: TerrainType,
+        secondary: TerrainType,
+        rock: TerrainType,
+        beach: TerrainType,
+        vegetation_stripped: TerrainType,
+    ) -> Self {
+        BiomeType(SPBiomeType {
+            normalTerrainType: normal.id(),
+            secondaryTerrainType: secondary.id(),
+            rockTerrainType: rock.id(),
+            beachTerrainType: beach.id(),
+            vegetationStrippedTerrainType: vegetation_stripped.id(),
+            _padding: [0; 3],
+        })
+    }
+
+    pub fn as_sp_biome_type(&self) -> &SPBiomeType {
+        &self.0
+    }
+
+    /// The ground most of the biome is covered in
+    pub fn normal(&self) -> TerrainType {
+        TerrainType::from_id(self.0.normalTerrainType)
+    }
+
+    pub fn set_normal(&mut self, terrain_type: TerrainType) {
+        self.0.normalTerrainType = terrain_type.id();
+    }
+
+    /// Patches of different ground mixed in with the normal terrain
+    pub fn secondary(&self) -> TerrainType {
+        TerrainType::from_id(self.0.secondaryTerrainType)
+    }
+
+    pub fn set_secondary(&mut self, terrain_type: TerrainType) {
+        self.0.secondaryTerrainType = terrain_type.id();
+    }
+
+    /// Exposed ground on slopes too steep for soil
+    pub fn rock(&self) -> TerrainType {
+        TerrainType::from_id(self.0.rockTerrainType)
+    }
+
+    pub fn set_rock(&mut self, terrain_type: TerrainType) {
+        self.0.rockTerrainType = terrain_type.id();
+    }
+
+    /// Ground near sea level
+    pub fn beach(&self) -> TerrainType {
+        TerrainType::from_id(self.0.beachTerrainType)
+    }
+
+    pub fn set_beach(&mut self, terrain_type: TerrainType) {
+        self.0.beachTerrainType = terrain_type.id();
+    }
+
+    /// What's left once the vegetation has been cleared away
+    pub fn vegetation_stripped(&self) -> TerrainType {
+        TerrainType::from_id(self.0.vegetationStrippedTerrainType)
+    }
+
+    pub fn set_vegetation_stripped(&mut self, terrain_type: TerrainType) {
+        self.0.vegetationStrippedTerrainType = terrain_type.id();
+    }
+}
+
+impl From<SPBiomeType> for BiomeType {
+    fn from(biome_type: SPBiomeType) -> Self {
+        BiomeType(biome_type)
+    }
+}
+
+impl From<BiomeType> for SPBiomeType {
+    fn from(biome_type: BiomeType) -> Self {
+        biome_type.0
+    }
+}
+
+/// The game's SPCommon library, opened the first time a vanilla hook is looked up and kept open from then on
+#[cfg(feature = "vanilla")]
+static SP_COMMON: OnceLock<Result<Library, String>> = OnceLock::new();
+
+/// Looks up one of the game's own world-gen functions in SPCommon, for the `Vanilla` handles
+///
+/// This goes through the dynamic loader instead of the externs in `sapiens-sys`, because a mod which exports a hook
+/// under the same name would otherwise link that name to itself
+///
+/// # Safety
+///
+/// `F` must be the function pointer type that `symbol` is declared with
+#[cfg(feature = "vanilla")]
+pub(crate) unsafe fn load_vanilla<F: Copy>(symbol: &'static str) -> Result<F, LoadVanillaError> {
+    let library = SP_COMMON
+        .get_or_init(|| {
+            Library::new(libloading::library_filename("SPCommon"))
+                .map_err(|error| error.to_string())
+        })
+        .as_ref()
+        .map_err(|message| LoadVanillaError {
+            symbol,
+            message: message.clone(),
+        })?;
+
+    library
+        .get::<F>(symbol.as_bytes())
+        .map(|function| *function)
+        .map_err(|error| LoadVanillaError {
+            symbol,
+            message: error.to_string(),
+        })
+}
+
+/// Why a `Vanilla` handle couldn't find the game's implementation of a hook
+#[cfg(feature = "vanilla")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadVanillaError {
+    /// The hook that was looked up, e.g. `spHeightGet`
+    pub symbol: &'static str,
+
+    /// What the dynamic loader said
+    pub message: String,
+}
+
+#[cfg(feature = "vanilla")]
+impl fmt::Display for LoadVanillaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Could not load the vanilla {} from SPCommon: {}",
+            self.symbol, self.message
+        )
+    }
+}
+
+#[cfg(feature = "vanilla")]
+impl Error for LoadVanillaError {}
+
+static BIOME_TYPE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Tells the hook wrappers how many biome types the game loaded, so `BiomeTypes` can offer the whole array
+///
+/// Sapiens passes `spBiomeGetSurfaceType` the array without its length, so until this is called only the point's own
+/// biome type can be read from it. `0` forgets the count again
+pub fn set_biome_type_count(count: usize) {
+    BIOME_TYPE_COUNT.store(count, Ordering::Relaxed);
+}
+
+/// The count given to `set_biome_type_count`, or `None` if there hasn't been one
+pub fn biome_type_count() -> Option<usize> {
+    match BIOME_TYPE_COUNT.load(Ordering::Relaxed) {
+        0 => None,
+        count => Some(count),
+    }
+}
+
+/// The biome types Sapiens passes to `spBiomeGetSurfaceType`, and the index of the point's own one among them
+#[derive(Debug, Clone, Copy)]
+pub struct BiomeTypes<'a> {
+    own: Option<&'a BiomeType>,
+    all: Option<&'a [BiomeType]>,
+}
+
+impl<'a> BiomeTypes<'a> {
+    /// Views the array Sapiens passed, as long as `set_biome_type_count` says it is
+    ///
+    /// A null `biome_types` has no biome types, and a negative `own_index` leaves the point without one
+    ///
+    /// # Safety
+    ///
+    /// Unless `biome_types` is null, it must point to the biome type at `own_index`, and to at least as many as
+    /// `biome_type_count` if that is set. They must stay valid and unchanged for `'a`
+    pub unsafe fn from_raw(biome_types: *const SPBiomeType, own_index: c_int) -> Self {
+        if biome_types.is_null() {
+            return BiomeTypes {
+                own: None,
+                all: None,
+            };
+        }
+
+        match biome_type_count() {
+            Some(count) => BiomeTypes::from_slice(
+                slice::from_raw_parts(biome_types as *const BiomeType, count),
+                own_index,
+            ),
+            None => BiomeTypes {
+                own: usize::try_from(own_index)
+                    .ok()
+                    .map(|index| &*(biome_types.add(index) as *const BiomeType)),
+                all: None,
+            },
+        }
+    }
+
+    /// Views biome types whose length is known, e.g. ones a tool made up to call a hook with
+    pub fn from_slice(biome_types: &'a [BiomeType], own_index: c_int) -> Self {
+        BiomeTypes {
+            own: usize::try_from(own_index)
+                .ok()
+                .and_then(|index| biome_types.get(index)),
+            all: Some(biome_types),
+        }
+    }
+
+    /// The point's own biome type
+    pub fn own(&self) -> Option<&'a BiomeType> {
+        self.own
+    }
+
+    /// The biome type at `index`, or `None` if it's out of range or the count isn't known
+    pub fn get(&self, index: usize) -> Option<&'a BiomeType> {
+        self.all.and_then(|all| all.get(index))
+    }
+
+    /// All of the biome types, or `None` if their count isn't known
+    pub fn as_slice(&self) -> Option<&'a [BiomeType]> {
+        self.all
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vanilla_leaves_every_option_neutral() {
+        let options = WorldGenOptions::vanilla();
+
+        assert_eq!(options.height_offset(), 0.0);
+        assert_eq!(options.rainfall_multiplier(), 1.0);
+        assert_eq!(options.temperature_offset(), 0.0);
+        assert_eq!(
+            options.as_sp_world_gen_options().scales,
+            SPVec3 {
+                x: 1.0,
+                y: 1.0,
+                z: 1.0
+            }
+        );
+        assert_eq!(
+            options.as_sp_world_gen_options().influences,
+            SPVec3 {
+                x: 1.0,
+                y: 1.0,
+                z: 1.0
+            }
+        );
+        assert_eq!(WorldGenOptions::default(), options);
+    }
+
+    #[test]
+    fn presets_are_valid_and_distinct() {
+        let presets = [
+            WorldGenOptions::vanilla(),
+            WorldGenOptions::archipelago(),
+            WorldGenOptions::arid(),
+        ];
+
+        for (i, preset) in presets.iter().enumerate() {
+            assert_eq!(preset.validate(), Ok(()));
+            assert_eq!(preset.unusual_options(), Vec::new());
+            for other in &presets[i + 1..] {
+                assert_ne!(preset, other);
+            }
+        }
+
+        assert!(WorldGenOptions::archipelago().height_offset() < 0.0);
+        assert!(WorldGenOptions::arid().rainfall_multiplier() < 1.0);
+    }
+
+    #[test]
+    fn setters_write_the_matching_axis() {
+        let mut options = WorldGenOptions::vanilla();
+
+        options.set_continent_scale(2.0);
+        options.set_mountain_scale(3.0);
+        options.set_hill_scale(4.0);
+        options.set_continent_influence(0.2);
+        options.set_mountain_influence(0.3);
+        options.set_hill_influence(0.4);
+
+        let sp_options = SPWorldGenOptions::from(options);
+        assert_eq!(
+            sp_options.scales,
+            SPVec3 {
+                x: 2.0,
+                y: 3.0,
+                z: 4.0
+            }
+        );
+        assert_eq!(
+            sp_options.influences,
+            SPVec3 {
+                x: 0.2,
+                y: 0.3,
+                z: 0.4
+            }
+        );
+    }
+
+    #[test]
+    fn builder_starts_from_vanilla() {
+        let options = WorldGenOptions::builder()
+            .rainfall_multiplier(2.0)
+            .build()
+            .unwrap();
+
+        assert_eq!(options.rainfall_multiplier(), 2.0);
+        assert_eq!(options.height_offset(), 0.0);
+
+        let tweaked = WorldGenOptionsBuilder::from(WorldGenOptions::arid())
+            .hill_scale(2.0)
+            .build()
+            .unwrap();
+        assert_eq!(tweaked.rainfall_multiplier(), 0.3);
+        assert_eq!(tweaked.hill_scale(), 2.0);
+    }
+
+    #[test]
+    fn builder_rejects_non_numbers() {
+        let error = WorldGenOptions::builder()
+            .temperature_offset(f64::NAN)
+            .build()
+            .unwrap_err();
+
+        assert_eq!(error.option, "temperature_offset");
+        assert_eq!(
+            error.to_string(),
+            "temperature_offset is NaN, but must be a finite number"
+        );
+
+        // Out of the typical range is only a warning
+        let options = WorldGenOptions::builder()
+            .mountain_scale(0.0)
+            .rainfall_multiplier(-0.1)
+            .build()
+            .unwrap();
+        let unusual = options.unusual_options();
+        assert_eq!(unusual.len(), 2);
+        assert_eq!(
+            unusual[0].to_string(),
+            "rainfall_multiplier is -0.1, outside the typical 0 to 10"
+        );
+    }
+
+    #[test]
+    fn terrain_type_ids_round_trip() {
+        for id in -2..40 {
+            assert_eq!(TerrainType::from_id(id).id(), id);
+            assert_eq!(i16::from(TerrainType::from(id)), id);
+        }
+    }
+
+    /// The registry is global, so everything that registers terrain types runs in this one test
+    #[test]
+    fn terrain_types_can_be_registered() {
+        let ash = TerrainType::from_id(210);
+
+        assert_eq!(ash.name(), None);
+        assert_eq!(TerrainType::from_name("ash"), None);
+        assert_eq!(register_terrain_type("ash", 210), Ok(ash));
+        assert_eq!(register_terrain_type("ash", 210), Ok(ash));
+        assert_eq!(TerrainType::from_name("ash"), Some(ash));
+        assert_eq!(ash.name().as_deref(), Some("ash"));
+
+        assert_eq!(
+            register_terrain_type("cinders", 210),
+            Err(TerrainTypeError::IdTaken(210))
+        );
+        assert_eq!(
+            register_terrain_type("ash", 211),
+            Err(TerrainTypeError::NameTaken("ash".to_string()))
+        );
+        assert_eq!(TerrainType::from_name("cinders"), None);
+    }
+
+    const SAND: TerrainType = TerrainType::from_id(1);
+    const DESERT_SAND: TerrainType = TerrainType::from_id(2);
+    const DESERT_RED_SAND: TerrainType = TerrainType::from_id(3);
+    const RED_ROCK: TerrainType = TerrainType::from_id(4);
+    const POOR_DIRT: TerrainType = TerrainType::from_id(5);
+    const SNOW: TerrainType = TerrainType::from_id(6);
+
+    fn desert() -> BiomeType {
+        BiomeType::new(DESERT_SAND, DESERT_RED_SAND, RED_ROCK, SAND, POOR_DIRT)
+    }
+
+    #[test]
+    fn biome_type_fields_are_named() {
+        let mut biome_type = desert();
+
+        assert_eq!(biome_type.normal(), DESERT_SAND);
+        assert_eq!(biome_type.secondary(), DESERT_RED_SAND);
+        assert_eq!(biome_type.rock(), RED_ROCK);
+        assert_eq!(biome_type.beach(), SAND);
+        assert_eq!(biome_type.vegetation_stripped(), POOR_DIRT);
+
+        biome_type.set_rock(TerrainType::from_id(200));
+        assert_eq!(biome_type.as_sp_biome_type().rockTerrainType, 200);
+    }
+
+    #[test]
+    fn biome_types_from_raw() {
+        let sp_biome_types = [SPBiomeType::from(desert()), SPBiomeType::default()];
+
+        let biome_types = unsafe { BiomeTypes::from_raw(sp_biome_types.as_ptr(), 0) };
+        assert_eq!(biome_types.own(), Some(&desert()));
+        assert_eq!(biome_types.get(1), None);
+        assert_eq!(biome_types.as_slice(), None);
+
+        set_biome_type_count(2);
+        let biome_types = unsafe { BiomeTypes::from_raw(sp_biome_types.as_ptr(), 1) };
+        set_biome_type_count(0);
+        assert_eq!(biome_types.own(), Some(&BiomeType::default()));
+        assert_eq!(
+            biome_types.as_slice(),
+            Some(&[desert(), BiomeType::default()][..])
+        );
+
+        assert_eq!(
+            unsafe { BiomeTypes::from_raw(sp_biome_types.as_ptr(), -1) }.own(),
+            None
+        );
+        assert_eq!(
+            unsafe { BiomeTypes::from_raw(std::ptr::null(), 0) }.own(),
+            None
+        );
+    }
+
+    #[test]
+    fn biome_types_from_slice() {
+        let all = [desert(), BiomeType::default()];
+
+        let biome_types = BiomeTypes::from_slice(&all, 0);
+        assert_eq!(biome_types.own(), Some(&desert()));
+        assert_eq!(biome_types.get(1), Some(&BiomeType::default()));
+        assert_eq!(biome_types.get(2), None);
+
+        assert_eq!(BiomeTypes::from_slice(&all, 2).own(), None);
+    }
+
+    #[test]
+    fn all_zero_sp_options_are_unusual() {
+        let options = WorldGenOptions::from(SPWorldGenOptions::default());
+
+        assert_eq!(options.validate(), Ok(()));
+        let unusual: Vec<_> = options
+            .unusual_options()
+            .iter()
+            .map(|unusual| unusual.option)
+            .collect();
+        assert_eq!(unusual, ["continent_scale", "mountain_scale", "hill_scale"]);
+    }
+
+    #[test]
+    fn only_non_numbers_are_invalid() {
+        let mut options = WorldGenOptions::vanilla();
+        options.set_height_offset(5.0);
+        assert_eq!(options.validate(), Ok(()));
+
+        options.set_mountain_scale(f64::INFINITY);
+        assert_eq!(options.validate().unwrap_err().option, "mountain_scale");
+    }
+}
@@ -0,0 +1,152 @@
+//! Heights read from an image
+
+use crate::sp::height::pipeline::{NoiseInput, Pipeline};
+use crate::sp::sphere_image::{ImageError, Projection, SphereImage};
+use sapiens_sys::SPVec3;
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+/// A heightmap wrapped around the planet, where black is `min_altitude` and white is `max_altitude`
+///
+/// Pixels are blended bilinearly, so a heightmap only needs enough resolution for the landforms it draws. Use
+/// `with_detail` to add noise on top of it
+#[derive(Clone)]
+pub struct ImageHeightSource {
+    image: SphereImage<f64>,
+
+    /// The altitude of black pixels, in prerender units
+    pub min_altitude: f64,
+
+    /// The altitude of white pixels, in prerender units
+    pub max_altitude: f64,
+}
+
+impl ImageHeightSource {
+    /// Uses an image of values in `[0, 1]`
+    pub fn new(image: SphereImage<f64>, min_altitude: f64, max_altitude: f64) -> Self {
+        ImageHeightSource {
+            image,
+            min_altitude,
+            max_altitude,
+        }
+    }
+
+    /// Loads a grayscale `.pgm`, or a `.png` with the `png` feature
+    pub fn load(
+        path: impl AsRef<Path>,
+        projection: Projection,
+        min_altitude: f64,
+        max_altitude: f64,
+    ) -> Result<Self, ImageError> {
+        Ok(ImageHeightSource::new(
+            SphereImage::load_grayscale(path, projection)?,
+            min_altitude,
+            max_altitude,
+        ))
+    }
+
+    pub fn image(&self) -> &SphereImage<f64> {
+        &self.image
+    }
+
+    /// The heightmap's altitude where `point_normal` points, in prerender units
+    pub fn altitude(&self, point_normal: &SPVec3) -> f64 {
+        let t = self.image.bilinear(point_normal);
+        self.min_altitude + (self.max_altitude - self.min_altitude) * t
+    }
+
+    /// A pipeline which starts from this heightmap and adds `noise` at `noise_loc * frequency`, scaled by `amplitude`
+    pub fn with_detail(
+        self,
+        noise: NoiseInput,
+        frequency: f64,
+        octaves: i32,
+        amplitude: f64,
+    ) -> Pipeline {
+        Pipeline::new()
+            .image(Arc::new(self))
+            .add(Pipeline::new().base(noise, frequency, octaves, amplitude))
+    }
+}
+
+impl fmt::Debug for ImageHeightSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ImageHeightSource")
+            .field("width", &self.image.width())
+            .field("height", &self.image.height())
+            .field("projection", &self.image.projection())
+            .field("min_altitude", &self.min_altitude)
+            .field("max_altitude", &self.max_altitude)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_vec_near;
+    use crate::sp::common::WorldGenOptions;
+    use crate::sp::height::HeightQuery;
+    use crate::sp::mock::ConstantNoise;
+
+    /// Black on the -X half of the equator, white on the +X half
+    fn source() -> ImageHeightSource {
+        let image = SphereImage::new(
+            5,
+            3,
+            Projection::Equirectangular,
+            vec![
+                0.5, 0.5, 0.5, 0.5, 0.5, //
+                0.0, 0.0, 0.5, 1.0, 1.0, //
+                0.5, 0.5, 0.5, 0.5, 0.5,
+            ],
+        )
+        .unwrap();
+        ImageHeightSource::new(image, -100.0, 300.0)
+    }
+
+    #[test]
+    fn maps_gray_to_altitude() {
+        let source = source();
+        let east = SPVec3 {
+            x: 1.0,
+            y: 0.0,
+            z: 0.0,
+        };
+        let west = SPVec3 {
+            x: -1.0,
+            y: 0.0,
+            z: 0.0,
+        };
+        let front = SPVec3 {
+            x: 0.0,
+            y: 0.0,
+            z: 1.0,
+        };
+
+        assert_vec_near!(source.altitude(&east), 300.0);
+        assert_vec_near!(source.altitude(&west), -100.0);
+        assert_vec_near!(source.altitude(&front), 100.0);
+    }
+
+    #[test]
+    fn detail_is_added_on_top() {
+        let pipeline = source().with_detail(NoiseInput::Noise1, 4.0, 3, 10.0);
+        let query = HeightQuery::at(SPVec3 {
+            x: 1.0,
+            y: 0.0,
+            z: 0.0,
+        });
+
+        let height = pipeline.get(
+            &ConstantNoise(0.5),
+            &ConstantNoise(0.0),
+            &query,
+            &WorldGenOptions::vanilla(),
+        );
+
+        assert_vec_near!(height.x, 305.0);
+        assert!(format!("{:?}", source()).contains("width: 5"));
+    }
+}
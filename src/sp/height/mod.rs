@@ -0,0 +1,241 @@
+//! Typed inputs for the height hook, and a handle to the game's own height function
+//!
+//! `spHeightGet` returns the altitude of a point in `x` of an `SPVec4`. A mod that only wants to adjust the game's
+//! terrain can ask `Vanilla` for the original result and change it. `Vanilla::load` needs the `vanilla` feature
+//!
+//! ```ignore
+//! let vanilla = Vanilla::load()?;
+//! let mut height = vanilla.get(&noise1, &noise2, &query, &options);
+//! if height.x > 0.0 {
+//!     // 10% taller mountains
+//!     height.x *= 1.1;
+//! }
+//! ```
+//!
+//! `pipeline` builds heights out of small layers instead, and `ImageHeightSource` reads them from a heightmap
+
+mod image;
+pub mod pipeline;
+
+pub use image::ImageHeightSource;
+
+use crate::sp::common::WorldGenOptions;
+#[cfg(feature = "vanilla")]
+use crate::sp::common::{self, LoadVanillaError};
+use crate::sp::noise::NoiseRef;
+use sapiens_sys::{spNoiseGet, SPHeightGetFunc, SPNoise, SPVec3, SPVec4, SPWorldGenOptions};
+
+/// Everything `spHeightGet` is told about a point, apart from the noise and world-gen options
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeightQuery {
+    /// Unit vector from the planet's center to the point
+    pub point_normal: SPVec3,
+
+    /// Where to sample noise for this point
+    pub noise_loc: SPVec3,
+
+    /// How strongly a river flows through the point, as Sapiens measures it
+    pub river_value: f64,
+
+    /// Distance to the nearest river, in prerender units
+    pub river_distance: f64,
+}
+
+impl HeightQuery {
+    /// Gathers the arguments of `spHeightGet` which describe the point, in the same order
+    pub fn from_sp(
+        point_normal: SPVec3,
+        noise_loc: SPVec3,
+        river_value: f64,
+        river_distance: f64,
+    ) -> Self {
+        HeightQuery {
+            point_normal,
+            noise_loc,
+            river_value,
+            river_distance,
+        }
+    }
+
+    /// A query for a point with no river nearby, sampling noise at the point itself, e.g. to preview heights offline
+    pub fn at(point_normal: SPVec3) -> Self {
+        HeightQuery {
+            point_normal,
+            noise_loc: point_normal,
+            river_value: 0.0,
+            river_distance: f64::INFINITY,
+        }
+    }
+}
+
+type HeightGetFn = unsafe extern "C" fn(
+    noise1: *mut SPNoise,
+    noise2: *mut SPNoise,
+    point_normal: SPVec3,
+    noise_loc: SPVec3,
+    world_gen_options: SPWorldGenOptions,
+    river_value: f64,
+    river_distance: f64,
+) -> SPVec4;
+
+/// The game's own `spHeightGet`, or a stand-in for it
+#[derive(Debug, Clone, Copy)]
+pub struct Vanilla(HeightGetFn);
+
+impl Vanilla {
+    /// Finds the game's `spHeightGet` in SPCommon, with the `vanilla` feature
+    #[cfg(feature = "vanilla")]
+    pub fn load() -> Result<Self, LoadVanillaError> {
+        unsafe { common::load_vanilla("spHeightGet").map(Vanilla) }
+    }
+
+    /// Wraps a height function the mod got some other way, or `None` if it's null
+    ///
+    /// # Safety
+    ///
+    /// `func` must behave like `spHeightGet`, and be safe to call with any valid noise and options
+    pub unsafe fn from_sp(func: SPHeightGetFunc) -> Option<Self> {
+        func.map(Vanilla)
+    }
+
+    /// A simple height function for tests and offline tools, which don't have the game to call into
+    ///
+    /// It isn't the game's terrain: the altitude is `noise1` at `noise_loc`, shifted by the height offset and scaled to
+    /// at most about 4km. The other components are zero
+    pub fn stand_in() -> Self {
+        Vanilla(stand_in_height)
+    }
+
+    /// Computes the height at a point, the same way Sapiens would call the function
+    pub fn get(
+        &self,
+        noise1: &NoiseRef,
+        noise2: &NoiseRef,
+        query: &HeightQuery,
+        options: &WorldGenOptions,
+    ) -> SPVec4 {
+        unsafe {
+            (self.0)(
+                noise1.as_sp_noise(),
+                noise2.as_sp_noise(),
+                query.point_normal,
+                query.noise_loc,
+                *options.as_sp_world_gen_options(),
+                query.river_value,
+                query.river_distance,
+            )
+        }
+    }
+
+    pub fn as_sp_height_get_func(&self) -> SPHeightGetFunc {
+        Some(self.0)
+    }
+}
+
+unsafe extern "C" fn stand_in_height(
+    noise1: *mut SPNoise,
+    _noise2: *mut SPNoise,
+    _point_normal: SPVec3,
+    noise_loc: SPVec3,
+    world_gen_options: SPWorldGenOptions,
+    _river_value: f64,
+    _river_distance: f64,
+) -> SPVec4 {
+    let options = WorldGenOptions::from_sp(world_gen_options);
+    let value = spNoiseGet(noise1, noise_loc, 6) + options.height_offset();
+
+    SPVec4 {
+        x: value * crate::sp_meters_to_prerender!(4000.0),
+        y: 0.0,
+        z: 0.0,
+        w: 0.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_vec_near;
+    use crate::sp::noise::Noise;
+
+    fn query() -> HeightQuery {
+        HeightQuery::from_sp(
+            SPVec3 {
+                x: 0.0,
+                y: 1.0,
+                z: 0.0,
+            },
+            SPVec3 {
+                x: 0.3,
+                y: 2.1,
+                z: -0.7,
+            },
+            0.0,
+            1.0,
+        )
+    }
+
+    unsafe extern "C" fn river_height(
+        _noise1: *mut SPNoise,
+        _noise2: *mut SPNoise,
+        _point_normal: SPVec3,
+        _noise_loc: SPVec3,
+        world_gen_options: SPWorldGenOptions,
+        river_value: f64,
+        river_distance: f64,
+    ) -> SPVec4 {
+        SPVec4 {
+            x: river_value - river_distance + world_gen_options.heightOffset,
+            y: 0.0,
+            z: 0.0,
+            w: 0.0,
+        }
+    }
+
+    #[test]
+    fn passes_the_query_through() {
+        let noise = Noise::new(1, 0.5);
+        let vanilla = unsafe { Vanilla::from_sp(Some(river_height)) }.unwrap();
+        let options = WorldGenOptions::builder()
+            .height_offset(0.5)
+            .build()
+            .unwrap();
+
+        let query = HeightQuery {
+            river_value: 2.0,
+            river_distance: 0.25,
+            ..query()
+        };
+        let height = vanilla.get(&noise.handle(), &noise.handle(), &query, &options);
+
+        assert_eq!(height.x, 2.25);
+        assert!(vanilla.as_sp_height_get_func().is_some());
+        assert!(unsafe { Vanilla::from_sp(None) }.is_none());
+    }
+
+    #[test]
+    fn stand_in_follows_noise_and_height_offset() {
+        let noise = Noise::new(1, 0.5);
+        let vanilla = Vanilla::stand_in();
+
+        let flat = vanilla.get(
+            &noise.handle(),
+            &noise.handle(),
+            &query(),
+            &WorldGenOptions::vanilla(),
+        );
+        let raised = vanilla.get(
+            &noise.handle(),
+            &noise.handle(),
+            &query(),
+            &WorldGenOptions::builder()
+                .height_offset(0.5)
+                .build()
+                .unwrap(),
+        );
+
+        let expected = noise.get(&query().noise_loc, 6) * crate::sp_meters_to_prerender!(4000.0);
+        assert_vec_near!(flat.x, expected);
+        assert_vec_near!(raised.x - flat.x, crate::sp_meters_to_prerender!(2000.0));
+    }
+}
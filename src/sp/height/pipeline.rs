@@ -0,0 +1,573 @@
+//! Heights built up from small, separately tested layers
+//!
+//! A `Pipeline` starts at sea level and runs its layers in order, each one changing the altitude the last one left
+//!
+//! ```ignore
+//! let terrain = Pipeline::new()
+//!     .base(NoiseInput::Noise1, 1.0, 6, sp_meters_to_prerender!(2000.0))
+//!     .add(Pipeline::new().base(NoiseInput::Noise2, 4.0, 4, sp_meters_to_prerender!(500.0)))
+//!     .ridge_mask(NoiseInput::Noise2, 2.0, 4, 2.0)
+//!     .river_carve(sp_meters_to_prerender!(20.0), sp_meters_to_prerender!(200.0))
+//!     .ocean_floor(sp_meters_to_prerender!(3000.0));
+//! ```
+//!
+//! Pipelines sample noise through `NoiseSource`, so the same pipeline runs in the game with the `NoiseRef`s Sapiens
+//! passes to `spHeightGet`, and offline with `Perlin` or any other generator. To export one, call it from a
+//! `height_get` function marked with `#[export_to_sapiens]`
+
+use crate::sp::common::WorldGenOptions;
+use crate::sp::height::{HeightQuery, ImageHeightSource};
+use crate::sp::noise::NoiseSource;
+use sapiens_sys::{SPVec3, SPVec4};
+use std::fmt;
+use std::sync::Arc;
+
+/// Everything a layer can look at while computing a height
+#[derive(Clone, Copy)]
+pub struct HeightContext<'a> {
+    /// The first noise generator Sapiens passes to `spHeightGet`
+    pub noise1: &'a dyn NoiseSource,
+
+    /// The second noise generator Sapiens passes to `spHeightGet`
+    pub noise2: &'a dyn NoiseSource,
+
+    pub query: &'a HeightQuery,
+
+    pub options: &'a WorldGenOptions,
+}
+
+/// Which noise a layer samples
+#[derive(Clone)]
+pub enum NoiseInput {
+    /// `HeightContext::noise1`
+    Noise1,
+
+    /// `HeightContext::noise2`
+    Noise2,
+
+    /// A generator owned by the mod, e.g. a `Perlin` with its own seed
+    Custom(Arc<dyn NoiseSource + Send + Sync>),
+}
+
+impl NoiseInput {
+    /// Samples this noise at `noise_loc * frequency`
+    pub fn sample(&self, context: &HeightContext<'_>, frequency: f64, octaves: i32) -> f64 {
+        let loc = context.query.noise_loc;
+        let pos = SPVec3 {
+            x: loc.x * frequency,
+            y: loc.y * frequency,
+            z: loc.z * frequency,
+        };
+
+        match self {
+            NoiseInput::Noise1 => context.noise1.get(&pos, octaves),
+            NoiseInput::Noise2 => context.noise2.get(&pos, octaves),
+            NoiseInput::Custom(noise) => noise.get(&pos, octaves),
+        }
+    }
+}
+
+impl fmt::Debug for NoiseInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NoiseInput::Noise1 => write!(f, "Noise1"),
+            NoiseInput::Noise2 => write!(f, "Noise2"),
+            NoiseInput::Custom(_) => write!(f, "Custom(..)"),
+        }
+    }
+}
+
+/// The right-hand side of `Layer::Add` and `Layer::Multiply`
+#[derive(Debug, Clone)]
+pub enum Operand {
+    Constant(f64),
+
+    /// Another pipeline, evaluated from sea level at the same point
+    Pipeline(Pipeline),
+}
+
+impl Operand {
+    pub fn value(&self, context: &HeightContext<'_>) -> f64 {
+        match self {
+            Operand::Constant(value) => *value,
+            Operand::Pipeline(pipeline) => pipeline.altitude(context),
+        }
+    }
+}
+
+impl From<f64> for Operand {
+    fn from(value: f64) -> Self {
+        Operand::Constant(value)
+    }
+}
+
+impl From<Pipeline> for Operand {
+    fn from(pipeline: Pipeline) -> Self {
+        Operand::Pipeline(pipeline)
+    }
+}
+
+/// One step of a `Pipeline`
+///
+/// Altitudes and distances are in prerender units, like everything else `spHeightGet` deals with. Use
+/// `sp_meters_to_prerender!` to give them in meters
+#[derive(Debug, Clone)]
+pub enum Layer {
+    /// Replaces the altitude with `noise` at `noise_loc * frequency`, scaled by `amplitude`
+    Base {
+        noise: NoiseInput,
+        frequency: f64,
+        octaves: i32,
+        amplitude: f64,
+    },
+
+    Add(Operand),
+
+    Multiply(Operand),
+
+    /// Turns slopes into steps `step` high. A `sharpness` of `1` leaves slopes as they are, and higher values make the
+    /// flat part of each step wider
+    Terrace {
+        step: f64,
+        sharpness: f64,
+    },
+
+    Clamp {
+        min: f64,
+        max: f64,
+    },
+
+    /// Scales land by `(1 - |noise|)^sharpness`, so high ground follows the noise's ridgelines. Points below sea level
+    /// are left alone
+    RidgeMask {
+        noise: NoiseInput,
+        frequency: f64,
+        octaves: i32,
+        sharpness: f64,
+    },
+
+    /// Lowers the ground by up to `depth` at a river, falling off smoothly to nothing `width` away from it. The depth
+    /// is scaled by the query's river value, clamped to `[0, 1]`
+    RiverCarve {
+        depth: f64,
+        width: f64,
+    },
+
+    /// Eases points below sea level towards `-depth`, so the sea floor flattens out instead of following the noise
+    /// all the way down. Shallow water is barely changed
+    OceanFloor {
+        depth: f64,
+    },
+
+    /// Adds the world's height offset option, times `scale`
+    HeightOffset {
+        scale: f64,
+    },
+
+    /// Replaces the altitude with a heightmap's altitude at the query's point
+    Image(Arc<ImageHeightSource>),
+}
+
+impl Layer {
+    /// Runs this layer on the altitude left by the layers before it
+    pub fn apply(&self, altitude: f64, context: &HeightContext<'_>) -> f64 {
+        match self {
+            Layer::Base {
+                noise,
+                frequency,
+                octaves,
+                amplitude,
+            } => noise.sample(context, *frequency, *octaves) * amplitude,
+            Layer::Add(operand) => altitude + operand.value(context),
+            Layer::Multiply(operand) => altitude * operand.value(context),
+            Layer::Terrace { step, sharpness } => {
+                if *step <= 0.0 {
+                    return altitude;
+                }
+
+                let steps = altitude / step;
+                let floor = steps.floor();
+                (floor + (steps - floor).powf(*sharpness)) * step
+            }
+            Layer::Clamp { min, max } => altitude.max(*min).min(*max),
+            Layer::RidgeMask {
+                noise,
+                frequency,
+                octaves,
+                sharpness,
+            } => {
+                if altitude <= 0.0 {
+                    return altitude;
+                }
+
+                let ridge = 1.0 - noise.sample(context, *frequency, *octaves).abs();
+                altitude * ridge.clamp(0.0, 1.0).powf(*sharpness)
+            }
+            Layer::RiverCarve { depth, width } => {
+                let distance = context.query.river_distance;
+                if *width <= 0.0 || distance >= *width {
+                    return altitude;
+                }
+
+                let falloff = 1.0 - distance.max(0.0) / width;
+                let strength = context.query.river_value.clamp(0.0, 1.0);
+                altitude - depth * strength * falloff * falloff
+            }
+            Layer::OceanFloor { depth } => {
+                if altitude >= 0.0 || *depth <= 0.0 {
+                    return altitude;
+                }
+
+                -depth * (-altitude / depth).tanh()
+            }
+            Layer::HeightOffset { scale } => altitude + context.options.height_offset() * scale,
+            Layer::Image(source) => source.altitude(&context.query.point_normal),
+        }
+    }
+}
+
+/// Layers which together compute a height
+#[derive(Debug, Clone, Default)]
+pub struct Pipeline {
+    layers: Vec<Layer>,
+}
+
+impl Pipeline {
+    /// A pipeline with no layers, which leaves everything at sea level
+    pub fn new() -> Self {
+        Pipeline { layers: Vec::new() }
+    }
+
+    /// Appends a layer
+    pub fn then(mut self, layer: Layer) -> Self {
+        self.layers.push(layer);
+        self
+    }
+
+    pub fn base(self, noise: NoiseInput, frequency: f64, octaves: i32, amplitude: f64) -> Self {
+        self.then(Layer::Base {
+            noise,
+            frequency,
+            octaves,
+            amplitude,
+        })
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn add(self, operand: impl Into<Operand>) -> Self {
+        self.then(Layer::Add(operand.into()))
+    }
+
+    pub fn multiply(self, operand: impl Into<Operand>) -> Self {
+        self.then(Layer::Multiply(operand.into()))
+    }
+
+    pub fn terrace(self, step: f64, sharpness: f64) -> Self {
+        self.then(Layer::Terrace { step, sharpness })
+    }
+
+    pub fn clamp(self, min: f64, max: f64) -> Self {
+        self.then(Layer::Clamp { min, max })
+    }
+
+    pub fn ridge_mask(
+        self,
+        noise: NoiseInput,
+        frequency: f64,
+        octaves: i32,
+        sharpness: f64,
+    ) -> Self {
+        self.then(Layer::RidgeMask {
+            noise,
+            frequency,
+            octaves,
+            sharpness,
+        })
+    }
+
+    pub fn river_carve(self, depth: f64, width: f64) -> Self {
+        self.then(Layer::RiverCarve { depth, width })
+    }
+
+    pub fn ocean_floor(self, depth: f64) -> Self {
+        self.then(Layer::OceanFloor { depth })
+    }
+
+    pub fn height_offset(self, scale: f64) -> Self {
+        self.then(Layer::HeightOffset { scale })
+    }
+
+    pub fn image(self, source: Arc<ImageHeightSource>) -> Self {
+        self.then(Layer::Image(source))
+    }
+
+    pub fn layers(&self) -> &[Layer] {
+        &self.layers
+    }
+
+    /// Runs every layer in order, starting from sea level
+    pub fn altitude(&self, context: &HeightContext<'_>) -> f64 {
+        self.layers
+            .iter()
+            .fold(0.0, |altitude, layer| layer.apply(altitude, context))
+    }
+
+    /// Computes what `spHeightGet` should return for a point, with the altitude in `x`
+    ///
+    /// `y`, `z` and `w` are zero, like `height::Vanilla::stand_in`'s. The SDK headers don't say what the game keeps in
+    /// them, so a pipeline can't work them out. To keep the game's values, start from its answer with `get_over`
+    pub fn get(
+        &self,
+        noise1: &dyn NoiseSource,
+        noise2: &dyn NoiseSource,
+        query: &HeightQuery,
+        options: &WorldGenOptions,
+    ) -> SPVec4 {
+        self.get_over(
+            SPVec4 {
+                x: 0.0,
+                y: 0.0,
+                z: 0.0,
+                w: 0.0,
+            },
+            noise1,
+            noise2,
+            query,
+            options,
+        )
+    }
+
+    /// Like `get`, but keeps `y`, `z` and `w` from `height`, e.g. the game's answer from `height::Vanilla::get`
+    pub fn get_over(
+        &self,
+        height: SPVec4,
+        noise1: &dyn NoiseSource,
+        noise2: &dyn NoiseSource,
+        query: &HeightQuery,
+        options: &WorldGenOptions,
+    ) -> SPVec4 {
+        let context = HeightContext {
+            noise1,
+            noise2,
+            query,
+            options,
+        };
+
+        SPVec4 {
+            x: self.altitude(&context),
+            ..height
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_vec_near;
+    use crate::sp::mock::ConstantNoise;
+    use crate::sp::noise::Perlin;
+
+    fn query() -> HeightQuery {
+        HeightQuery::at(SPVec3 {
+            x: 0.0,
+            y: 0.6,
+            z: 0.8,
+        })
+    }
+
+    fn run(pipeline: &Pipeline, noise: f64, query: &HeightQuery) -> f64 {
+        pipeline
+            .get(
+                &ConstantNoise(noise),
+                &ConstantNoise(-noise),
+                query,
+                &WorldGenOptions::vanilla(),
+            )
+            .x
+    }
+
+    #[test]
+    fn empty_pipeline_is_sea_level() {
+        assert_eq!(run(&Pipeline::new(), 0.5, &query()), 0.0);
+    }
+
+    #[test]
+    fn base_scales_the_chosen_noise() {
+        assert_eq!(
+            run(
+                &Pipeline::new().base(NoiseInput::Noise1, 1.0, 4, 2.0),
+                0.5,
+                &query()
+            ),
+            1.0
+        );
+        assert_eq!(
+            run(
+                &Pipeline::new().base(NoiseInput::Noise2, 1.0, 4, 2.0),
+                0.5,
+                &query()
+            ),
+            -1.0
+        );
+        assert_eq!(
+            run(
+                &Pipeline::new().base(
+                    NoiseInput::Custom(Arc::new(ConstantNoise(0.25))),
+                    1.0,
+                    4,
+                    2.0
+                ),
+                0.5,
+                &query()
+            ),
+            0.5
+        );
+    }
+
+    #[test]
+    fn base_samples_at_the_scaled_noise_location() {
+        let perlin = Perlin::new(3, 0.5);
+        let pipeline = Pipeline::new().base(NoiseInput::Noise1, 4.0, 3, 1.0);
+
+        let height = pipeline.get(&perlin, &perlin, &query(), &WorldGenOptions::vanilla());
+
+        let pos = SPVec3 {
+            x: 0.0,
+            y: 2.4,
+            z: 3.2,
+        };
+        assert_vec_near!(height.x, perlin.get(&pos, 3));
+    }
+
+    #[test]
+    fn get_over_keeps_the_other_components() {
+        let pipeline = Pipeline::new().add(2.0);
+        let noise = ConstantNoise(0.0);
+        let vanilla = SPVec4 {
+            x: 7.0,
+            y: 0.25,
+            z: -1.0,
+            w: 3.0,
+        };
+
+        let height = pipeline.get_over(
+            vanilla,
+            &noise,
+            &noise,
+            &query(),
+            &WorldGenOptions::vanilla(),
+        );
+
+        assert_eq!(height, SPVec4 { x: 2.0, ..vanilla });
+        let height = pipeline.get(&noise, &noise, &query(), &WorldGenOptions::vanilla());
+        assert_eq!((height.y, height.z, height.w), (0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn add_and_multiply_combine_pipelines() {
+        let hills = Pipeline::new().base(NoiseInput::Noise2, 1.0, 4, 1.0);
+        let pipeline = Pipeline::new()
+            .base(NoiseInput::Noise1, 1.0, 4, 3.0)
+            .add(hills)
+            .multiply(1.1);
+
+        assert_vec_near!(run(&pipeline, 0.5, &query()), (1.5 - 0.5) * 1.1);
+    }
+
+    #[test]
+    fn terrace_makes_steps() {
+        let context_noise = ConstantNoise(0.0);
+        let query = query();
+        let options = WorldGenOptions::vanilla();
+        let context = HeightContext {
+            noise1: &context_noise,
+            noise2: &context_noise,
+            query: &query,
+            options: &options,
+        };
+        let flat = Layer::Terrace {
+            step: 2.0,
+            sharpness: 1.0,
+        };
+        let steep = Layer::Terrace {
+            step: 2.0,
+            sharpness: 3.0,
+        };
+
+        assert_vec_near!(flat.apply(3.0, &context), 3.0);
+        assert_vec_near!(steep.apply(3.0, &context), 2.25);
+        assert_vec_near!(steep.apply(4.0, &context), 4.0);
+        assert_vec_near!(steep.apply(-1.0, &context), -1.75);
+    }
+
+    #[test]
+    fn clamp_limits_the_altitude() {
+        let pipeline = Pipeline::new()
+            .base(NoiseInput::Noise1, 1.0, 4, 10.0)
+            .clamp(-2.0, 3.0);
+
+        assert_eq!(run(&pipeline, 0.9, &query()), 3.0);
+        assert_eq!(run(&pipeline, -0.9, &query()), -2.0);
+        assert_eq!(run(&pipeline, 0.1, &query()), 1.0);
+    }
+
+    #[test]
+    fn ridge_mask_only_touches_land() {
+        let pipeline = Pipeline::new()
+            .base(NoiseInput::Noise1, 1.0, 4, 4.0)
+            .ridge_mask(NoiseInput::Noise2, 1.0, 4, 2.0);
+
+        // Noise2 is -0.5, so the mask is 0.5^2
+        assert_vec_near!(run(&pipeline, 0.5, &query()), 0.5);
+        assert_vec_near!(run(&pipeline, -0.5, &query()), -2.0);
+    }
+
+    #[test]
+    fn river_carve_falls_off_with_distance() {
+        let pipeline = Pipeline::new()
+            .base(NoiseInput::Noise1, 1.0, 4, 1.0)
+            .river_carve(0.4, 2.0);
+        let at_river = |river_value: f64, river_distance: f64| HeightQuery {
+            river_value,
+            river_distance,
+            ..query()
+        };
+
+        assert_vec_near!(run(&pipeline, 0.5, &at_river(1.0, 0.0)), 0.1);
+        assert_vec_near!(run(&pipeline, 0.5, &at_river(1.0, 1.0)), 0.4);
+        assert_vec_near!(run(&pipeline, 0.5, &at_river(0.5, 0.0)), 0.3);
+        assert_vec_near!(run(&pipeline, 0.5, &at_river(1.0, 2.0)), 0.5);
+        assert_vec_near!(run(&pipeline, 0.5, &query()), 0.5);
+    }
+
+    #[test]
+    fn ocean_floor_flattens_deep_water() {
+        let pipeline = |noise: f64| {
+            run(
+                &Pipeline::new()
+                    .base(NoiseInput::Noise1, 1.0, 4, 10.0)
+                    .ocean_floor(1.0),
+                noise,
+                &query(),
+            )
+        };
+
+        assert_eq!(pipeline(0.5), 5.0);
+        assert_vec_near!(pipeline(-0.001), -0.01, epsilon = 1e-5);
+        assert!(pipeline(-1.0) > -1.0);
+        assert!(pipeline(-1.0) < pipeline(-0.2));
+    }
+
+    #[test]
+    fn height_offset_follows_the_options() {
+        let pipeline = Pipeline::new().height_offset(10.0);
+        let options = WorldGenOptions::builder()
+            .height_offset(-0.25)
+            .build()
+            .unwrap();
+
+        let height = pipeline.get(&ConstantNoise(0.0), &ConstantNoise(0.0), &query(), &options);
+
+        assert_eq!(height.x, -2.5);
+    }
+}
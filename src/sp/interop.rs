@@ -0,0 +1,96 @@
+//! Conversions between the `sp::math` wrappers and other math libraries
+//!
+//! The conversions for the raw `SP*` types live in `sapiens-sys`. These just forward through them, so the same row and
+//! column conventions apply: row `i` of a Sapiens matrix becomes column `i` of the other library's matrix
+
+#[allow(unused_macros)]
+macro_rules! wrapper_conversions {
+    ($wrapper:ty, $sp_type:ty, $as_sp:ident, $($other:ty),+) => {
+        $(
+            impl From<$wrapper> for $other {
+                fn from(value: $wrapper) -> Self {
+                    <$other>::from(value.$as_sp())
+                }
+            }
+
+            impl From<$other> for $wrapper {
+                fn from(value: $other) -> Self {
+                    <$wrapper>::from(<$sp_type>::from(value))
+                }
+            }
+        )+
+    };
+}
+
+#[cfg(feature = "glam")]
+mod glam {
+    use crate::sp::math::{Mat3, Mat4, Vec2, Vec3, Vec4};
+    use sapiens_sys::glam::{DMat3, DMat4, DQuat, DVec2, DVec3, DVec4};
+    use sapiens_sys::{SPMat3, SPMat4, SPVec2, SPVec3, SPVec4};
+
+    wrapper_conversions!(Vec2, SPVec2, as_sp_vec, DVec2);
+    wrapper_conversions!(Vec3, SPVec3, as_sp_vec, DVec3);
+    wrapper_conversions!(Vec4, SPVec4, as_sp_vec, DVec4, DQuat);
+    wrapper_conversions!(Mat3, SPMat3, as_sp_mat, DMat3);
+    wrapper_conversions!(Mat4, SPMat4, as_sp_mat, DMat4);
+}
+
+#[cfg(feature = "mint")]
+mod mint {
+    use crate::sp::math::{Mat3, Mat4, Vec2, Vec3, Vec4};
+    use sapiens_sys::mint::{ColumnMatrix3, ColumnMatrix4, Quaternion, Vector2, Vector3, Vector4};
+    use sapiens_sys::{SPMat3, SPMat4, SPVec2, SPVec3, SPVec4};
+
+    wrapper_conversions!(Vec2, SPVec2, as_sp_vec, Vector2<f64>);
+    wrapper_conversions!(Vec3, SPVec3, as_sp_vec, Vector3<f64>);
+    wrapper_conversions!(Vec4, SPVec4, as_sp_vec, Vector4<f64>, Quaternion<f64>);
+    wrapper_conversions!(Mat3, SPMat3, as_sp_mat, ColumnMatrix3<f64>);
+    wrapper_conversions!(Mat4, SPMat4, as_sp_mat, ColumnMatrix4<f64>);
+}
+
+#[cfg(feature = "nalgebra")]
+mod nalgebra {
+    use crate::sp::math::{Mat3, Mat4, Vec2, Vec3, Vec4};
+    use sapiens_sys::nalgebra::{Matrix3, Matrix4, Quaternion, Vector2, Vector3, Vector4};
+    use sapiens_sys::{SPMat3, SPMat4, SPVec2, SPVec3, SPVec4};
+
+    wrapper_conversions!(Vec2, SPVec2, as_sp_vec, Vector2<f64>);
+    wrapper_conversions!(Vec3, SPVec3, as_sp_vec, Vector3<f64>);
+    wrapper_conversions!(Vec4, SPVec4, as_sp_vec, Vector4<f64>, Quaternion<f64>);
+    wrapper_conversions!(Mat3, SPMat3, as_sp_mat, Matrix3<f64>);
+    wrapper_conversions!(Mat4, SPMat4, as_sp_mat, Matrix4<f64>);
+}
+
+#[cfg(all(test, feature = "glam"))]
+mod glam_tests {
+    use crate::sp::math::{Mat3, Vec3};
+    use sapiens_sys::glam::{DMat3, DVec3};
+
+    #[test]
+    fn wrapper_round_trip() {
+        let v = Vec3::new(1.0, 2.0, 3.0);
+
+        let glam_v = DVec3::from(v);
+
+        assert_eq!(glam_v, DVec3::new(1.0, 2.0, 3.0));
+        assert_eq!(Vec3::from(glam_v).as_sp_vec(), Vec3::new(1.0, 2.0, 3.0).as_sp_vec());
+    }
+
+    #[test]
+    fn mat3_rows_become_glam_columns() {
+        let m = Mat3::new(
+            &Vec3::new(1.0, 2.0, 3.0),
+            &Vec3::new(4.0, 5.0, 6.0),
+            &Vec3::new(7.0, 8.0, 9.0),
+        );
+        let sp_result = Vec3::new(1.0, 10.0, 100.0) * Mat3::from(m.as_sp_mat());
+
+        let glam_m = DMat3::from(m);
+
+        assert_eq!(glam_m.col(1), DVec3::new(4.0, 5.0, 6.0));
+        assert_eq!(
+            glam_m * DVec3::new(1.0, 10.0, 100.0),
+            DVec3::from(sp_result)
+        );
+    }
+}
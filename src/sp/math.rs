@@ -4,24 +4,25 @@
 //! these types can be very cumbersome so I recommend you don't do it unless you have complex vector math where most of
 //! your code can use this interface
 
+use sapiens_sys::approx::{AbsDiffEq, RelativeEq, UlpsEq};
 use sapiens_sys::*;
 use std::ops::{Add, Deref, Div, Mul, Neg, Sub};
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct Vec2(SPVec2);
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct Vec3(SPVec3);
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct Vec4(SPVec4);
 
 pub type Quat = Vec4;
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct Mat3(SPMat3);
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct Mat4(SPMat4);
 
 impl Vec2 {
@@ -361,19 +362,21 @@ impl From<SPMat3> for Mat3 {
     }
 }
 
+impl From<&Quat> for Mat3 {
+    fn from(quat: &Quat) -> Self {
+        Mat3(crate::sp::mat3_cast(&quat.0))
+    }
+}
+
 impl From<&mut Quat> for Mat3 {
     fn from(quat: &mut Quat) -> Self {
-        let mut mat: Mat3 = Default::default();
-
-        unsafe { spMat3Cast(&mut quat.0, &mut mat.0) }
-
-        mat
+        Mat3::from(&*quat)
     }
 }
 
 impl Into<Quat> for Mat3 {
-    fn into(mut self) -> Quat {
-        Vec4(unsafe { spQuatCast(&mut self.0) })
+    fn into(self) -> Quat {
+        Vec4(crate::sp::quat_cast(&self.0))
     }
 }
 
@@ -440,3 +443,46 @@ impl From<SPMat4> for Mat4 {
         Mat4(sp_mat)
     }
 }
+
+/// Approximate equality for the wrappers just compares the wrapped Sapiens values
+macro_rules! impl_approx {
+    ($wrapper:ty) => {
+        impl AbsDiffEq for $wrapper {
+            type Epsilon = f64;
+
+            fn default_epsilon() -> f64 {
+                f64::default_epsilon()
+            }
+
+            fn abs_diff_eq(&self, other: &Self, epsilon: f64) -> bool {
+                self.0.abs_diff_eq(&other.0, epsilon)
+            }
+        }
+
+        impl RelativeEq for $wrapper {
+            fn default_max_relative() -> f64 {
+                f64::default_max_relative()
+            }
+
+            fn relative_eq(&self, other: &Self, epsilon: f64, max_relative: f64) -> bool {
+                self.0.relative_eq(&other.0, epsilon, max_relative)
+            }
+        }
+
+        impl UlpsEq for $wrapper {
+            fn default_max_ulps() -> u32 {
+                f64::default_max_ulps()
+            }
+
+            fn ulps_eq(&self, other: &Self, epsilon: f64, max_ulps: u32) -> bool {
+                self.0.ulps_eq(&other.0, epsilon, max_ulps)
+            }
+        }
+    };
+}
+
+impl_approx!(Vec2);
+impl_approx!(Vec3);
+impl_approx!(Vec4);
+impl_approx!(Mat3);
+impl_approx!(Mat4);
@@ -0,0 +1,297 @@
+//! Stand-ins for Sapiens' generators and particle manager, for testing mod logic without the game
+//!
+//! Anything written against `RandomSource` or `NoiseSource` accepts these, and `particles::ThreadState::mock` builds a
+//! thread state around them:
+//!
+//! ```ignore
+//! let mut recorder = ParticleRecorder::new();
+//! let mut thread_state = ThreadState::mock(&mut recorder, SeededRand::new(42), ConstantNoise(0.0));
+//!
+//! my_emitter.emit(&mut thread_state, &mut emitter_state);
+//!
+//! assert_eq!(recorder.particles.len(), 32);
+//! ```
+
+use crate::sp::noise::NoiseSource;
+use crate::sp::particles::{EmitterState, ParticleState};
+use crate::sp::rand::RandomSource;
+use sapiens_sys::SPVec3;
+use std::cell::Cell;
+use std::os::raw;
+
+/// A generator which always returns the same value
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConstantRand(pub f64);
+
+impl RandomSource for ConstantRand {
+    fn get_float(&self) -> f64 {
+        self.0
+    }
+}
+
+/// A generator which plays back a fixed list of values, starting over once it runs out
+#[derive(Debug, Clone)]
+pub struct ScriptedRand {
+    values: Vec<f64>,
+    next: Cell<usize>,
+}
+
+impl ScriptedRand {
+    /// # Panics
+    ///
+    /// If `values` is empty
+    pub fn new<Values: Into<Vec<f64>>>(values: Values) -> Self {
+        let values = values.into();
+        assert!(!values.is_empty(), "ScriptedRand needs at least one value");
+
+        ScriptedRand {
+            values,
+            next: Cell::new(0),
+        }
+    }
+
+    /// How many values have been handed out so far
+    pub fn calls(&self) -> usize {
+        self.next.get()
+    }
+}
+
+impl RandomSource for ScriptedRand {
+    fn get_float(&self) -> f64 {
+        let next = self.next.get();
+        self.next.set(next + 1);
+        self.values[next % self.values.len()]
+    }
+}
+
+/// A small deterministic generator, for tests that need lots of varied values
+///
+/// This is SplitMix64, so the same seed always gives the same sequence on every platform. It isn't the generator
+/// Sapiens uses, so don't expect it to reproduce the game's values
+#[derive(Debug, Clone)]
+pub struct SeededRand {
+    state: Cell<u64>,
+}
+
+impl SeededRand {
+    pub fn new(seed: u64) -> Self {
+        SeededRand {
+            state: Cell::new(seed),
+        }
+    }
+
+    /// The next raw 64 bit output
+    pub fn next_u64(&self) -> u64 {
+        let state = self.state.get().wrapping_add(0x9E37_79B9_7F4A_7C15);
+        self.state.set(state);
+
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+impl RandomSource for SeededRand {
+    /// Uniform in `[0, 1)`, using the top 53 bits so every value is exactly representable
+    fn get_float(&self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Noise which is the same everywhere
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConstantNoise(pub f64);
+
+impl NoiseSource for ConstantNoise {
+    fn get(&self, _pos: &SPVec3, _end_octave: i32) -> f64 {
+        self.0
+    }
+}
+
+/// Noise which plays back a fixed list of values, one per call, starting over once it runs out
+#[derive(Debug, Clone)]
+pub struct ScriptedNoise {
+    values: Vec<f64>,
+    next: Cell<usize>,
+}
+
+impl ScriptedNoise {
+    /// # Panics
+    ///
+    /// If `values` is empty
+    pub fn new<Values: Into<Vec<f64>>>(values: Values) -> Self {
+        let values = values.into();
+        assert!(!values.is_empty(), "ScriptedNoise needs at least one value");
+
+        ScriptedNoise {
+            values,
+            next: Cell::new(0),
+        }
+    }
+
+    /// How many values have been handed out so far
+    pub fn calls(&self) -> usize {
+        self.next.get()
+    }
+}
+
+impl NoiseSource for ScriptedNoise {
+    fn get(&self, _pos: &SPVec3, _end_octave: i32) -> f64 {
+        let next = self.next.get();
+        self.next.set(next + 1);
+        self.values[next % self.values.len()]
+    }
+}
+
+/// Collects the particles added through a `particles::ThreadState::mock`
+///
+/// Particles still go through the same checks as in game, so once render groups are registered with
+/// `particles::register_render_groups`, adding to any other render group fails
+#[derive(Debug, Default)]
+pub struct ParticleRecorder {
+    /// Every particle that was added, along with its render group
+    pub particles: Vec<(u32, ParticleState)>,
+}
+
+impl ParticleRecorder {
+    pub fn new() -> Self {
+        ParticleRecorder::default()
+    }
+
+    /// Stands in for Sapiens' `addParticle` callback, with the recorder passed as the particle manager
+    pub(crate) unsafe extern "C" fn record(
+        particle_manager: *mut raw::c_void,
+        _emitter_state: *mut EmitterState,
+        local_render_group_type_id: u32,
+        particle_state: *mut ParticleState,
+    ) {
+        let recorder = &mut *(particle_manager as *mut ParticleRecorder);
+        recorder
+            .particles
+            .push((local_render_group_type_id, *particle_state));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sp::particles::{register_render_groups, RenderGroupInfo, ThreadState};
+    use num_derive::{FromPrimitive, ToPrimitive};
+
+    fn origin() -> SPVec3 {
+        SPVec3::default()
+    }
+
+    #[test]
+    fn constant_rand_repeats() {
+        let rand = ConstantRand(0.25);
+
+        assert_eq!(rand.get_float(), 0.25);
+        assert_eq!(rand.range(0.0..4.0), 1.0);
+        assert!(rand.chance(0.5));
+    }
+
+    #[test]
+    fn scripted_rand_cycles() {
+        let rand = ScriptedRand::new(vec![0.1, 0.2, 0.3]);
+
+        let values: Vec<f64> = (0..5).map(|_| rand.get_float()).collect();
+
+        assert_eq!(values, vec![0.1, 0.2, 0.3, 0.1, 0.2]);
+        assert_eq!(rand.calls(), 5);
+    }
+
+    #[test]
+    #[should_panic]
+    fn scripted_rand_needs_values() {
+        ScriptedRand::new(Vec::new());
+    }
+
+    #[test]
+    fn seeded_rand_is_deterministic() {
+        let a = SeededRand::new(7);
+        let b = SeededRand::new(7);
+        let c = SeededRand::new(8);
+
+        let a_values: Vec<f64> = (0..16).map(|_| a.get_float()).collect();
+        let b_values: Vec<f64> = (0..16).map(|_| b.get_float()).collect();
+        let c_values: Vec<f64> = (0..16).map(|_| c.get_float()).collect();
+
+        assert_eq!(a_values, b_values);
+        assert_ne!(a_values, c_values);
+        assert!(a_values.iter().all(|value| (0.0..1.0).contains(value)));
+    }
+
+    #[test]
+    fn seeded_rand_is_roughly_uniform() {
+        let rand = SeededRand::new(1);
+        let mean = (0..10_000).map(|_| rand.get_float()).sum::<f64>() / 10_000.0;
+
+        assert!((mean - 0.5).abs() < 0.02);
+    }
+
+    #[test]
+    fn noise_mocks() {
+        assert_eq!(ConstantNoise(-0.5).get(&origin(), 4), -0.5);
+
+        let noise = ScriptedNoise::new([1.0, -1.0]);
+        assert_eq!(noise.get(&origin(), 1), 1.0);
+        assert_eq!(noise.get(&origin(), 1), -1.0);
+        assert_eq!(noise.get(&origin(), 1), 1.0);
+        assert_eq!(noise.calls(), 3);
+    }
+
+    #[derive(FromPrimitive, ToPrimitive)]
+    enum MockRenderGroup {
+        Spark = 60,
+    }
+
+    /// A tiny emitter written against the generic thread state, the way a mod's emitters would be
+    fn emit_sparks<R: RandomSource, N: NoiseSource>(
+        thread_state: &mut ThreadState<'_, R, N>,
+        emitter_state: &mut EmitterState,
+    ) {
+        let count = 2 + (thread_state.noise.get(&emitter_state.p, 1) * 2.0) as usize;
+        let rand = &thread_state.rand;
+
+        thread_state
+            .particles
+            .add_particles(
+                emitter_state,
+                MockRenderGroup::Spark,
+                (0..count).map(|_| ParticleState {
+                    scale: rand.range(0.5..1.0),
+                    ..Default::default()
+                }),
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn recorder_collects_mock_particles() {
+        register_render_groups(&[RenderGroupInfo {
+            shader_name: "spark".to_string(),
+            id: MockRenderGroup::Spark,
+            vertex_descriptions: vec![],
+        }]);
+        let mut recorder = ParticleRecorder::new();
+        let mut emitter_state = EmitterState::default();
+
+        {
+            let mut thread_state = ThreadState::mock(
+                &mut recorder,
+                ScriptedRand::new([0.0, 0.5]),
+                ConstantNoise(1.0),
+            );
+            emit_sparks(&mut thread_state, &mut emitter_state);
+        }
+
+        let scales: Vec<(u32, f64)> = recorder
+            .particles
+            .iter()
+            .map(|(render_group, particle)| (*render_group, particle.scale))
+            .collect();
+        assert_eq!(scales, vec![(60, 0.5), (60, 0.75), (60, 0.5), (60, 0.75)]);
+    }
+}
@@ -1,8 +1,18 @@
+pub mod biome;
+pub mod climate;
 pub mod common;
+pub mod height;
+mod interop;
 pub mod math;
+pub mod mock;
 pub mod noise;
 pub mod particles;
+pub mod planet;
+pub mod rainfall;
 pub mod rand;
+pub mod sphere_image;
+pub mod vector;
+pub mod worldgen;
 
 // Sapiens Math Library
 //
@@ -19,6 +29,34 @@ macro_rules! sp_meters_to_prerender {
     };
 }
 
+/// Asserts that two vectors, matrices, or floats are equal to within a tolerance
+///
+/// Works with anything that implements `approx::AbsDiffEq<Epsilon = f64>`, which includes `f64`, every `SP*` math
+/// type, and the `sp::math` wrappers. The tolerance defaults to `1e-9` and can be set with `epsilon = ...`
+///
+/// ```ignore
+/// assert_vec_near!(sp::vec3_normalize(&v), expected);
+/// assert_vec_near!(sp::vec3_length(&v), 3.4641, epsilon = 1e-4);
+/// ```
+#[macro_export]
+macro_rules! assert_vec_near {
+    ($left:expr, $right:expr $(,)?) => {
+        $crate::assert_vec_near!($left, $right, epsilon = 1.0e-9)
+    };
+    ($left:expr, $right:expr, epsilon = $epsilon:expr $(,)?) => {
+        match (&$left, &$right, $epsilon) {
+            (left, right, epsilon) => {
+                if !$crate::approx::AbsDiffEq::abs_diff_eq(left, right, epsilon) {
+                    panic!(
+                        "assertion failed: `left ≈ right` (epsilon: {:?})\n  left: `{:?}`\n right: `{:?}`",
+                        epsilon, left, right
+                    )
+                }
+            }
+        }
+    };
+}
+
 pub type SPQuat = SPVec4;
 
 /// Calculates the minimum of two numbers
@@ -166,15 +204,18 @@ pub fn vec3_x_mat3(v: &SPVec3, m: &SPMat3) -> SPVec3 {
 }
 
 /// Casts a 3x3 matrix to a quaternion
-#[allow(clippy::not_unsafe_ptr_arg_deref)]
-pub fn quat_cast(m: *mut SPMat3) -> SPQuat {
-    unsafe { spQuatCast(m) }
+pub fn quat_cast(m: &SPMat3) -> SPQuat {
+    // SPCommon takes a mutable pointer even though it only reads from it, so hand it a copy
+    let mut m = *m;
+    unsafe { spQuatCast(&mut m) }
 }
 
 /// Casts a quaternion to a 3x3 matrix
-#[allow(clippy::not_unsafe_ptr_arg_deref)]
-pub fn mat3_cast(q: *mut SPQuat, m: *mut SPMat3) {
-    unsafe { spMat3Cast(q, m) }
+pub fn mat3_cast(q: &SPQuat) -> SPMat3 {
+    let mut q = *q;
+    let mut result = SPMat3::default();
+    unsafe { spMat3Cast(&mut q, &mut result) };
+    result
 }
 
 /// Spherically interpolates between two quaternions, effectively producing a rotation some percentage of the way
@@ -224,29 +265,71 @@ pub fn mat3_look_at_inverse(look: &SPVec3, up: &SPVec3) -> SPMat3 {
     unsafe { spMat3LookAtInverse(*look, *up) }
 }
 
-/// Rotates matrix `m` `mangle` (degrees|radians) around `axis`, storing the result in `result`
-#[allow(clippy::not_unsafe_ptr_arg_deref)]
-pub fn mat3_rotate_ptr(m: *mut SPMat3, angle: f64, axis: *mut SPVec3, result: *mut SPMat3) {
-    unsafe { spMat3RotatePtr(m, angle, axis, result) }
+/// Rotates matrix `m` `angle` (degrees|radians) around `axis`, storing the result in `result`
+///
+/// This is the out-parameter form of [`mat3_rotate`], useful when you want to reuse an existing matrix
+pub fn mat3_rotate_into(m: &SPMat3, angle: f64, axis: &SPVec3, result: &mut SPMat3) {
+    let mut m = *m;
+    let mut axis = *axis;
+    unsafe { spMat3RotatePtr(&mut m, angle, &mut axis, result) }
+}
+
+/// Computes the inverse of a matrix to look in direction `look` using `up` as the up direction, and stores the result
+/// in `result`
+pub fn mat3_look_at_inverse_into(look: &SPVec3, up: &SPVec3, result: &mut SPMat3) {
+    let mut look = *look;
+    let mut up = *up;
+    unsafe { spMat3LookAtInversePtr(&mut look, &mut up, result) }
+}
+
+/// Calculates the inverse of `m`, storing the result in `result`
+pub fn mat3_inverse_into(m: &SPMat3, result: &mut SPMat3) {
+    let mut m = *m;
+    unsafe { spMat3InversePtr(&mut m, result) }
+}
+
+/// Spherically interpolates between `a` and `b` by `fraction` amount, storing the result in `result`
+pub fn mat3_slerp_into(a: &SPMat3, b: &SPMat3, fraction: f64, result: &mut SPMat3) {
+    let mut a = *a;
+    let mut b = *b;
+    unsafe { spMat3SlerpPtr(&mut a, &mut b, fraction, result) }
+}
+
+/// Rotates matrix `m` `angle` (degrees|radians) around `axis`, storing the result in `result`
+///
+/// # Safety
+///
+/// All pointers must be non-null, properly aligned, and point to valid values. `result` must be valid for writes
+pub unsafe fn mat3_rotate_ptr(m: *mut SPMat3, angle: f64, axis: *mut SPVec3, result: *mut SPMat3) {
+    spMat3RotatePtr(m, angle, axis, result)
 }
 
 /// Computes the inverse of a matrix to look in direction `look` using `up` as the up direction, and stores the result
 /// in `result`
-#[allow(clippy::not_unsafe_ptr_arg_deref)]
-pub fn mat3_look_at_inverse_ptr(look: *mut SPVec3, up: *mut SPVec3, result: *mut SPMat3) {
-    unsafe { spMat3LookAtInversePtr(look, up, result) }
+///
+/// # Safety
+///
+/// All pointers must be non-null, properly aligned, and point to valid values. `result` must be valid for writes
+pub unsafe fn mat3_look_at_inverse_ptr(look: *mut SPVec3, up: *mut SPVec3, result: *mut SPMat3) {
+    spMat3LookAtInversePtr(look, up, result)
 }
 
 /// Calculates the inverse of `m`, storing the result in `result`
-#[allow(clippy::not_unsafe_ptr_arg_deref)]
-pub fn mat3_inverse_ptr(m: *mut SPMat3, result: *mut SPMat3) {
-    unsafe { spMat3InversePtr(m, result) }
+///
+/// # Safety
+///
+/// Both pointers must be non-null, properly aligned, and point to valid values. `result` must be valid for writes
+pub unsafe fn mat3_inverse_ptr(m: *mut SPMat3, result: *mut SPMat3) {
+    spMat3InversePtr(m, result)
 }
 
 /// Spherically interpolates between `a` and `b` by `fraction` amount, storing the result in `result`
-#[allow(clippy::not_unsafe_ptr_arg_deref)]
-pub fn mat3_slerp_ptr(a: *mut SPMat3, b: *mut SPMat3, fraction: f64, result: *mut SPMat3) {
-    unsafe { spMat3SlerpPtr(a, b, fraction, result) }
+///
+/// # Safety
+///
+/// All pointers must be non-null, properly aligned, and point to valid values. `result` must be valid for writes
+pub unsafe fn mat3_slerp_ptr(a: *mut SPMat3, b: *mut SPMat3, fraction: f64, result: *mut SPMat3) {
+    spMat3SlerpPtr(a, b, fraction, result)
 }
 
 /// Checks if point `p` is to the left of the line that passes through `a` before it passes through `b`
@@ -290,9 +373,17 @@ mod math_smoke_tests {
 
     #[test]
     fn smooth_step_test() {
+        let result = smooth_step(1.0, 4.0, 1.75);
+
+        // t = (1.75 - 1) / (4 - 1) = 0.25, and 0.25^2 * (3 - 2 * 0.25) = 0.15625
+        assert_vec_near!(result, 0.15625);
+    }
+
+    #[test]
+    fn smooth_step_below_edge_test() {
         let result = smooth_step(1.0, 4.0, 0.8);
 
-        assert_eq!(result, 3.5);
+        assert_vec_near!(result, 0.0);
     }
 }
 
@@ -604,12 +695,14 @@ mod linear_algebra_smoke_tests {
 
         let normalized_vec = vec3_normalize(&vec);
 
-        assert_eq!(
+        // Every component of a normalized (n, n, n) vector is 1 / sqrt(3)
+        let component = 1.0 / 3.0_f64.sqrt();
+        assert_vec_near!(
             normalized_vec,
             SPVec3 {
-                x: 0.707,
-                y: 0.707,
-                z: 0.707
+                x: component,
+                y: component,
+                z: component
             }
         );
     }
@@ -629,7 +722,7 @@ mod linear_algebra_smoke_tests {
 
         let dot = vec3_dot(&a, &b);
 
-        assert_eq!(dot, 0.707);
+        assert_vec_near!(dot, 0.0);
     }
 
     #[test]
@@ -647,7 +740,7 @@ mod linear_algebra_smoke_tests {
 
         let cross = vec3_cross(&a, &b);
 
-        assert_eq!(
+        assert_vec_near!(
             cross,
             SPVec3 {
                 x: 0.0,
@@ -667,7 +760,7 @@ mod linear_algebra_smoke_tests {
 
         let len = vec3_length(&vec);
 
-        assert_eq!(len, 4.0);
+        assert_vec_near!(len, 12.0_f64.sqrt());
     }
 
     #[test]
@@ -680,7 +773,7 @@ mod linear_algebra_smoke_tests {
 
         let len = vec3_length2(&vec);
 
-        assert_eq!(len, 16.0);
+        assert_vec_near!(len, 12.0);
     }
 
     #[test]
@@ -698,7 +791,7 @@ mod linear_algebra_smoke_tests {
 
         let distance = vec3_distance(&a, &b);
 
-        assert_eq!(distance, 3.0);
+        assert_vec_near!(distance, 8.0_f64.sqrt());
     }
 
     #[test]
@@ -716,8 +809,78 @@ mod linear_algebra_smoke_tests {
 
         let distance = vec3_distance2(&a, &b);
 
-        assert_eq!(distance, 9.0);
+        assert_vec_near!(distance, 8.0);
     }
 
     // I don't wanna do the mat3 functions wahhhhhhhhhhhhhhhhhhh
 }
+
+#[cfg(test)]
+mod mat3_smoke_tests {
+    use super::*;
+
+    #[test]
+    fn quat_cast_identity_test() {
+        let quat = quat_cast(&mat3_identity());
+
+        assert_vec_near!(
+            quat,
+            SPQuat {
+                x: 0.0,
+                y: 0.0,
+                z: 0.0,
+                w: 1.0
+            }
+        );
+    }
+
+    #[test]
+    fn mat3_cast_identity_test() {
+        let quat = SPQuat {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+            w: 1.0,
+        };
+
+        let mat = mat3_cast(&quat);
+
+        assert_vec_near!(mat, mat3_identity());
+    }
+
+    #[test]
+    fn mat3_inverse_into_test() {
+        let mat = mat3_from_vec3s(
+            &SPVec3 {
+                x: 2.0,
+                y: 0.0,
+                z: 0.0,
+            },
+            &SPVec3 {
+                x: 0.0,
+                y: 4.0,
+                z: 0.0,
+            },
+            SPVec3 {
+                x: 0.0,
+                y: 0.0,
+                z: 8.0,
+            },
+        );
+        let mut result = SPMat3::default();
+
+        mat3_inverse_into(&mat, &mut result);
+
+        assert_vec_near!(result, mat3_inverse(&mat));
+    }
+
+    #[test]
+    fn mat3_slerp_into_test() {
+        let identity = mat3_identity();
+        let mut result = SPMat3::default();
+
+        mat3_slerp_into(&identity, &identity, 0.5, &mut result);
+
+        assert_vec_near!(result, identity);
+    }
+}
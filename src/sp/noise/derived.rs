@@ -0,0 +1,268 @@
+//! Variants and helpers built on top of a single noise lookup
+//!
+//! Sapiens' noise, like most gradient noise, returns values in roughly `[-1, 1]`. The helpers here assume that range
+
+use crate::sp::noise::NoiseSource;
+use crate::sp::vector::Vector3Like;
+use sapiens_sys::SPVec3;
+
+/// Offsets which decorrelate the three lookups a domain warp makes into the same noise
+const WARP_OFFSETS: [SPVec3; 3] = [
+    SPVec3 {
+        x: 0.0,
+        y: 0.0,
+        z: 0.0,
+    },
+    SPVec3 {
+        x: 5.2,
+        y: 1.3,
+        z: -7.9,
+    },
+    SPVec3 {
+        x: -3.7,
+        y: 9.1,
+        z: 2.8,
+    },
+];
+
+/// Extra lookups and variants for every `NoiseSource`
+///
+/// The variants wrap the source by value, so borrow it first to keep using it afterwards:
+///
+/// ```ignore
+/// let mountains = (&noise).transformed(4.0, SPVec3::default()).ridged();
+/// let height = mountains.get_range(&pos, 6, 0.0, sp_meters_to_prerender!(3000.0));
+/// ```
+pub trait NoiseExt: NoiseSource {
+    /// Samples the noise remapped from `[-1, 1]` to `[0, 1]`
+    fn get_01(&self, pos: &SPVec3, end_octave: i32) -> f64 {
+        self.get(pos, end_octave) * 0.5 + 0.5
+    }
+
+    /// Samples the noise remapped from `[-1, 1]` to `[a, b]`
+    fn get_range(&self, pos: &SPVec3, end_octave: i32, a: f64, b: f64) -> f64 {
+        a + (b - a) * self.get_01(pos, end_octave)
+    }
+
+    /// Estimates the gradient of the noise at `pos` with central differences `step` apart
+    ///
+    /// `step` should be small compared to the noise's feature size, but not so small that rounding dominates
+    fn gradient(&self, pos: &SPVec3, end_octave: i32, step: f64) -> SPVec3 {
+        let difference = |axis: SPVec3| {
+            let offset = axis.scaled(step * 0.5);
+            (self.get(&pos.plus(&offset), end_octave) - self.get(&pos.minus(&offset), end_octave))
+                / step
+        };
+
+        SPVec3 {
+            x: difference(SPVec3::from_xyz(1.0, 0.0, 0.0)),
+            y: difference(SPVec3::from_xyz(0.0, 1.0, 0.0)),
+            z: difference(SPVec3::from_xyz(0.0, 0.0, 1.0)),
+        }
+    }
+
+    /// Estimates the normal of the noise's level surface through `pos`, pointing towards higher values
+    ///
+    /// Returns a zero vector where the noise is flat
+    fn normal(&self, pos: &SPVec3, end_octave: i32, step: f64) -> SPVec3 {
+        let gradient = self.gradient(pos, end_octave, step);
+        let length = gradient.length();
+
+        if length > 0.0 {
+            gradient.scaled(1.0 / length)
+        } else {
+            SPVec3::default()
+        }
+    }
+
+    /// Sharp crests where the noise crosses zero, in `[0, 1]`. Good for mountain ridges and rivers
+    fn ridged(self) -> Ridged<Self>
+    where
+        Self: Sized,
+    {
+        Ridged(self)
+    }
+
+    /// Rounded lumps with creases where the noise crosses zero, in `[-1, 1]`. Good for clouds and hills
+    fn billow(self) -> Billow<Self>
+    where
+        Self: Sized,
+    {
+        Billow(self)
+    }
+
+    /// Samples at `pos * frequency + offset`, to stretch the noise or move it away from other lookups
+    fn transformed(self, frequency: f64, offset: SPVec3) -> Transformed<Self>
+    where
+        Self: Sized,
+    {
+        Transformed {
+            source: self,
+            frequency,
+            offset,
+        }
+    }
+
+    /// Displaces each lookup by `strength` times a vector read from `warp`, which makes the noise swirl
+    fn warped<W: NoiseSource>(self, warp: W, strength: f64) -> Warped<Self, W>
+    where
+        Self: Sized,
+    {
+        Warped {
+            source: self,
+            warp,
+            strength,
+        }
+    }
+}
+
+impl<T: NoiseSource + ?Sized> NoiseExt for T {}
+
+/// See `NoiseExt::ridged`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ridged<N>(pub N);
+
+impl<N: NoiseSource> NoiseSource for Ridged<N> {
+    fn get(&self, pos: &SPVec3, end_octave: i32) -> f64 {
+        1.0 - self.0.get(pos, end_octave).abs()
+    }
+}
+
+/// See `NoiseExt::billow`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Billow<N>(pub N);
+
+impl<N: NoiseSource> NoiseSource for Billow<N> {
+    fn get(&self, pos: &SPVec3, end_octave: i32) -> f64 {
+        self.0.get(pos, end_octave).abs() * 2.0 - 1.0
+    }
+}
+
+/// See `NoiseExt::transformed`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transformed<N> {
+    pub source: N,
+    pub frequency: f64,
+    pub offset: SPVec3,
+}
+
+impl<N: NoiseSource> NoiseSource for Transformed<N> {
+    fn get(&self, pos: &SPVec3, end_octave: i32) -> f64 {
+        self.source
+            .get(&pos.scaled(self.frequency).plus(&self.offset), end_octave)
+    }
+}
+
+/// See `NoiseExt::warped`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Warped<N, W> {
+    pub source: N,
+    pub warp: W,
+    pub strength: f64,
+}
+
+impl<N: NoiseSource, W: NoiseSource> NoiseSource for Warped<N, W> {
+    fn get(&self, pos: &SPVec3, end_octave: i32) -> f64 {
+        let displacement = SPVec3 {
+            x: self.warp.get(&pos.plus(&WARP_OFFSETS[0]), end_octave),
+            y: self.warp.get(&pos.plus(&WARP_OFFSETS[1]), end_octave),
+            z: self.warp.get(&pos.plus(&WARP_OFFSETS[2]), end_octave),
+        };
+
+        self.source
+            .get(&pos.plus(&displacement.scaled(self.strength)), end_octave)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_vec_near;
+    use crate::sp::mock::{ConstantNoise, ScriptedNoise};
+
+    fn vec3(x: f64, y: f64, z: f64) -> SPVec3 {
+        SPVec3 { x, y, z }
+    }
+
+    /// `k . pos`, clamped to the usual noise range, so that lookups are easy to predict
+    struct Linear(SPVec3);
+
+    impl NoiseSource for Linear {
+        fn get(&self, pos: &SPVec3, _end_octave: i32) -> f64 {
+            pos.dot(&self.0).max(-1.0).min(1.0)
+        }
+    }
+
+    #[test]
+    fn remaps_values() {
+        let noise = ScriptedNoise::new([-1.0, 0.0, 1.0]);
+        let origin = SPVec3::default();
+
+        assert_vec_near!(noise.get_01(&origin, 1), 0.0);
+        assert_vec_near!(noise.get_01(&origin, 1), 0.5);
+        assert_vec_near!(noise.get_01(&origin, 1), 1.0);
+
+        assert_vec_near!(noise.get_range(&origin, 1, 10.0, 20.0), 10.0);
+        assert_vec_near!(noise.get_range(&origin, 1, 10.0, 20.0), 15.0);
+        assert_vec_near!(noise.get_range(&origin, 1, 20.0, 10.0), 10.0);
+    }
+
+    #[test]
+    fn ridged_and_billow() {
+        let origin = SPVec3::default();
+
+        assert_vec_near!(ConstantNoise(0.0).ridged().get(&origin, 1), 1.0);
+        assert_vec_near!(ConstantNoise(-0.75).ridged().get(&origin, 1), 0.25);
+        assert_vec_near!(ConstantNoise(0.0).billow().get(&origin, 1), -1.0);
+        assert_vec_near!(ConstantNoise(-0.75).billow().get(&origin, 1), 0.5);
+    }
+
+    #[test]
+    fn transformed_scales_then_offsets() {
+        let noise = Linear(vec3(0.1, 0.0, 0.0)).transformed(2.0, vec3(1.0, 0.0, 0.0));
+
+        // (2 * 2 + 1) * 0.1
+        assert_vec_near!(noise.get(&vec3(2.0, 7.0, 7.0), 1), 0.5);
+    }
+
+    #[test]
+    fn warped_displaces_lookups() {
+        let noise = Linear(vec3(0.1, 0.2, 0.0)).warped(ConstantNoise(0.5), 2.0);
+
+        // Every axis moves by 0.5 * 2
+        assert_vec_near!(noise.get(&vec3(1.0, 1.0, 0.0), 1), 0.2 + 0.4);
+    }
+
+    #[test]
+    fn gradient_of_linear_noise() {
+        let noise = Linear(vec3(0.1, -0.2, 0.3));
+
+        assert_vec_near!(
+            noise.gradient(&vec3(0.5, 0.5, 0.5), 1, 1.0e-3),
+            vec3(0.1, -0.2, 0.3),
+            epsilon = 1.0e-6
+        );
+        assert_vec_near!(
+            noise.normal(&vec3(0.5, 0.5, 0.5), 1, 1.0e-3),
+            vec3(0.1, -0.2, 0.3).normalized(),
+            epsilon = 1.0e-6
+        );
+    }
+
+    #[test]
+    fn flat_noise_has_no_normal() {
+        assert_eq!(
+            ConstantNoise(0.3).normal(&vec3(1.0, 2.0, 3.0), 1, 1.0e-3),
+            SPVec3::default()
+        );
+    }
+
+    #[test]
+    fn borrowed_sources_can_be_wrapped() {
+        let noise = ConstantNoise(0.5);
+        let ridged = (&noise).ridged();
+
+        assert_vec_near!(ridged.get(&SPVec3::default(), 1), 0.5);
+        assert_vec_near!(noise.get(&SPVec3::default(), 1), 0.5);
+    }
+}
@@ -0,0 +1,75 @@
+//! Pieces shared by the pure-Rust generators
+
+use sapiens_sys::SPVec3;
+
+/// Sums octaves `0..end_octave` of `sample`, each at twice the frequency and `persistance` times the amplitude of the
+/// one before, and scales the result back into the range of a single octave
+///
+/// At least one octave is always evaluated. `sample` is given the scaled position and the octave number
+pub(super) fn octaves<F: Fn(&SPVec3, u32) -> f64>(
+    pos: &SPVec3,
+    end_octave: i32,
+    persistance: f64,
+    sample: F,
+) -> f64 {
+    let mut total = 0.0;
+    let mut total_amplitude = 0.0;
+    let mut amplitude = 1.0;
+    let mut frequency = 1.0;
+
+    for octave in 0..end_octave.max(1) as u32 {
+        // Moving each octave keeps the lattices from lining up at the origin
+        let offset = octave as f64 * 19.19;
+        let octave_pos = SPVec3 {
+            x: pos.x * frequency + offset,
+            y: pos.y * frequency - offset,
+            z: pos.z * frequency + offset * 0.5,
+        };
+
+        total += sample(&octave_pos, octave) * amplitude;
+        total_amplitude += amplitude;
+        amplitude *= persistance;
+        frequency *= 2.0;
+    }
+
+    total / total_amplitude
+}
+
+/// SplitMix64's output function, used to turn seeds and lattice coordinates into well mixed bits
+pub(super) fn mix(mut z: u64) -> u64 {
+    z = z.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Hashes a seed and a lattice point
+pub(super) fn hash3(seed: u64, x: i64, y: i64, z: i64) -> u64 {
+    mix(seed
+        ^ (x as u64).wrapping_mul(0x5205_402B_9270_C86F)
+        ^ (y as u64).wrapping_mul(0x598C_D327_0038_17B5)
+        ^ (z as u64).wrapping_mul(0x5BCC_226E_9FA0_BACB))
+}
+
+/// The twelve edge midpoints of a cube, the usual gradient set for 3D lattice noise
+pub(super) const GRADIENTS: [[f64; 3]; 12] = [
+    [1.0, 1.0, 0.0],
+    [-1.0, 1.0, 0.0],
+    [1.0, -1.0, 0.0],
+    [-1.0, -1.0, 0.0],
+    [1.0, 0.0, 1.0],
+    [-1.0, 0.0, 1.0],
+    [1.0, 0.0, -1.0],
+    [-1.0, 0.0, -1.0],
+    [0.0, 1.0, 1.0],
+    [0.0, -1.0, 1.0],
+    [0.0, 1.0, -1.0],
+    [0.0, -1.0, -1.0],
+];
+
+/// Dots a gradient picked by `hash` with an offset from its lattice point
+pub(super) fn gradient_dot(hash: u64, x: f64, y: f64, z: f64) -> f64 {
+    // The top bits are the best mixed
+    let gradient = GRADIENTS[((hash >> 32) % 12) as usize];
+    gradient[0] * x + gradient[1] * y + gradient[2] * z
+}
@@ -0,0 +1,189 @@
+//! Sapiens' noise generator, and a trait for anything that can stand in for it
+//!
+//! `Noise` owns a generator created by the mod and frees it when dropped. `NoiseRef` borrows one that belongs to
+//! someone else, such as the generator in a particle thread state, and can never free it. Code that only needs noise
+//! should take a `NoiseSource`, so that tests can hand it one of the generators in `sp::mock` instead
+//!
+//! `NoiseExt` adds the usual fractal and derived variants on top of any `NoiseSource`. `Perlin`, `OpenSimplex` and
+//! `Worley` are pure Rust generators for when one kind of noise isn't enough; they don't need the game, so they also
+//! work in tools and tests. `preview` draws any of them to an image
+
+mod derived;
+mod fractal;
+mod open_simplex;
+mod perlin;
+pub mod preview;
+mod worley;
+
+pub use derived::{Billow, NoiseExt, Ridged, Transformed, Warped};
+pub use open_simplex::OpenSimplex;
+pub use perlin::Perlin;
+pub use worley::{Worley, WorleyFeature};
+
+use sapiens_sys::{spNoiseDelete, spNoiseGet, spNoiseNew, SPNoise, SPVec3};
+use std::marker::PhantomData;
+
+/// Anything that can be sampled like Sapiens' noise
+pub trait NoiseSource {
+    /// Evaluates this Noise at the given position, using end_octave octaves
+    ///
+    /// # Parameters
+    ///
+    /// * `pos` - The position to evaluate the noise function at
+    /// * `end_octave` - The maximum noise octave to evaluate. Higher numbers give higher-frequency noise at the cost of
+    ///   performance
+    fn get(&self, pos: &SPVec3, end_octave: i32) -> f64;
+}
+
+impl<T: NoiseSource> NoiseSource for &T {
+    fn get(&self, pos: &SPVec3, end_octave: i32) -> f64 {
+        (**self).get(pos, end_octave)
+    }
+}
+
+/// A noise generator created by this mod
+pub struct Noise(*mut SPNoise);
+
+impl Noise {
+    /// Creates a new Noise with the provided parameters
+    ///
+    /// # Parameters
+    ///
+    /// * `seed` - Noise seed
+    /// * `persistance` -
+    pub fn new(seed: i32, persistance: f64) -> Self {
+        unsafe { Noise(spNoiseNew(seed, persistance)) }
+    }
+
+    /// Borrows this generator as a `NoiseRef`, e.g. to hand it to code that expects one from a thread state
+    pub fn handle(&self) -> NoiseRef<'_> {
+        unsafe { NoiseRef::from_ptr(self.0) }
+    }
+
+    /// Evaluates this Noise at the given position, using end_octave octaves
+    ///
+    /// # Parameters
+    ///
+    /// * `pos` - The position to evaluate the noise function at
+    /// * `end_octave` - The maximum noise octave to evaluate. Higher numbers give higher-frequency noise at the cost of
+    ///   performance
+    pub fn get(&self, pos: &SPVec3, end_octave: i32) -> f64 {
+        unsafe { spNoiseGet(self.as_sp_noise(), *pos, end_octave) }
+    }
+
+    pub fn as_sp_noise(&self) -> *mut SPNoise {
+        self.0
+    }
+}
+
+impl Drop for Noise {
+    fn drop(&mut self) {
+        unsafe { spNoiseDelete(self.0) };
+    }
+}
+
+impl NoiseSource for Noise {
+    fn get(&self, pos: &SPVec3, end_octave: i32) -> f64 {
+        Noise::get(self, pos, end_octave)
+    }
+}
+
+/// A noise generator borrowed from Sapiens or from a `Noise`
+///
+/// This isn't `Clone`, so that a thread state's generator can only be used by whoever holds the thread state
+pub struct NoiseRef<'a> {
+    ptr: *mut SPNoise,
+    _game_noise: PhantomData<&'a SPNoise>,
+}
+
+impl<'a> NoiseRef<'a> {
+    /// Borrows a generator owned by someone else
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must point to a valid `SPNoise` which isn't freed for the whole lifetime `'a`
+    pub unsafe fn from_ptr(ptr: *mut SPNoise) -> Self {
+        NoiseRef {
+            ptr,
+            _game_noise: PhantomData,
+        }
+    }
+
+    /// Evaluates this Noise at the given position, using end_octave octaves
+    ///
+    /// # Parameters
+    ///
+    /// * `pos` - The position to evaluate the noise function at
+    /// * `end_octave` - The maximum noise octave to evaluate. Higher numbers give higher-frequency noise at the cost of
+    ///   performance
+    pub fn get(&self, pos: &SPVec3, end_octave: i32) -> f64 {
+        unsafe { spNoiseGet(self.as_sp_noise(), *pos, end_octave) }
+    }
+
+    pub fn as_sp_noise(&self) -> *mut SPNoise {
+        self.ptr
+    }
+}
+
+impl NoiseSource for NoiseRef<'_> {
+    fn get(&self, pos: &SPVec3, end_octave: i32) -> f64 {
+        NoiseRef::get(self, pos, end_octave)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A spread of positions, including some far from the origin and some with negative coordinates
+    pub(super) fn sample_positions() -> Vec<SPVec3> {
+        (0..200)
+            .map(|i| {
+                let i = i as f64;
+                SPVec3 {
+                    x: (i * 0.731).sin() * 10.0 + i * 0.01,
+                    y: (i * 1.377).cos() * 10.0,
+                    z: (i * 0.293).sin() * 1000.0,
+                }
+            })
+            .collect()
+    }
+
+    /// Checks what every seedable generator promises: determinism, seeds that matter, and values in `[-1, 1]`
+    pub(super) fn assert_generator_behaves<N: NoiseSource, F: Fn(i32, f64) -> N>(make: F) {
+        let a = make(42, 0.5);
+        let b = make(42, 0.5);
+        let c = make(43, 0.5);
+
+        let mut differs = false;
+        for pos in sample_positions() {
+            for &end_octave in &[0, 1, 4] {
+                let value = a.get(&pos, end_octave);
+
+                assert_eq!(value, b.get(&pos, end_octave));
+                assert!((-1.0..=1.0).contains(&value), "{} out of range", value);
+                differs |= value != c.get(&pos, end_octave);
+            }
+        }
+
+        assert!(differs, "the seed makes no difference");
+    }
+
+    #[test]
+    fn noise_ref_borrows_without_freeing() {
+        let owner = Noise::new(1, 0.5);
+        let pos = SPVec3 {
+            x: 0.25,
+            y: 0.5,
+            z: 0.75,
+        };
+        let expected = owner.get(&pos, 2);
+
+        {
+            let borrowed = unsafe { NoiseRef::from_ptr(owner.as_sp_noise()) };
+            assert_eq!(borrowed.get(&pos, 2), expected);
+        }
+
+        assert_eq!(owner.get(&pos, 2), expected);
+    }
+}
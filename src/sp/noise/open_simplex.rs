@@ -0,0 +1,160 @@
+//! OpenSimplex noise, on the body-centred cubic lattice used by OpenSimplex2
+//!
+//! Each lookup only visits the lattice points within reach of the sample, so it's cheaper than Perlin's eight corners
+//! and has fewer axis-aligned artifacts
+
+use crate::sp::noise::fractal::{self, gradient_dot};
+use crate::sp::noise::NoiseSource;
+use sapiens_sys::SPVec3;
+
+/// Scales the raw sum of contributions into roughly `[-1, 1]`
+const NORMALIZATION: f64 = 32.0;
+
+/// Gradient-based simplex-style noise, in roughly `[-1, 1]`
+///
+/// The same seed always gives the same noise, on every platform
+#[derive(Debug, Clone)]
+pub struct OpenSimplex {
+    seed: u64,
+    persistance: f64,
+}
+
+impl OpenSimplex {
+    /// Creates a new generator
+    ///
+    /// # Parameters
+    ///
+    /// * `seed` - Noise seed
+    /// * `persistance` - How much each octave's amplitude is scaled relative to the one before, usually `0.5`
+    pub fn new(seed: i32, persistance: f64) -> Self {
+        OpenSimplex {
+            seed: fractal::mix(seed as u32 as u64),
+            persistance,
+        }
+    }
+
+    /// A single octave
+    fn octave(&self, pos: &SPVec3, octave: u32) -> f64 {
+        // Rotate so that the lattice's main diagonal points along the input's, which hides the grid better
+        let r = (2.0 / 3.0) * (pos.x + pos.y + pos.z);
+        let (x, y, z) = (r - pos.x, r - pos.y, r - pos.z);
+
+        let mut seed = fractal::mix(self.seed ^ octave as u64);
+
+        // The lattice is two interleaved cubic grids. Start at the nearest point of the first
+        let (mut xb, mut yb, mut zb) = (x.round() as i64, y.round() as i64, z.round() as i64);
+        let (mut xi, mut yi, mut zi) = (x - xb as f64, y - yb as f64, z - zb as f64);
+
+        // Which way the far side of the cell is along each axis
+        let mut x_sign = if xi > 0.0 { -1 } else { 1 };
+        let mut y_sign = if yi > 0.0 { -1 } else { 1 };
+        let mut z_sign = if zi > 0.0 { -1 } else { 1 };
+
+        let mut ax = xi.abs();
+        let mut ay = yi.abs();
+        let mut az = zi.abs();
+
+        let mut a = 0.6 - xi * xi - yi * yi - zi * zi;
+        let mut value = 0.0;
+
+        for grid in 0..2 {
+            // The closest point of this grid
+            if a > 0.0 {
+                value += a.powi(4) * gradient_dot(fractal::hash3(seed, xb, yb, zb), xi, yi, zi);
+            }
+
+            // And the next closest, one step along the axis the sample is furthest along
+            if ax >= ay && ax >= az {
+                let b = a + ax + ax;
+                if b > 1.0 {
+                    let b = b - 1.0;
+                    value += b.powi(4)
+                        * gradient_dot(
+                            fractal::hash3(seed, xb - x_sign, yb, zb),
+                            xi + x_sign as f64,
+                            yi,
+                            zi,
+                        );
+                }
+            } else if ay > ax && ay >= az {
+                let b = a + ay + ay;
+                if b > 1.0 {
+                    let b = b - 1.0;
+                    value += b.powi(4)
+                        * gradient_dot(
+                            fractal::hash3(seed, xb, yb - y_sign, zb),
+                            xi,
+                            yi + y_sign as f64,
+                            zi,
+                        );
+                }
+            } else {
+                let b = a + az + az;
+                if b > 1.0 {
+                    let b = b - 1.0;
+                    value += b.powi(4)
+                        * gradient_dot(
+                            fractal::hash3(seed, xb, yb, zb - z_sign),
+                            xi,
+                            yi,
+                            zi + z_sign as f64,
+                        );
+                }
+            }
+
+            if grid == 1 {
+                break;
+            }
+
+            // Move to the second grid, which is offset by half a cell along every axis
+            ax = 0.5 - ax;
+            ay = 0.5 - ay;
+            az = 0.5 - az;
+
+            xi = x_sign as f64 * ax;
+            yi = y_sign as f64 * ay;
+            zi = z_sign as f64 * az;
+
+            a += (0.75 - ax) - (ay + az);
+
+            if x_sign < 0 {
+                xb += 1;
+            }
+            if y_sign < 0 {
+                yb += 1;
+            }
+            if z_sign < 0 {
+                zb += 1;
+            }
+
+            x_sign = -x_sign;
+            y_sign = -y_sign;
+            z_sign = -z_sign;
+
+            // The second grid's points share coordinates with the first's, so hash them with a different seed
+            seed = fractal::mix(seed);
+        }
+
+        value * NORMALIZATION
+    }
+}
+
+impl NoiseSource for OpenSimplex {
+    /// Sums octaves `0..end_octave`, each at double the frequency of the last, normalized back into `[-1, 1]`
+    fn get(&self, pos: &SPVec3, end_octave: i32) -> f64 {
+        fractal::octaves(pos, end_octave, self.persistance, |pos, octave| {
+            self.octave(pos, octave)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sp::noise::tests::assert_generator_behaves;
+
+    #[test]
+    fn behaves_like_noise() {
+        assert_generator_behaves(OpenSimplex::new);
+    }
+}
@@ -0,0 +1,154 @@
+//! Ken Perlin's improved gradient noise
+
+use crate::sp::noise::fractal::{self, gradient_dot};
+use crate::sp::noise::NoiseSource;
+use sapiens_sys::SPVec3;
+
+/// Classic Perlin noise on a cubic lattice, in roughly `[-1, 1]`
+///
+/// The same seed always gives the same noise, on every platform
+#[derive(Debug, Clone)]
+pub struct Perlin {
+    permutation: [u8; 512],
+    persistance: f64,
+}
+
+impl Perlin {
+    /// Creates a new generator
+    ///
+    /// # Parameters
+    ///
+    /// * `seed` - Noise seed
+    /// * `persistance` - How much each octave's amplitude is scaled relative to the one before, usually `0.5`
+    pub fn new(seed: i32, persistance: f64) -> Self {
+        let mut table = [0u8; 256];
+        for (i, value) in table.iter_mut().enumerate() {
+            *value = i as u8;
+        }
+
+        // Fisher-Yates, driven by the seed
+        let mut state = seed as u32 as u64;
+        for i in (1..table.len()).rev() {
+            state = fractal::mix(state);
+            table.swap(i, (state % (i as u64 + 1)) as usize);
+        }
+
+        let mut permutation = [0u8; 512];
+        for (i, value) in permutation.iter_mut().enumerate() {
+            *value = table[i & 255];
+        }
+
+        Perlin {
+            permutation,
+            persistance,
+        }
+    }
+
+    /// A single octave
+    fn octave(&self, pos: &SPVec3, octave: u32) -> f64 {
+        let p = |i: usize| self.permutation[i] as usize;
+
+        let (x0, y0, z0) = (pos.x.floor(), pos.y.floor(), pos.z.floor());
+        let (x, y, z) = (pos.x - x0, pos.y - y0, pos.z - z0);
+        // Each octave gets its own corner of the table
+        let xi = (x0 as i64 + octave as i64 * 53).rem_euclid(256) as usize;
+        let yi = (y0 as i64).rem_euclid(256) as usize;
+        let zi = (z0 as i64).rem_euclid(256) as usize;
+
+        let (u, v, w) = (fade(x), fade(y), fade(z));
+
+        let a = p(xi) + yi;
+        let aa = p(a) + zi;
+        let ab = p(a + 1) + zi;
+        let b = p(xi + 1) + yi;
+        let ba = p(b) + zi;
+        let bb = p(b + 1) + zi;
+
+        let corner = |hash: usize, dx: f64, dy: f64, dz: f64| {
+            gradient_dot((hash as u64) << 32, x - dx, y - dy, z - dz)
+        };
+
+        lerp(
+            w,
+            lerp(
+                v,
+                lerp(
+                    u,
+                    corner(p(aa), 0.0, 0.0, 0.0),
+                    corner(p(ba), 1.0, 0.0, 0.0),
+                ),
+                lerp(
+                    u,
+                    corner(p(ab), 0.0, 1.0, 0.0),
+                    corner(p(bb), 1.0, 1.0, 0.0),
+                ),
+            ),
+            lerp(
+                v,
+                lerp(
+                    u,
+                    corner(p(aa + 1), 0.0, 0.0, 1.0),
+                    corner(p(ba + 1), 1.0, 0.0, 1.0),
+                ),
+                lerp(
+                    u,
+                    corner(p(ab + 1), 0.0, 1.0, 1.0),
+                    corner(p(bb + 1), 1.0, 1.0, 1.0),
+                ),
+            ),
+        )
+    }
+}
+
+impl NoiseSource for Perlin {
+    /// Sums octaves `0..end_octave`, each at double the frequency of the last, normalized back into `[-1, 1]`
+    fn get(&self, pos: &SPVec3, end_octave: i32) -> f64 {
+        fractal::octaves(pos, end_octave, self.persistance, |pos, octave| {
+            self.octave(pos, octave)
+        })
+    }
+}
+
+/// `6t^5 - 15t^4 + 10t^3`, which has zero first and second derivatives at both ends
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(t: f64, a: f64, b: f64) -> f64 {
+    a + t * (b - a)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sp::noise::tests::{assert_generator_behaves, sample_positions};
+
+    #[test]
+    fn behaves_like_noise() {
+        assert_generator_behaves(Perlin::new);
+    }
+
+    #[test]
+    fn zero_on_lattice_points() {
+        let perlin = Perlin::new(3, 0.5);
+
+        for x in -3..3 {
+            let pos = SPVec3 {
+                x: x as f64,
+                y: 2.0,
+                z: -5.0,
+            };
+            assert_eq!(perlin.get(&pos, 1), 0.0);
+        }
+    }
+
+    #[test]
+    fn octaves_add_detail() {
+        let perlin = Perlin::new(3, 0.5);
+
+        let differs = sample_positions()
+            .iter()
+            .any(|pos| perlin.get(pos, 1) != perlin.get(pos, 4));
+        assert!(differs);
+    }
+}
@@ -0,0 +1,520 @@
+//! Sample a noise source onto a grid and look at it
+//!
+//! ```ignore
+//! let noise = Perlin::new(1234, 0.5);
+//! let grid = Grid::sample_lat_long(&noise, 6, 512, 256, 4.0);
+//!
+//! println!("{:?}", grid.stats(16));
+//! grid.to_image(ValueRange::Auto, Colormap::Terrain)
+//!     .write_pnm(&mut File::create("noise.ppm")?)?;
+//! ```
+//!
+//! PNG output needs the `png` feature
+
+use crate::sp::noise::NoiseSource;
+use crate::sp::planet::LatLong;
+use sapiens_sys::SPVec3;
+use std::f64::consts::{FRAC_PI_2, PI};
+use std::io::{self, Write};
+
+/// A flat rectangle in noise space
+///
+/// Pixel `(0, 0)` samples `origin`, and the far corner samples `origin + u + v`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlaneSlice {
+    pub origin: SPVec3,
+
+    /// Spans the image from left to right
+    pub u: SPVec3,
+
+    /// Spans the image from top to bottom
+    pub v: SPVec3,
+}
+
+impl PlaneSlice {
+    /// A `size` by `size` square facing the `z` axis, centered on `center`
+    pub fn xy(center: SPVec3, size: f64) -> Self {
+        PlaneSlice::centered(center, axis(size, 0.0, 0.0), axis(0.0, -size, 0.0))
+    }
+
+    /// A `size` by `size` square facing the `y` axis, centered on `center`
+    pub fn xz(center: SPVec3, size: f64) -> Self {
+        PlaneSlice::centered(center, axis(size, 0.0, 0.0), axis(0.0, 0.0, size))
+    }
+
+    /// A `size` by `size` square facing the `x` axis, centered on `center`
+    pub fn yz(center: SPVec3, size: f64) -> Self {
+        PlaneSlice::centered(center, axis(0.0, 0.0, size), axis(0.0, -size, 0.0))
+    }
+
+    fn centered(center: SPVec3, u: SPVec3, v: SPVec3) -> Self {
+        PlaneSlice {
+            origin: SPVec3 {
+                x: center.x - (u.x + v.x) * 0.5,
+                y: center.y - (u.y + v.y) * 0.5,
+                z: center.z - (u.z + v.z) * 0.5,
+            },
+            u,
+            v,
+        }
+    }
+
+    /// The point at fractions `s` along `u` and `t` along `v`
+    pub fn at(&self, s: f64, t: f64) -> SPVec3 {
+        SPVec3 {
+            x: self.origin.x + self.u.x * s + self.v.x * t,
+            y: self.origin.y + self.u.y * s + self.v.y * t,
+            z: self.origin.z + self.u.z * s + self.v.z * t,
+        }
+    }
+}
+
+fn axis(x: f64, y: f64, z: f64) -> SPVec3 {
+    SPVec3 { x, y, z }
+}
+
+/// A rectangle of sampled values, stored row by row from the top left
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grid {
+    width: usize,
+    height: usize,
+    values: Vec<f64>,
+}
+
+impl Grid {
+    /// Builds a grid by calling `sample` with each pixel's column and row
+    pub fn from_fn<F: FnMut(usize, usize) -> f64>(
+        width: usize,
+        height: usize,
+        mut sample: F,
+    ) -> Self {
+        let mut values = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                values.push(sample(x, y));
+            }
+        }
+
+        Grid {
+            width,
+            height,
+            values,
+        }
+    }
+
+    /// Samples `noise` over a flat slice of space
+    pub fn sample_plane<N: NoiseSource>(
+        noise: &N,
+        end_octave: i32,
+        width: usize,
+        height: usize,
+        slice: &PlaneSlice,
+    ) -> Self {
+        Grid::from_fn(width, height, |x, y| {
+            noise.get(
+                &slice.at(pixel_fraction(x, width), pixel_fraction(y, height)),
+                end_octave,
+            )
+        })
+    }
+
+    /// Samples `noise` over a sphere of `radius` in an equirectangular projection, the way the planet would see it
+    ///
+    /// Columns run from longitude -180° to 180° and rows from latitude 90° at the top to -90° at the bottom. Sapiens'
+    /// planet has a radius of 1, but noise is usually sampled at a larger scale to get more detail
+    pub fn sample_lat_long<N: NoiseSource>(
+        noise: &N,
+        end_octave: i32,
+        width: usize,
+        height: usize,
+        radius: f64,
+    ) -> Self {
+        Grid::from_fn(width, height, |x, y| {
+            let lat_long = lat_long_at(x, y, width, height);
+            let unit: SPVec3 = lat_long.to_unit_vector();
+            let pos = SPVec3 {
+                x: unit.x * radius,
+                y: unit.y * radius,
+                z: unit.z * radius,
+            };
+
+            noise.get(&pos, end_octave)
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The value at column `x` and row `y`
+    pub fn get(&self, x: usize, y: usize) -> f64 {
+        assert!(
+            x < self.width && y < self.height,
+            "({}, {}) is outside the grid",
+            x,
+            y
+        );
+        self.values[y * self.width + x]
+    }
+
+    /// All the values, row by row from the top left
+    pub fn values(&self) -> &[f64] {
+        &self.values
+    }
+
+    /// Summarizes the values, with a histogram of `bins` equal-width bins between the minimum and maximum
+    pub fn stats(&self, bins: usize) -> Stats {
+        Stats::of(&self.values, bins)
+    }
+
+    /// Turns the values into pixels, mapping `range` onto the colormap's ends
+    pub fn to_image(&self, range: ValueRange, colormap: Colormap) -> Image {
+        let (min, max) = match range {
+            ValueRange::Fixed(min, max) => (min, max),
+            ValueRange::Auto => {
+                let stats = self.stats(0);
+                (stats.min, stats.max)
+            }
+        };
+
+        let channels = colormap.channels();
+        let mut pixels = Vec::with_capacity(self.values.len() * channels);
+        for value in &self.values {
+            let t = if max > min {
+                ((value - min) / (max - min)).clamp(0.0, 1.0)
+            } else {
+                0.5
+            };
+            colormap.push(t, &mut pixels);
+        }
+
+        Image {
+            width: self.width,
+            height: self.height,
+            channels,
+            pixels,
+        }
+    }
+}
+
+/// Where in `[0, 1]` pixel `i` of `count` lies, so that the first and last pixels land on the edges
+fn pixel_fraction(i: usize, count: usize) -> f64 {
+    if count > 1 {
+        i as f64 / (count - 1) as f64
+    } else {
+        0.5
+    }
+}
+
+/// The latitude and longitude that an equirectangular pixel covers
+///
+/// The top and bottom rows lie on the poles. Each column is centered on its longitude, so the left and right edges of
+/// the image meet at the -Z meridian without the same longitude showing up twice
+pub fn lat_long_at(x: usize, y: usize, width: usize, height: usize) -> LatLong {
+    LatLong::new(
+        FRAC_PI_2 - pixel_fraction(y, height) * PI,
+        (x as f64 + 0.5) / width as f64 * 2.0 * PI - PI,
+    )
+}
+
+fn bin(value: f64, min: f64, max: f64, bins: usize) -> usize {
+    if max > min {
+        (((value - min) / (max - min) * bins as f64) as usize).min(bins - 1)
+    } else {
+        0
+    }
+}
+
+/// A summary of a set of values, such as a grid's
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stats {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+
+    /// How many values fall in each equal-width bin between `min` and `max`
+    pub histogram: Vec<usize>,
+}
+
+impl Stats {
+    /// Summarizes any values, with a histogram of `bins` equal-width bins between the minimum and maximum
+    pub fn of(values: &[f64], bins: usize) -> Self {
+        let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+
+        let mut histogram = vec![0; bins];
+        if bins > 0 {
+            for value in values {
+                histogram[bin(*value, min, max, bins)] += 1;
+            }
+        }
+
+        Stats {
+            min,
+            max,
+            mean,
+            histogram,
+        }
+    }
+}
+
+/// Which values map to the ends of the colormap
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ValueRange {
+    /// Stretch from the grid's minimum to its maximum, to see as much contrast as possible
+    Auto,
+
+    /// Use fixed bounds, e.g. `Fixed(-1.0, 1.0)`, to compare images of different grids. Values outside are clamped
+    Fixed(f64, f64),
+}
+
+/// How values become colors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Colormap {
+    /// Black to white, as a single channel
+    Grayscale,
+
+    /// Deep water, shallows, sand, grass, rock and snow, with the coast at the middle of the range
+    Terrain,
+
+    /// Black through red and yellow to white
+    Heat,
+}
+
+impl Colormap {
+    fn channels(self) -> usize {
+        match self {
+            Colormap::Grayscale => 1,
+            Colormap::Terrain | Colormap::Heat => 3,
+        }
+    }
+
+    fn push(self, t: f64, pixels: &mut Vec<u8>) {
+        match self {
+            Colormap::Grayscale => pixels.push(to_byte(t)),
+            Colormap::Terrain => pixels.extend_from_slice(&gradient(
+                t,
+                &[
+                    (0.0, [8, 24, 88]),
+                    (0.45, [40, 96, 176]),
+                    (0.5, [216, 200, 144]),
+                    (0.55, [72, 140, 56]),
+                    (0.8, [120, 104, 88]),
+                    (1.0, [248, 248, 248]),
+                ],
+            )),
+            Colormap::Heat => pixels.extend_from_slice(&gradient(
+                t,
+                &[
+                    (0.0, [0, 0, 0]),
+                    (0.4, [200, 32, 0]),
+                    (0.8, [255, 216, 0]),
+                    (1.0, [255, 255, 255]),
+                ],
+            )),
+        }
+    }
+}
+
+fn to_byte(t: f64) -> u8 {
+    (t * 255.0).round() as u8
+}
+
+/// Interpolates between color stops, which must be sorted and cover `[0, 1]`
+fn gradient(t: f64, stops: &[(f64, [u8; 3])]) -> [u8; 3] {
+    for pair in stops.windows(2) {
+        let (start, from) = pair[0];
+        let (end, to) = pair[1];
+
+        if t <= end {
+            let s = (t - start) / (end - start);
+            let lerp = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * s).round() as u8;
+            return [
+                lerp(from[0], to[0]),
+                lerp(from[1], to[1]),
+                lerp(from[2], to[2]),
+            ];
+        }
+    }
+
+    stops[stops.len() - 1].1
+}
+
+/// 8 bit pixels, either grayscale or RGB
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+
+    /// 1 for grayscale, 3 for RGB
+    pub channels: usize,
+
+    /// Row by row from the top left, `channels` bytes per pixel
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    /// Writes a binary PGM for grayscale images, or a PPM for color ones
+    pub fn write_pnm<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let magic = if self.channels == 1 { "P5" } else { "P6" };
+        write!(writer, "{}\n{} {}\n255\n", magic, self.width, self.height)?;
+        writer.write_all(&self.pixels)
+    }
+
+    /// Writes a PNG
+    #[cfg(feature = "png")]
+    pub fn write_png<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut encoder = png::Encoder::new(writer, self.width as u32, self.height as u32);
+        encoder.set_color(if self.channels == 1 {
+            png::ColorType::Grayscale
+        } else {
+            png::ColorType::Rgb
+        });
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut writer = encoder.write_header().map_err(png_error)?;
+        writer.write_image_data(&self.pixels).map_err(png_error)
+    }
+}
+
+#[cfg(feature = "png")]
+fn png_error(error: png::EncodingError) -> io::Error {
+    match error {
+        png::EncodingError::IoError(error) => error,
+        error => io::Error::other(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_vec_near;
+    use crate::sp::mock::ConstantNoise;
+    use crate::sp::vector::Vector3Like;
+
+    /// Noise which just reports one coordinate, so that sample positions can be checked
+    struct Coordinate(fn(&SPVec3) -> f64);
+
+    impl NoiseSource for Coordinate {
+        fn get(&self, pos: &SPVec3, _end_octave: i32) -> f64 {
+            (self.0)(pos)
+        }
+    }
+
+    #[test]
+    fn plane_slice_covers_its_corners() {
+        let slice = PlaneSlice::xy(axis(1.0, 2.0, 3.0), 4.0);
+
+        assert_vec_near!(slice.at(0.0, 0.0), axis(-1.0, 4.0, 3.0));
+        assert_vec_near!(slice.at(1.0, 1.0), axis(3.0, 0.0, 3.0));
+
+        let grid = Grid::sample_plane(&Coordinate(|pos| pos.x), 1, 5, 3, &slice);
+        assert_vec_near!(grid.get(0, 0), -1.0);
+        assert_vec_near!(grid.get(2, 1), 1.0);
+        assert_vec_near!(grid.get(4, 2), 3.0);
+    }
+
+    #[test]
+    fn lat_long_grid_runs_north_to_south() {
+        let grid = Grid::sample_lat_long(&Coordinate(|pos| pos.y), 1, 9, 5, 2.0);
+
+        assert_vec_near!(grid.get(4, 0), 2.0);
+        assert_vec_near!(grid.get(4, 2), 0.0);
+        assert_vec_near!(grid.get(4, 4), -2.0);
+    }
+
+    #[test]
+    fn lat_long_grid_wraps_at_the_antimeridian() {
+        let west = lat_long_at(0, 3, 9, 7).to_unit_vector::<SPVec3>();
+        let east = lat_long_at(8, 3, 9, 7).to_unit_vector::<SPVec3>();
+
+        // Half a column either side of the -Z meridian
+        assert_vec_near!(west.plus(&east).normalized(), axis(0.0, 0.0, -1.0));
+        assert_vec_near!(
+            lat_long_at(8, 3, 9, 7).longitude - lat_long_at(0, 3, 9, 7).longitude,
+            2.0 * PI * 8.0 / 9.0
+        );
+    }
+
+    #[test]
+    fn stats_and_histogram() {
+        let grid = Grid::from_fn(4, 1, |x, _| x as f64);
+
+        let stats = grid.stats(2);
+
+        assert_eq!(stats.min, 0.0);
+        assert_eq!(stats.max, 3.0);
+        assert_eq!(stats.mean, 1.5);
+        assert_eq!(stats.histogram, vec![2, 2]);
+    }
+
+    #[test]
+    fn flat_grids_do_not_divide_by_zero() {
+        let grid = Grid::sample_plane(
+            &ConstantNoise(0.3),
+            1,
+            2,
+            2,
+            &PlaneSlice::xz(axis(0.0, 0.0, 0.0), 1.0),
+        );
+
+        assert_eq!(grid.stats(3).histogram, vec![4, 0, 0]);
+        assert_eq!(
+            grid.to_image(ValueRange::Auto, Colormap::Grayscale).pixels,
+            vec![128; 4]
+        );
+    }
+
+    #[test]
+    fn images_map_range_onto_colormap() {
+        let grid = Grid::from_fn(3, 1, |x, _| x as f64 - 1.0);
+
+        let gray = grid.to_image(ValueRange::Fixed(-1.0, 1.0), Colormap::Grayscale);
+        assert_eq!(gray.pixels, vec![0, 128, 255]);
+
+        let heat = grid.to_image(ValueRange::Auto, Colormap::Heat);
+        assert_eq!(heat.channels, 3);
+        assert_eq!(&heat.pixels[0..3], &[0, 0, 0]);
+        assert_eq!(&heat.pixels[6..9], &[255, 255, 255]);
+
+        let clamped = grid.to_image(ValueRange::Fixed(0.0, 0.5), Colormap::Grayscale);
+        assert_eq!(clamped.pixels, vec![0, 0, 255]);
+    }
+
+    #[test]
+    fn writes_pnm_headers() {
+        let image =
+            Grid::from_fn(2, 1, |x, _| x as f64).to_image(ValueRange::Auto, Colormap::Grayscale);
+        let mut pgm = Vec::new();
+        image.write_pnm(&mut pgm).unwrap();
+        assert_eq!(pgm, b"P5\n2 1\n255\n\x00\xff");
+
+        let image = Grid::from_fn(1, 1, |_, _| 0.0).to_image(ValueRange::Auto, Colormap::Terrain);
+        let mut ppm = Vec::new();
+        image.write_pnm(&mut ppm).unwrap();
+        assert!(ppm.starts_with(b"P6\n1 1\n255\n"));
+        assert_eq!(ppm.len(), 11 + 3);
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn png_round_trips() {
+        let image = Grid::from_fn(3, 2, |x, y| (x + y) as f64)
+            .to_image(ValueRange::Auto, Colormap::Terrain);
+        let mut encoded = Vec::new();
+        image.write_png(&mut encoded).unwrap();
+
+        let mut reader = png::Decoder::new(&encoded[..]).read_info().unwrap();
+        let mut decoded = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut decoded).unwrap();
+
+        assert_eq!((info.width, info.height), (3, 2));
+        assert_eq!(info.color_type, png::ColorType::Rgb);
+        assert_eq!(&decoded[..info.buffer_size()], &image.pixels[..]);
+    }
+}
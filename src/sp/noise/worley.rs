@@ -0,0 +1,175 @@
+//! Worley (cellular) noise
+//!
+//! Space is split into cells around randomly placed feature points, which gives pebbles, cracked mud, rock fields and
+//! other patterns that gradient noise can't
+
+use crate::sp::noise::fractal;
+use crate::sp::noise::NoiseSource;
+use sapiens_sys::SPVec3;
+
+/// What a `Worley` lookup measures
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(sapiens_sys::serde::Serialize, sapiens_sys::serde::Deserialize),
+    serde(crate = "sapiens_sys::serde", rename_all = "snake_case")
+)]
+pub enum WorleyFeature {
+    /// Distance to the nearest feature point. Low in the middle of each cell
+    Nearest,
+
+    /// Difference between the distances to the two nearest feature points. Low along the borders between cells
+    Edge,
+
+    /// A random value shared by every position in the same cell, for flat patches
+    CellValue,
+}
+
+/// Cellular noise with one feature point per unit cell, in `[-1, 1]`
+///
+/// The same seed always gives the same noise, on every platform
+#[derive(Debug, Clone)]
+pub struct Worley {
+    seed: u64,
+    persistance: f64,
+    feature: WorleyFeature,
+}
+
+impl Worley {
+    /// Creates a new generator which measures the distance to the nearest feature point
+    ///
+    /// # Parameters
+    ///
+    /// * `seed` - Noise seed
+    /// * `persistance` - How much each octave's amplitude is scaled relative to the one before, usually `0.5`
+    pub fn new(seed: i32, persistance: f64) -> Self {
+        Worley {
+            seed: fractal::mix(seed as u32 as u64),
+            persistance,
+            feature: WorleyFeature::Nearest,
+        }
+    }
+
+    /// Measures `feature` instead
+    pub fn with_feature(mut self, feature: WorleyFeature) -> Self {
+        self.feature = feature;
+        self
+    }
+
+    pub fn feature(&self) -> WorleyFeature {
+        self.feature
+    }
+
+    /// A single octave
+    fn octave(&self, pos: &SPVec3, octave: u32) -> f64 {
+        let seed = fractal::mix(self.seed ^ octave as u64);
+        let (cx, cy, cz) = (
+            pos.x.floor() as i64,
+            pos.y.floor() as i64,
+            pos.z.floor() as i64,
+        );
+
+        let mut nearest = f64::INFINITY;
+        let mut second = f64::INFINITY;
+        let mut nearest_hash = 0;
+
+        for dz in -1..=1 {
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    let (x, y, z) = (cx + dx, cy + dy, cz + dz);
+                    let hash = fractal::hash3(seed, x, y, z);
+                    let point = [
+                        x as f64 + unit(hash),
+                        y as f64 + unit(hash >> 21),
+                        z as f64 + unit(hash >> 42),
+                    ];
+
+                    let distance = ((point[0] - pos.x).powi(2)
+                        + (point[1] - pos.y).powi(2)
+                        + (point[2] - pos.z).powi(2))
+                    .sqrt();
+
+                    if distance < nearest {
+                        second = nearest;
+                        nearest = distance;
+                        nearest_hash = hash;
+                    } else if distance < second {
+                        second = distance;
+                    }
+                }
+            }
+        }
+
+        let value = match self.feature {
+            WorleyFeature::Nearest => nearest,
+            WorleyFeature::Edge => second - nearest,
+            WorleyFeature::CellValue => unit(fractal::mix(nearest_hash)),
+        };
+
+        (value * 2.0 - 1.0).min(1.0)
+    }
+}
+
+impl NoiseSource for Worley {
+    /// Sums octaves `0..end_octave`, each at double the frequency of the last, normalized back into `[-1, 1]`
+    fn get(&self, pos: &SPVec3, end_octave: i32) -> f64 {
+        fractal::octaves(pos, end_octave, self.persistance, |pos, octave| {
+            self.octave(pos, octave)
+        })
+    }
+}
+
+/// The low 21 bits of `bits` as a value in `[0, 1)`
+fn unit(bits: u64) -> f64 {
+    (bits & 0x1F_FFFF) as f64 / (1u64 << 21) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sp::noise::tests::{assert_generator_behaves, sample_positions};
+    use crate::sp::vector::Vector3Like;
+
+    #[test]
+    fn behaves_like_noise() {
+        assert_generator_behaves(Worley::new);
+        assert_generator_behaves(|seed, persistance| {
+            Worley::new(seed, persistance).with_feature(WorleyFeature::Edge)
+        });
+        assert_generator_behaves(|seed, persistance| {
+            Worley::new(seed, persistance).with_feature(WorleyFeature::CellValue)
+        });
+    }
+
+    #[test]
+    fn cell_values_are_piecewise_constant() {
+        let worley = Worley::new(9, 0.5).with_feature(WorleyFeature::CellValue);
+        let nudge = SPVec3 {
+            x: 1.0e-6,
+            y: 0.0,
+            z: 0.0,
+        };
+
+        let positions = sample_positions();
+        let unchanged = positions
+            .iter()
+            .filter(|pos| worley.get(pos, 1) == worley.get(&pos.plus(&nudge), 1))
+            .count();
+
+        // Only samples right on a border between cells can change
+        assert!(unchanged >= positions.len() - 2);
+    }
+
+    #[test]
+    fn nearest_is_lowest_at_feature_points() {
+        let worley = Worley::new(9, 0.5);
+
+        let lowest = sample_positions()
+            .iter()
+            .map(|pos| worley.get(pos, 1))
+            .fold(f64::INFINITY, f64::min);
+
+        // Every cell has a feature point, so nowhere is far from one
+        assert!(lowest < 0.0);
+    }
+}
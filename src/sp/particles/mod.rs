@@ -0,0 +1,741 @@
+//! Rusty structs for particle things
+
+#![allow(non_upper_case_globals)]
+
+use crate::sp::mock::ParticleRecorder;
+use crate::sp::noise::{NoiseRef, NoiseSource};
+use crate::sp::rand::{RandRef, RandomSource};
+use num_derive::{FromPrimitive, ToPrimitive};
+use num_traits::{FromPrimitive, ToPrimitive};
+use sapiens_sys::*;
+use std::cell::Cell;
+use std::convert::{TryFrom, TryInto};
+use std::error::Error;
+use std::ffi::{CStr, CString};
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem;
+use std::os::raw;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, PoisonError, RwLock};
+
+mod orientation;
+pub mod shapes;
+
+pub use orientation::Orientation;
+
+// rustc thinks that this import isn't used, but it actually is
+#[allow(unused_imports)]
+use std::ptr::null_mut;
+
+/// All the types of vertex attributes that Sapiens supports
+#[repr(i32)]
+#[derive(Debug, PartialEq, FromPrimitive, ToPrimitive)]
+#[cfg_attr(
+    feature = "serde",
+    derive(sapiens_sys::serde::Serialize, sapiens_sys::serde::Deserialize),
+    serde(crate = "sapiens_sys::serde", rename_all = "lowercase")
+)]
+pub enum VertexAttributeType {
+    Float = SPRenderGroupVertexDescriptionType_SPRenderGroupVertexDescriptionType_float,
+    Vec2 = SPRenderGroupVertexDescriptionType_SPRenderGroupVertexDescriptionType_vec2,
+    Vec3 = SPRenderGroupVertexDescriptionType_SPRenderGroupVertexDescriptionType_vec3,
+    Vec4 = SPRenderGroupVertexDescriptionType_SPRenderGroupVertexDescriptionType_vec4,
+}
+
+/// Idiomatic struct for a information about a render group
+#[derive(Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(sapiens_sys::serde::Serialize, sapiens_sys::serde::Deserialize),
+    serde(crate = "sapiens_sys::serde")
+)]
+pub struct RenderGroupInfo<RenderGroupIdType>
+where
+    RenderGroupIdType: FromPrimitive + ToPrimitive,
+{
+    /// Name of the shader to use to render this render group
+    pub shader_name: String,
+
+    /// Identifier for the render group
+    pub id: RenderGroupIdType,
+
+    /// All the vertex attributes that make up the vertex data that this render group renders
+    pub vertex_descriptions: Vec<VertexAttributeType>,
+}
+
+impl<RenderGroupIdType> TryFrom<SPParticleRenderGroupInfo> for RenderGroupInfo<RenderGroupIdType>
+where
+    RenderGroupIdType: FromPrimitive + ToPrimitive,
+{
+    type Error = ();
+
+    fn try_from(sp_info: SPParticleRenderGroupInfo) -> Result<Self, Self::Error> {
+        Ok(RenderGroupInfo {
+            shader_name: unsafe { CStr::from_ptr(sp_info.shaderName) }
+                .to_str()
+                .unwrap()
+                .to_owned(),
+            id: match FromPrimitive::from_u32(sp_info.localID) {
+                Some(val) => val,
+                None => panic!("it broke"),
+            },
+            vertex_descriptions: unsafe {
+                Vec::from_raw_parts(
+                    sp_info.vertexDescriptionTypes as _,
+                    sp_info.vertexDescriptionTypeCount as _,
+                    sp_info.vertexDescriptionTypeCount as _,
+                )
+            },
+        })
+    }
+}
+
+impl<RenderGroupTypeId> TryInto<SPParticleRenderGroupInfo> for RenderGroupInfo<RenderGroupTypeId>
+where
+    RenderGroupTypeId: FromPrimitive + ToPrimitive,
+{
+    type Error = ();
+
+    fn try_into(mut self) -> Result<SPParticleRenderGroupInfo, Self::Error> {
+        self.vertex_descriptions.shrink_to_fit();
+        let ptr = self.vertex_descriptions.as_mut_ptr() as *mut raw::c_int;
+        let len = self.vertex_descriptions.len() as raw::c_int;
+        mem::forget(self.vertex_descriptions);
+
+        Ok(SPParticleRenderGroupInfo {
+            shaderName: CString::new(self.shader_name).unwrap().into_raw() as _,
+            localID: match ToPrimitive::to_u32(&self.id) {
+                Some(val) => val,
+                None => panic!("it broke"),
+            },
+            vertexDescriptionTypeCount: len,
+            vertexDescriptionTypes: ptr,
+        })
+    }
+}
+
+impl<RenderGroupTypeId> PartialEq<SPParticleRenderGroupInfo> for RenderGroupInfo<RenderGroupTypeId>
+where
+    RenderGroupTypeId: FromPrimitive + ToPrimitive + PartialEq,
+{
+    fn eq(&self, sp_info: &SPParticleRenderGroupInfo) -> bool {
+        let ffi_vertex_desc = unsafe {
+            Vec::from_raw_parts(
+                sp_info.vertexDescriptionTypes as *mut VertexAttributeType,
+                sp_info.vertexDescriptionTypeCount as _,
+                sp_info.vertexDescriptionTypeCount as _,
+            )
+        };
+
+        let ffi_shader_name = unsafe { CStr::from_ptr(sp_info.shaderName) }
+            .to_str()
+            .unwrap()
+            .to_owned();
+
+        self.shader_name == ffi_shader_name
+            && self.id
+                == (match FromPrimitive::from_u32(sp_info.localID) {
+                    Some(val) => val,
+                    None => panic!("it broke"),
+                })
+            && self.vertex_descriptions == ffi_vertex_desc
+    }
+}
+
+#[derive(Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(sapiens_sys::serde::Serialize, sapiens_sys::serde::Deserialize),
+    serde(crate = "sapiens_sys::serde")
+)]
+pub struct EmitterTypeInfo<EmitterTypeId> {
+    pub name: String,
+    pub id: EmitterTypeId,
+}
+
+impl<EmitterTypeId> TryFrom<SPParticleEmitterTypeInfo> for EmitterTypeInfo<EmitterTypeId>
+where
+    EmitterTypeId: FromPrimitive + ToPrimitive,
+{
+    type Error = ();
+
+    fn try_from(sp_emitter_type: SPParticleEmitterTypeInfo) -> Result<Self, Self::Error> {
+        Ok(EmitterTypeInfo {
+            name: unsafe { CStr::from_ptr(sp_emitter_type.name) }
+                .to_str()
+                .unwrap()
+                .to_owned(),
+            id: FromPrimitive::from_u32(sp_emitter_type.localID).unwrap(),
+        })
+    }
+}
+
+impl<EmitterTypeId> TryInto<SPParticleEmitterTypeInfo> for EmitterTypeInfo<EmitterTypeId>
+where
+    EmitterTypeId: FromPrimitive + ToPrimitive,
+{
+    type Error = ();
+
+    fn try_into(self) -> Result<SPParticleEmitterTypeInfo, Self::Error> {
+        Ok(SPParticleEmitterTypeInfo {
+            name: CString::new(self.name).unwrap().into_raw() as _,
+            localID: ToPrimitive::to_u32(&self.id).unwrap(),
+        })
+    }
+}
+
+impl<EmitterTypeId> PartialEq<SPParticleEmitterTypeInfo> for EmitterTypeInfo<EmitterTypeId>
+where
+    EmitterTypeId: FromPrimitive + PartialEq,
+{
+    fn eq(&self, other: &SPParticleEmitterTypeInfo) -> bool {
+        let ffi_emitter_type_name = unsafe { CStr::from_ptr(other.name) }
+            .to_str()
+            .unwrap()
+            .to_owned();
+
+        let ffi_emitter_type = EmitterTypeId::from_u32(other.localID).unwrap();
+
+        self.name == ffi_emitter_type_name && self.id == ffi_emitter_type
+    }
+}
+
+/// The per-thread state that Sapiens passes to particle hooks
+///
+/// This borrows the game's `SPParticleThreadState` for the duration of one hook call. Sapiens runs emitter hooks on its
+/// worker threads, each with its own thread state, so a `ThreadState` may be moved to another thread but never shared
+/// between threads.
+///
+/// The generators are generic so that emitter logic can be tested without the game: write hooks against
+/// `ThreadState<'_, R, N>` with `R: RandomSource` and `N: NoiseSource`, and build one with `ThreadState::mock`. The
+/// fields are public so that the generators can be borrowed while adding particles
+pub struct ThreadState<'a, R = RandRef<'a>, N = NoiseRef<'a>> {
+    /// Thread-local random number generator
+    pub rand: R,
+
+    /// Thread-local noise generator
+    pub noise: N,
+
+    /// Where new particles go
+    pub particles: ParticleSink<'a>,
+
+    /// Sapiens' generators aren't thread safe, so two threads mustn't use the same thread state at once
+    _not_sync: PhantomData<Cell<()>>,
+}
+
+// Sapiens hands each worker thread its own thread state, and the generator handles can't be cloned, so moving the
+// thread state moves the only way to reach them
+unsafe impl Send for ThreadState<'_> {}
+
+impl<'a> ThreadState<'a> {
+    /// Borrows a thread state that Sapiens passed to a hook
+    ///
+    /// # Safety
+    ///
+    /// `thread_state` must have come from Sapiens, so that its particle manager, random number generator and noise
+    /// generator are all valid for `'a`
+    pub unsafe fn from_sp(thread_state: &'a mut SPParticleThreadState) -> Self {
+        ThreadState {
+            rand: RandRef::from_ptr(thread_state.spRand),
+            noise: NoiseRef::from_ptr(thread_state.spNoise),
+            particles: ParticleSink {
+                particle_manager: thread_state.particleManager,
+                add_particle: thread_state.addParticle,
+                registered_render_groups: registered_render_groups(),
+                _thread_state: PhantomData,
+            },
+            _not_sync: PhantomData,
+        }
+    }
+}
+
+impl<'a, R: RandomSource, N: NoiseSource> ThreadState<'a, R, N> {
+    /// Builds a thread state for tests, which records added particles in `recorder` instead of sending them to Sapiens
+    pub fn mock(recorder: &'a mut ParticleRecorder, rand: R, noise: N) -> Self {
+        ThreadState {
+            rand,
+            noise,
+            particles: ParticleSink {
+                particle_manager: recorder as *mut ParticleRecorder as *mut raw::c_void,
+                add_particle: Some(ParticleRecorder::record),
+                registered_render_groups: registered_render_groups(),
+                _thread_state: PhantomData,
+            },
+            _not_sync: PhantomData,
+        }
+    }
+
+    /// Tells Sapiens to spawn a new particle for your emitter
+    ///
+    /// Shorthand for `self.particles.add_particle`
+    pub fn add_particle<RenderTypeId: ToPrimitive>(
+        &mut self,
+        emitter_state: &mut EmitterState,
+        render_type_id: RenderTypeId,
+        particle_state: &mut ParticleState,
+    ) -> Result<(), AddParticleError> {
+        self.particles
+            .add_particle(emitter_state, render_type_id, particle_state)
+    }
+
+    /// Tells Sapiens to spawn a batch of particles which all use the same render group
+    ///
+    /// Shorthand for `self.particles.add_particles`
+    pub fn add_particles<RenderTypeId, Particles>(
+        &mut self,
+        emitter_state: &mut EmitterState,
+        render_type_id: RenderTypeId,
+        particles: Particles,
+    ) -> Result<usize, AddParticleError>
+    where
+        RenderTypeId: ToPrimitive,
+        Particles: IntoIterator<Item = ParticleState>,
+    {
+        self.particles
+            .add_particles(emitter_state, render_type_id, particles)
+    }
+}
+
+/// The part of a thread state that adds particles
+pub struct ParticleSink<'a> {
+    particle_manager: *mut raw::c_void,
+    add_particle: Option<AddParticleFn>,
+
+    /// The mod's registered render groups when the sink was set up, or `None` if it didn't register any
+    registered_render_groups: Option<Arc<[u32]>>,
+
+    _thread_state: PhantomData<&'a mut SPParticleThreadState>,
+}
+
+impl ParticleSink<'_> {
+    /// Tells Sapiens to spawn a new particle for your emitter
+    pub fn add_particle<RenderTypeId: ToPrimitive>(
+        &mut self,
+        emitter_state: &mut EmitterState,
+        render_type_id: RenderTypeId,
+        particle_state: &mut ParticleState,
+    ) -> Result<(), AddParticleError> {
+        let add_particle = self.checked_add_particle(&render_type_id)?;
+
+        unsafe {
+            (add_particle.func)(
+                self.particle_manager,
+                emitter_state,
+                add_particle.render_group,
+                particle_state,
+            )
+        };
+
+        Ok(())
+    }
+
+    /// Tells Sapiens to spawn a batch of particles which all use the same render group
+    ///
+    /// The render group is only checked once, which makes this the better choice for burst emitters. Returns how many
+    /// particles were added
+    pub fn add_particles<RenderTypeId, Particles>(
+        &mut self,
+        emitter_state: &mut EmitterState,
+        render_type_id: RenderTypeId,
+        particles: Particles,
+    ) -> Result<usize, AddParticleError>
+    where
+        RenderTypeId: ToPrimitive,
+        Particles: IntoIterator<Item = ParticleState>,
+    {
+        let add_particle = self.checked_add_particle(&render_type_id)?;
+
+        let mut count = 0;
+        for mut particle_state in particles {
+            unsafe {
+                (add_particle.func)(
+                    self.particle_manager,
+                    emitter_state,
+                    add_particle.render_group,
+                    &mut particle_state,
+                )
+            };
+            count += 1;
+        }
+
+        Ok(count)
+    }
+
+    fn checked_add_particle<RenderTypeId: ToPrimitive>(
+        &self,
+        render_type_id: &RenderTypeId,
+    ) -> Result<CheckedAddParticle, AddParticleError> {
+        let func = self.add_particle.ok_or(AddParticleError::MissingCallback)?;
+        let render_group = render_type_id
+            .to_u32()
+            .ok_or(AddParticleError::UnconvertibleRenderGroupId)?;
+
+        if let Some(registered) = &self.registered_render_groups {
+            if !registered.contains(&render_group) {
+                return Err(AddParticleError::RenderGroupNotRegistered(render_group));
+            }
+        }
+
+        Ok(CheckedAddParticle { func, render_group })
+    }
+}
+
+type AddParticleFn = unsafe extern "C" fn(
+    particle_manager: *mut raw::c_void,
+    emitter_state: *mut SPParticleEmitterState,
+    local_render_group_type_id: u32,
+    particle_state: *mut SPParticleState,
+);
+
+/// An `addParticle` callback that's known to exist, along with a render group that's known to be registered
+struct CheckedAddParticle {
+    func: AddParticleFn,
+    render_group: u32,
+}
+
+/// Reasons that `ThreadState::add_particle` couldn't spawn a particle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddParticleError {
+    /// Sapiens didn't provide an `addParticle` callback in the thread state
+    MissingCallback,
+
+    /// The render group ID couldn't be converted to the `u32` that Sapiens expects
+    UnconvertibleRenderGroupId,
+
+    /// This mod registered its render groups, but `get_render_group_types` didn't return one with this ID
+    RenderGroupNotRegistered(u32),
+}
+
+impl fmt::Display for AddParticleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AddParticleError::MissingCallback => {
+                write!(f, "Sapiens didn't provide an addParticle callback")
+            }
+            AddParticleError::UnconvertibleRenderGroupId => {
+                write!(f, "render group ID can't be converted to a u32")
+            }
+            AddParticleError::RenderGroupNotRegistered(id) => {
+                write!(f, "render group {} wasn't registered by this mod", id)
+            }
+        }
+    }
+}
+
+impl Error for AddParticleError {}
+
+/// IDs of the render groups this mod told Sapiens about, so `add_particle` can catch typos and stale IDs
+static REGISTERED_RENDER_GROUPS: RwLock<Option<Arc<[u32]>>> = RwLock::new(None);
+
+/// Whether `REGISTERED_RENDER_GROUPS` has been filled in, so mods which never register skip the lock entirely
+static RENDER_GROUPS_REGISTERED: AtomicBool = AtomicBool::new(false);
+
+/// Records the render groups that this mod registers with Sapiens
+///
+/// `#[export_to_sapiens]` calls this from the generated `spGetRenderGroupTypes`, so mods don't usually need to. Until
+/// something is registered, `add_particle` accepts any render group
+pub fn register_render_groups<RenderGroupIdType>(
+    render_groups: &[RenderGroupInfo<RenderGroupIdType>],
+) where
+    RenderGroupIdType: FromPrimitive + ToPrimitive,
+{
+    let mut registered = REGISTERED_RENDER_GROUPS
+        .write()
+        .unwrap_or_else(PoisonError::into_inner);
+
+    let mut ids = registered.as_deref().unwrap_or(&[]).to_vec();
+    for id in render_groups.iter().filter_map(|info| info.id.to_u32()) {
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+
+    *registered = Some(ids.into());
+    RENDER_GROUPS_REGISTERED.store(true, Ordering::Release);
+}
+
+/// The registered render groups, looked up once per thread state rather than once per particle
+fn registered_render_groups() -> Option<Arc<[u32]>> {
+    if !RENDER_GROUPS_REGISTERED.load(Ordering::Acquire) {
+        return None;
+    }
+
+    REGISTERED_RENDER_GROUPS
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
+
+pub type EmitterState = SPParticleEmitterState;
+
+pub type ParticleState = SPParticleState;
+
+/*
+ * Tests
+ */
+
+#[cfg(test)]
+mod conversion_tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Debug, PartialEq, FromPrimitive, ToPrimitive)]
+    enum RenderGroupId {
+        // TODO: Some way to put the render group type data in here
+        Fire,
+        Smoke,
+    }
+
+    #[derive(Debug, PartialEq, FromPrimitive, ToPrimitive)]
+    enum EmitterTypeId {
+        Campfire,
+    }
+
+    #[test]
+    fn test_render_group_info_from_sp() {
+        let shader_name = CString::new("fire").unwrap();
+
+        let mut vertex_description_types: [VertexAttributeType; 3] = [
+            VertexAttributeType::Vec3,
+            VertexAttributeType::Vec2,
+            VertexAttributeType::Vec4,
+        ];
+
+        let sp_render_group = SPParticleRenderGroupInfo {
+            shaderName: shader_name.into_raw(),
+            localID: RenderGroupId::Fire as _,
+            vertexDescriptionTypeCount: vertex_description_types.len() as _,
+            vertexDescriptionTypes: vertex_description_types.as_mut_ptr() as _,
+        };
+
+        let render_group: RenderGroupInfo<RenderGroupId> =
+            TryFrom::try_from(sp_render_group).unwrap();
+
+        assert_eq!(render_group.shader_name, "fire");
+        assert_eq!(render_group.id, RenderGroupId::Fire);
+        assert_eq!(
+            render_group.vertex_descriptions.len(),
+            vertex_description_types.len()
+        );
+        assert_eq!(
+            render_group.vertex_descriptions[0],
+            vertex_description_types[0]
+        );
+        assert_eq!(
+            render_group.vertex_descriptions[1],
+            vertex_description_types[1]
+        );
+        assert_eq!(
+            render_group.vertex_descriptions[2],
+            vertex_description_types[2]
+        );
+    }
+
+    #[test]
+    fn test_sp_particle_render_group_info_from_rs() {
+        let render_group = RenderGroupInfo {
+            shader_name: "fire".to_string(),
+            id: RenderGroupId::Fire,
+            vertex_descriptions: vec![
+                VertexAttributeType::Vec3,
+                VertexAttributeType::Vec2,
+                VertexAttributeType::Vec4,
+            ],
+        };
+
+        let sp_render_group: SPParticleRenderGroupInfo = render_group.try_into().unwrap();
+
+        assert_eq!(
+            unsafe { CStr::from_ptr(sp_render_group.shaderName) }
+                .to_str()
+                .unwrap(),
+            "fire"
+        );
+        assert_eq!(sp_render_group.localID, 0);
+
+        let sp_vertex_description_types = unsafe {
+            Vec::from_raw_parts(
+                sp_render_group.vertexDescriptionTypes,
+                sp_render_group.vertexDescriptionTypeCount as _,
+                sp_render_group.vertexDescriptionTypeCount as _,
+            )
+        };
+
+        assert_eq!(sp_render_group.vertexDescriptionTypeCount, 3);
+        assert_eq!(
+            sp_vertex_description_types[0],
+            SPRenderGroupVertexDescriptionType_SPRenderGroupVertexDescriptionType_vec3
+        );
+        assert_eq!(
+            sp_vertex_description_types[1],
+            SPRenderGroupVertexDescriptionType_SPRenderGroupVertexDescriptionType_vec2
+        );
+        assert_eq!(
+            sp_vertex_description_types[2],
+            SPRenderGroupVertexDescriptionType_SPRenderGroupVertexDescriptionType_vec4
+        );
+    }
+
+    #[test]
+    fn test_emitter_type_info_from_sp() {
+        let name = CString::new("campfire").unwrap();
+
+        let sp_emitter_type_info = SPParticleEmitterTypeInfo {
+            name: name.into_raw(),
+            localID: ToPrimitive::to_u32(&EmitterTypeId::Campfire).unwrap(),
+        };
+
+        let emitter_type_info: EmitterTypeInfo<EmitterTypeId> =
+            TryFrom::try_from(sp_emitter_type_info).unwrap();
+
+        assert_eq!(emitter_type_info.name, "campfire".to_string());
+        assert_eq!(emitter_type_info.id, EmitterTypeId::Campfire);
+    }
+
+    #[test]
+    fn test_sp_emitter_type_info_from_rs() {
+        let emitter_type_info = EmitterTypeInfo {
+            name: "campfire".to_string(),
+            id: EmitterTypeId::Campfire,
+        };
+
+        let sp_emitter_type_info: SPParticleEmitterTypeInfo = emitter_type_info.try_into().unwrap();
+
+        assert_eq!(
+            unsafe { CStr::from_ptr(sp_emitter_type_info.name) }
+                .to_str()
+                .unwrap(),
+            "campfire"
+        );
+        assert_eq!(sp_emitter_type_info.localID, 0);
+    }
+
+    #[test]
+    fn thread_state_from_sp() {
+        let mut sp_thread_state = SPParticleThreadState {
+            particleManager: null_mut(),
+            addParticle: None,
+            spRand: null_mut(),
+            spNoise: null_mut(),
+        };
+
+        let thread_state = unsafe { ThreadState::from_sp(&mut sp_thread_state) };
+
+        assert_eq!(thread_state.rand.as_sp_rand(), null_mut());
+        assert_eq!(thread_state.noise.as_sp_noise(), null_mut());
+    }
+
+    static ADDED_PARTICLES: Mutex<Vec<(u32, f64)>> = Mutex::new(Vec::new());
+
+    unsafe extern "C" fn record_particle(
+        _particle_manager: *mut raw::c_void,
+        _emitter_state: *mut SPParticleEmitterState,
+        render_group: u32,
+        particle_state: *mut SPParticleState,
+    ) {
+        ADDED_PARTICLES
+            .lock()
+            .unwrap()
+            .push((render_group, (*particle_state).scale));
+    }
+
+    #[derive(Debug, PartialEq, FromPrimitive, ToPrimitive)]
+    enum AddParticleRenderGroup {
+        Registered = 40,
+        Unregistered = 41,
+    }
+
+    struct Negative;
+
+    impl ToPrimitive for Negative {
+        fn to_i64(&self) -> Option<i64> {
+            Some(-1)
+        }
+
+        fn to_u64(&self) -> Option<u64> {
+            None
+        }
+    }
+
+    fn particle(scale: f64) -> ParticleState {
+        ParticleState {
+            scale,
+            ..Default::default()
+        }
+    }
+
+    /// Everything that touches `ADDED_PARTICLES` runs in this one test, so other tests can't add to it
+    #[test]
+    fn add_particle_reports_errors() {
+        register_render_groups(&[RenderGroupInfo {
+            shader_name: "particle".to_string(),
+            id: AddParticleRenderGroup::Registered,
+            vertex_descriptions: vec![],
+        }]);
+        let mut emitter_state = EmitterState::default();
+
+        let mut sp_thread_state = SPParticleThreadState {
+            particleManager: null_mut(),
+            addParticle: None,
+            spRand: null_mut(),
+            spNoise: null_mut(),
+        };
+        let mut thread_state = unsafe { ThreadState::from_sp(&mut sp_thread_state) };
+        assert_eq!(
+            thread_state.add_particle(
+                &mut emitter_state,
+                AddParticleRenderGroup::Registered,
+                &mut particle(1.0)
+            ),
+            Err(AddParticleError::MissingCallback)
+        );
+
+        let mut sp_thread_state = SPParticleThreadState {
+            addParticle: Some(record_particle),
+            ..sp_thread_state
+        };
+        let mut thread_state = unsafe { ThreadState::from_sp(&mut sp_thread_state) };
+        assert_eq!(
+            thread_state.add_particle(&mut emitter_state, Negative, &mut particle(1.0)),
+            Err(AddParticleError::UnconvertibleRenderGroupId)
+        );
+        assert_eq!(
+            thread_state.add_particle(
+                &mut emitter_state,
+                AddParticleRenderGroup::Unregistered,
+                &mut particle(1.0)
+            ),
+            Err(AddParticleError::RenderGroupNotRegistered(41))
+        );
+        assert!(ADDED_PARTICLES.lock().unwrap().is_empty());
+
+        assert_eq!(
+            thread_state.add_particle(
+                &mut emitter_state,
+                AddParticleRenderGroup::Registered,
+                &mut particle(0.5)
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            thread_state.add_particles(
+                &mut emitter_state,
+                AddParticleRenderGroup::Registered,
+                (1..=3).map(|i| particle(i as f64))
+            ),
+            Ok(3)
+        );
+        assert_eq!(
+            *ADDED_PARTICLES.lock().unwrap(),
+            vec![(40, 0.5), (40, 1.0), (40, 2.0), (40, 3.0)]
+        );
+    }
+
+    #[test]
+    fn thread_state_is_send() {
+        fn assert_send<T: Send>() {}
+
+        assert_send::<ThreadState>();
+    }
+}
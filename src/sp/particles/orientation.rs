@@ -0,0 +1,249 @@
+//! Named access to the axes of an emitter's rotation matrix
+
+use crate::sp;
+use crate::sp::particles::EmitterState;
+use crate::sp::planet::TangentFrame;
+use crate::sp::vector::Vector3Like;
+use sapiens_sys::{SPMat3, SPVec3};
+
+/// The rotation of an emitter, viewed as three named axes
+///
+/// Sapiens stores an emitter's rotation as the rows of its `rot` matrix and multiplies vectors from the left (`v * M`):
+///
+/// | Row | Axis        | Local direction |
+/// |-----|-------------|-----------------|
+/// | 0   | `right()`   | `(1, 0, 0)`     |
+/// | 1   | `up()`      | `(0, 1, 0)`     |
+/// | 2   | `forward()` | `(0, 0, 1)`     |
+///
+/// For emitters placed on the ground, `up()` is the surface normal and the other two axes lie flat on the surface
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Orientation(SPMat3);
+
+impl Orientation {
+    /// Wraps a rotation matrix that's already in Sapiens' row layout
+    pub fn from_mat3(rot: SPMat3) -> Self {
+        Orientation(rot)
+    }
+
+    /// Reads the orientation of an emitter
+    pub fn of(emitter_state: &EmitterState) -> Self {
+        Orientation(emitter_state.rot)
+    }
+
+    /// Builds an orientation from its axes, which should be unit length and perpendicular to each other
+    pub fn from_axes<R: Vector3Like, U: Vector3Like, F: Vector3Like>(
+        right: &R,
+        up: &U,
+        forward: &F,
+    ) -> Self {
+        Orientation(sp::mat3_from_vec3s(
+            &right.to_sp_vec3(),
+            &up.to_sp_vec3(),
+            forward.to_sp_vec3(),
+        ))
+    }
+
+    /// Builds an orientation standing on the surface with the given up direction, facing `heading`
+    ///
+    /// `heading` is in radians clockwise from north, as returned by `sp::planet::initial_bearing`. The right axis is
+    /// `forward x up`, to the right of someone standing on the surface facing `heading`, so facing north it's the
+    /// `TangentFrame`'s east. Like x right, y up and z forward in general, the rows are a left-handed basis
+    pub fn from_surface_normal<N: Vector3Like>(normal: &N, heading: f64) -> Self {
+        let frame = TangentFrame::<SPVec3>::at(normal);
+        let (sin_heading, cos_heading) = heading.sin_cos();
+
+        let forward = frame
+            .north
+            .scaled(cos_heading)
+            .plus(&frame.east.scaled(sin_heading));
+        let right = forward.cross(&frame.up);
+
+        Orientation::from_axes(&right, &frame.up, &forward)
+    }
+
+    /// Builds an orientation standing on the surface at `position`, facing `heading`
+    ///
+    /// See `from_surface_normal`
+    pub fn at_position<P: Vector3Like>(position: &P, heading: f64) -> Self {
+        Orientation::from_surface_normal(
+            &sp::planet::surface_normal(&position.to_sp_vec3()),
+            heading,
+        )
+    }
+
+    /// Row 0 of the rotation matrix
+    pub fn right(&self) -> SPVec3 {
+        sp::mat3_get_row(&self.0, 0)
+    }
+
+    /// Row 1 of the rotation matrix
+    pub fn up(&self) -> SPVec3 {
+        sp::mat3_get_row(&self.0, 1)
+    }
+
+    /// Row 2 of the rotation matrix
+    pub fn forward(&self) -> SPVec3 {
+        sp::mat3_get_row(&self.0, 2)
+    }
+
+    /// Converts a direction relative to the emitter into world space
+    ///
+    /// This is `local.x * right + local.y * up + local.z * forward`
+    pub fn to_world<V: Vector3Like>(&self, local: &V) -> V {
+        V::from_sp_vec3(sp::vec3_x_mat3(&local.to_sp_vec3(), &self.0))
+    }
+
+    /// Converts a world space direction into one relative to the emitter
+    ///
+    /// The inverse of `to_world`, assuming the axes are orthonormal
+    pub fn to_local<V: Vector3Like>(&self, world: &V) -> V {
+        V::from_xyz(
+            world.dot(&self.right()),
+            world.dot(&self.up()),
+            world.dot(&self.forward()),
+        )
+    }
+
+    pub fn as_mat3(&self) -> &SPMat3 {
+        &self.0
+    }
+
+    /// Writes this orientation into an emitter's `rot`
+    pub fn apply_to(&self, emitter_state: &mut EmitterState) {
+        emitter_state.rot = self.0;
+    }
+}
+
+impl From<SPMat3> for Orientation {
+    fn from(rot: SPMat3) -> Self {
+        Orientation(rot)
+    }
+}
+
+impl From<Orientation> for SPMat3 {
+    fn from(orientation: Orientation) -> Self {
+        orientation.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_vec_near;
+    use crate::sp::planet;
+    use std::f64::consts::FRAC_PI_2;
+
+    fn vec3(x: f64, y: f64, z: f64) -> SPVec3 {
+        SPVec3 { x, y, z }
+    }
+
+    fn rows_1_to_9() -> Orientation {
+        Orientation::from_mat3(SPMat3 {
+            m0: 1.0,
+            m1: 2.0,
+            m2: 3.0,
+            m3: 4.0,
+            m4: 5.0,
+            m5: 6.0,
+            m6: 7.0,
+            m7: 8.0,
+            m8: 9.0,
+        })
+    }
+
+    #[test]
+    fn axes_are_rows() {
+        let orientation = rows_1_to_9();
+
+        assert_eq!(orientation.right(), vec3(1.0, 2.0, 3.0));
+        assert_eq!(orientation.up(), vec3(4.0, 5.0, 6.0));
+        assert_eq!(orientation.forward(), vec3(7.0, 8.0, 9.0));
+    }
+
+    #[test]
+    fn to_world_multiplies_from_the_left() {
+        let orientation = rows_1_to_9();
+
+        assert_vec_near!(
+            orientation.to_world(&vec3(1.0, 0.0, 0.0)),
+            orientation.right()
+        );
+        assert_vec_near!(
+            orientation.to_world(&vec3(0.0, 0.0, 1.0)),
+            orientation.forward()
+        );
+        assert_vec_near!(
+            orientation.to_world(&vec3(1.0, 10.0, 100.0)),
+            vec3(741.0, 852.0, 963.0)
+        );
+    }
+
+    #[test]
+    fn identity_axes() {
+        let orientation = Orientation::from_mat3(sp::mat3_identity());
+
+        assert_eq!(orientation.right(), vec3(1.0, 0.0, 0.0));
+        assert_eq!(orientation.up(), vec3(0.0, 1.0, 0.0));
+        assert_eq!(orientation.forward(), vec3(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn surface_normal_is_up() {
+        let normal = vec3(0.6, 0.0, 0.8);
+
+        let orientation = Orientation::from_surface_normal(&normal, 0.3);
+
+        assert_vec_near!(orientation.up(), normal);
+        assert_vec_near!(orientation.forward().dot(&normal), 0.0);
+        assert_vec_near!(orientation.right().dot(&normal), 0.0);
+        assert_vec_near!(
+            orientation.right(),
+            orientation.forward().cross(&orientation.up())
+        );
+    }
+
+    #[test]
+    fn right_is_to_the_right_of_the_heading() {
+        let position = vec3(0.0, 0.0, 1.0);
+        let frame = TangentFrame::<SPVec3>::at(&position);
+
+        let facing_north = Orientation::at_position(&position, 0.0);
+        let facing_east = Orientation::at_position(&position, FRAC_PI_2);
+
+        assert_vec_near!(facing_north.right(), frame.east);
+        assert_vec_near!(facing_east.right(), frame.north.scaled(-1.0));
+    }
+
+    #[test]
+    fn heading_is_clockwise_from_north() {
+        let position = vec3(0.0, 0.0, 1.0);
+
+        let facing_north = Orientation::at_position(&position, 0.0);
+        let facing_east = Orientation::at_position(&position, FRAC_PI_2);
+
+        assert_vec_near!(facing_north.forward(), vec3(0.0, 1.0, 0.0));
+        assert_vec_near!(facing_east.forward(), vec3(1.0, 0.0, 0.0));
+
+        let ahead = position.plus(&facing_east.forward().scaled(0.01));
+        assert_vec_near!(planet::initial_bearing(&position, &ahead), FRAC_PI_2);
+    }
+
+    #[test]
+    fn to_local_inverts_to_world() {
+        let orientation = Orientation::at_position(&vec3(0.3, 0.9, -0.2), 1.2);
+        let local = vec3(0.5, -2.0, 0.25);
+
+        assert_vec_near!(orientation.to_local(&orientation.to_world(&local)), local);
+    }
+
+    #[test]
+    fn round_trips_through_emitter_state() {
+        let orientation = Orientation::at_position(&vec3(1.0, 1.0, 0.0), 0.5);
+        let mut emitter_state = EmitterState::default();
+
+        orientation.apply_to(&mut emitter_state);
+
+        assert_eq!(Orientation::of(&emitter_state), orientation);
+    }
+}
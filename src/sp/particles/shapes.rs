@@ -0,0 +1,265 @@
+//! Shapes that particles can be emitted from
+//!
+//! Shapes are defined in the emitter's local space, where `y` is the emitter's up axis and `x`/`z` lie flat on the
+//! ground, and are rotated into world space by the emitter's `Orientation`. Sizes are in prerender units, so wrap
+//! distances in meters with `sp_meters_to_prerender!`
+
+use crate::sp::particles::{EmitterState, Orientation};
+use crate::sp::rand::RandomSource;
+use crate::sp::vector::Vector3Like;
+use sapiens_sys::SPVec3;
+use std::f64::consts::PI;
+
+/// A volume, surface, or set of directions to spawn particles from
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(sapiens_sys::serde::Serialize, sapiens_sys::serde::Deserialize),
+    serde(crate = "sapiens_sys::serde", rename_all = "snake_case")
+)]
+pub enum Shape {
+    /// Every particle starts at the emitter and moves along its up axis
+    Point,
+
+    /// Anywhere inside a ball, moving away from its center
+    Sphere { radius: f64 },
+
+    /// Anywhere inside the upper half of a ball, moving away from its center
+    Hemisphere { radius: f64 },
+
+    /// Anywhere on a flat disc lying on the ground, moving up
+    Disc { radius: f64 },
+
+    /// Anywhere on a flat ring lying on the ground, moving outwards
+    Ring {
+        inner_radius: f64,
+        outer_radius: f64,
+    },
+
+    /// From the emitter, in any direction within `angle` radians of the up axis
+    Cone { angle: f64 },
+
+    /// Anywhere inside a box, moving up. The half extents are along the right, up and forward axes
+    Box { half_extents: SPVec3 },
+}
+
+/// Where a particle starts and which way it's heading
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShapeSample {
+    /// Start position in prerender space
+    pub position: SPVec3,
+
+    /// Unit vector in the direction the particle should move
+    pub direction: SPVec3,
+}
+
+impl Shape {
+    /// Picks a random point and direction relative to an emitter at the origin
+    ///
+    /// `position` is an offset from the emitter, already rotated into world space
+    pub fn sample<R: RandomSource>(&self, rand: &R, orientation: &Orientation) -> ShapeSample {
+        let (position, direction) = self.sample_local(rand);
+
+        ShapeSample {
+            position: orientation.to_world(&position),
+            direction: orientation.to_world(&direction),
+        }
+    }
+
+    /// Picks a random point and direction around an emitter, using its position and rotation
+    pub fn sample_at<R: RandomSource>(
+        &self,
+        rand: &R,
+        emitter_state: &EmitterState,
+    ) -> ShapeSample {
+        let sample = self.sample(rand, &Orientation::of(emitter_state));
+
+        ShapeSample {
+            position: emitter_state.p.plus(&sample.position),
+            direction: sample.direction,
+        }
+    }
+
+    fn sample_local<R: RandomSource>(&self, rand: &R) -> (SPVec3, SPVec3) {
+        match *self {
+            Shape::Point => (SPVec3::default(), up()),
+            Shape::Sphere { radius } => {
+                let direction = rand.unit_sphere();
+                (
+                    direction.scaled(radius * rand.get_float().cbrt()),
+                    direction,
+                )
+            }
+            Shape::Hemisphere { radius } => {
+                let mut direction = rand.unit_sphere();
+                direction.y = direction.y.abs();
+                (
+                    direction.scaled(radius * rand.get_float().cbrt()),
+                    direction,
+                )
+            }
+            Shape::Disc { radius } => {
+                let outwards = unit_circle(rand);
+                (outwards.scaled(radius * rand.get_float().sqrt()), up())
+            }
+            Shape::Ring {
+                inner_radius,
+                outer_radius,
+            } => {
+                let outwards = unit_circle(rand);
+                // Uniform over the ring's area rather than its radius, so the outer edge isn't sparse
+                let inner2 = inner_radius * inner_radius;
+                let outer2 = outer_radius * outer_radius;
+                let radius = (inner2 + rand.get_float() * (outer2 - inner2)).sqrt();
+                (outwards.scaled(radius), outwards)
+            }
+            Shape::Cone { angle } => (SPVec3::default(), rand.on_sphere_cap(&up(), angle)),
+            Shape::Box { half_extents } => {
+                let position = SPVec3 {
+                    x: (rand.get_float() * 2.0 - 1.0) * half_extents.x,
+                    y: (rand.get_float() * 2.0 - 1.0) * half_extents.y,
+                    z: (rand.get_float() * 2.0 - 1.0) * half_extents.z,
+                };
+                (position, up())
+            }
+        }
+    }
+}
+
+fn up() -> SPVec3 {
+    SPVec3 {
+        x: 0.0,
+        y: 1.0,
+        z: 0.0,
+    }
+}
+
+/// A uniformly distributed unit vector in the local ground plane
+fn unit_circle<R: RandomSource>(rand: &R) -> SPVec3 {
+    let (sin, cos) = (rand.get_float() * 2.0 * PI).sin_cos();
+    SPVec3 {
+        x: cos,
+        y: 0.0,
+        z: sin,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_vec_near;
+    use crate::sp;
+    use crate::sp::rand::Rand;
+
+    const SAMPLES: usize = 200;
+
+    fn vec3(x: f64, y: f64, z: f64) -> SPVec3 {
+        SPVec3 { x, y, z }
+    }
+
+    fn samples(shape: Shape, orientation: &Orientation) -> Vec<ShapeSample> {
+        let rand = Rand::new(1234);
+        (0..SAMPLES)
+            .map(|_| shape.sample(&rand, orientation))
+            .collect()
+    }
+
+    fn tilted() -> Orientation {
+        Orientation::at_position(&vec3(0.3, -0.5, 0.8), 0.7)
+    }
+
+    #[test]
+    fn point_moves_along_up() {
+        let orientation = tilted();
+
+        for sample in samples(Shape::Point, &orientation) {
+            assert_vec_near!(sample.position, SPVec3::default());
+            assert_vec_near!(sample.direction, orientation.up());
+        }
+    }
+
+    #[test]
+    fn sphere_stays_inside_radius() {
+        for sample in samples(Shape::Sphere { radius: 0.5 }, &tilted()) {
+            assert!(sample.position.length() <= 0.5 + 1.0e-9);
+            assert_vec_near!(sample.direction.length(), 1.0);
+        }
+    }
+
+    #[test]
+    fn hemisphere_stays_above_ground() {
+        let orientation = tilted();
+
+        for sample in samples(Shape::Hemisphere { radius: 0.5 }, &orientation) {
+            assert!(sample.position.length() <= 0.5 + 1.0e-9);
+            assert!(sample.position.dot(&orientation.up()) >= -1.0e-9);
+            assert!(sample.direction.dot(&orientation.up()) >= -1.0e-9);
+        }
+    }
+
+    #[test]
+    fn disc_lies_on_the_ground() {
+        let orientation = tilted();
+
+        for sample in samples(Shape::Disc { radius: 2.0 }, &orientation) {
+            assert!(sample.position.length() <= 2.0 + 1.0e-9);
+            assert_vec_near!(sample.position.dot(&orientation.up()), 0.0);
+            assert_vec_near!(sample.direction, orientation.up());
+        }
+    }
+
+    #[test]
+    fn ring_stays_between_radii() {
+        let orientation = tilted();
+        let ring = Shape::Ring {
+            inner_radius: 1.0,
+            outer_radius: 2.0,
+        };
+
+        for sample in samples(ring, &orientation) {
+            let radius = sample.position.length();
+            assert!(radius >= 1.0 - 1.0e-9 && radius <= 2.0 + 1.0e-9);
+            assert_vec_near!(sample.position.dot(&orientation.up()), 0.0);
+            assert_vec_near!(sample.direction, sample.position.normalized());
+        }
+    }
+
+    #[test]
+    fn cone_stays_within_angle() {
+        let orientation = tilted();
+        let angle = 0.3;
+
+        for sample in samples(Shape::Cone { angle }, &orientation) {
+            assert_vec_near!(sample.position, SPVec3::default());
+            assert_vec_near!(sample.direction.length(), 1.0);
+            assert!(sample.direction.dot(&orientation.up()) >= angle.cos() - 1.0e-9);
+        }
+    }
+
+    #[test]
+    fn box_stays_inside_extents() {
+        let orientation = tilted();
+        let half_extents = vec3(1.0, 0.25, 3.0);
+
+        for sample in samples(Shape::Box { half_extents }, &orientation) {
+            let local = orientation.to_local(&sample.position);
+            assert!(local.x.abs() <= 1.0 + 1.0e-9);
+            assert!(local.y.abs() <= 0.25 + 1.0e-9);
+            assert!(local.z.abs() <= 3.0 + 1.0e-9);
+        }
+    }
+
+    #[test]
+    fn sample_at_offsets_from_emitter() {
+        let mut emitter_state = EmitterState::default();
+        emitter_state.p = vec3(0.0, 1.0, 0.0);
+        emitter_state.rot = sp::mat3_identity();
+        let rand = Rand::new(5);
+
+        let sample = Shape::Disc { radius: 0.1 }.sample_at(&rand, &emitter_state);
+
+        assert_vec_near!(sample.position.y, 1.0);
+        assert!(sample.position.distance(&emitter_state.p) <= 0.1);
+        assert_vec_near!(sample.direction, vec3(0.0, 1.0, 0.0));
+    }
+}
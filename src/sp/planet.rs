@@ -0,0 +1,301 @@
+//! Geometry helpers for Sapiens' spherical world
+//!
+//! The planet is a sphere centered at the origin. Positions are in prerender units, where sea level is a distance of
+//! `PLANET_RADIUS` from the center, so "up" at any point is just the direction away from the origin
+//!
+//! Latitude and longitude are in radians. Latitude is measured from the equator towards the +Y pole, and longitude is
+//! measured around the Y axis starting at +Z and increasing towards +X
+
+use crate::sp::vector::Vector3Like;
+use sapiens_sys::SPVec3;
+use std::f64::consts::PI;
+
+/// Distance from the center of the planet to sea level, in prerender units
+pub const PLANET_RADIUS: f64 = 1.0;
+
+/// Height of `p` above sea level, in prerender units. Negative below sea level
+pub fn altitude<V: Vector3Like>(p: &V) -> f64 {
+    p.length() - PLANET_RADIUS
+}
+
+/// The "up" direction at `p`, i.e. the unit vector pointing away from the center of the planet
+pub fn surface_normal<V: Vector3Like>(p: &V) -> V {
+    p.normalized()
+}
+
+/// Moves `p` straight up (or down, if `offset` is negative) by `offset` prerender units
+pub fn offset_along_up<V: Vector3Like>(p: &V, offset: f64) -> V {
+    let length = p.length();
+    p.scaled((length + offset) / length)
+}
+
+/// Returns the point directly above or below `p` at `altitude` prerender units above sea level
+pub fn at_altitude<V: Vector3Like>(p: &V, altitude: f64) -> V {
+    surface_normal(p).scaled(PLANET_RADIUS + altitude)
+}
+
+/// A position on the planet's surface
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(sapiens_sys::serde::Serialize, sapiens_sys::serde::Deserialize),
+    serde(crate = "sapiens_sys::serde")
+)]
+pub struct LatLong {
+    /// Radians north of the equator, from `-PI / 2` to `PI / 2`
+    pub latitude: f64,
+    /// Radians east of the +Z meridian, from `-PI` to `PI`
+    pub longitude: f64,
+}
+
+impl LatLong {
+    pub fn new(latitude: f64, longitude: f64) -> Self {
+        LatLong {
+            latitude,
+            longitude,
+        }
+    }
+
+    pub fn from_degrees(latitude: f64, longitude: f64) -> Self {
+        LatLong::new(latitude.to_radians(), longitude.to_radians())
+    }
+
+    /// Finds the latitude and longitude of the point on the surface directly below `p`
+    pub fn from_vector<V: Vector3Like>(p: &V) -> Self {
+        let v = p.to_sp_vec3();
+        let length = p.length();
+
+        LatLong {
+            latitude: (v.y / length).clamp(-1.0, 1.0).asin(),
+            longitude: v.x.atan2(v.z),
+        }
+    }
+
+    /// Returns the unit vector pointing at this position
+    pub fn to_unit_vector<V: Vector3Like>(&self) -> V {
+        let (sin_lat, cos_lat) = self.latitude.sin_cos();
+        let (sin_long, cos_long) = self.longitude.sin_cos();
+
+        V::from_xyz(cos_lat * sin_long, sin_lat, cos_lat * cos_long)
+    }
+
+    pub fn latitude_degrees(&self) -> f64 {
+        self.latitude.to_degrees()
+    }
+
+    pub fn longitude_degrees(&self) -> f64 {
+        self.longitude.to_degrees()
+    }
+}
+
+/// The angle between `a` and `b` as seen from the center of the planet, in radians
+pub fn central_angle<A: Vector3Like, B: Vector3Like>(a: &A, b: &B) -> f64 {
+    let a = a.to_sp_vec3().normalized();
+    let b = b.to_sp_vec3().normalized();
+
+    // atan2 stays accurate for nearly identical and nearly opposite points, unlike acos of the dot product
+    a.cross(&b).length().atan2(a.dot(&b))
+}
+
+/// The shortest distance between `a` and `b` along the surface at sea level, in prerender units
+pub fn great_circle_distance<A: Vector3Like, B: Vector3Like>(a: &A, b: &B) -> f64 {
+    central_angle(a, b) * PLANET_RADIUS
+}
+
+/// The direction to head in from `from` to reach `to` along a great circle
+///
+/// Returns radians clockwise from north, from `0` to `2 * PI`. Heading towards the pole you're standing on isn't well
+/// defined, see `TangentFrame::at`
+pub fn initial_bearing<A: Vector3Like, B: Vector3Like>(from: &A, to: &B) -> f64 {
+    let frame = TangentFrame::<SPVec3>::at(from);
+    let direction = to.to_sp_vec3().normalized();
+
+    let bearing = direction
+        .dot(&frame.east)
+        .atan2(direction.dot(&frame.north));
+    if bearing < 0.0 {
+        bearing + 2.0 * PI
+    } else {
+        bearing
+    }
+}
+
+/// Orthonormal axes lying flat on the surface at a point, plus the surface normal
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TangentFrame<V> {
+    pub east: V,
+    pub north: V,
+    pub up: V,
+}
+
+impl<V: Vector3Like> TangentFrame<V> {
+    /// Builds the frame at `p`
+    ///
+    /// North points towards the +Y pole. Exactly at a pole every direction is south (or north), so the frame falls back
+    /// to treating +X as east
+    pub fn at<P: Vector3Like>(p: &P) -> Self {
+        let up = p.to_sp_vec3().normalized();
+        let pole = SPVec3 {
+            x: 0.0,
+            y: 1.0,
+            z: 0.0,
+        };
+
+        let east = pole.cross(&up);
+        let east = if east.length2() < 1.0e-24 {
+            SPVec3 {
+                x: 1.0,
+                y: 0.0,
+                z: 0.0,
+            }
+        } else {
+            east.normalized()
+        };
+        let north = up.cross(&east);
+
+        TangentFrame {
+            east: east.convert(),
+            north: north.convert(),
+            up: up.convert(),
+        }
+    }
+
+    /// Converts a direction given as `(east, north, up)` components into a world space direction
+    pub fn to_world<L: Vector3Like>(&self, local: &L) -> V {
+        self.east
+            .scaled(local.x())
+            .plus(&self.north.scaled(local.y()))
+            .plus(&self.up.scaled(local.z()))
+    }
+
+    /// Converts a world space direction into `(east, north, up)` components
+    pub fn to_local<W: Vector3Like>(&self, world: &W) -> V {
+        V::from_xyz(
+            world.dot(&self.east),
+            world.dot(&self.north),
+            world.dot(&self.up),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_vec_near;
+    use crate::sp::math::Vec3;
+    use std::f64::consts::FRAC_PI_2;
+
+    fn vec3(x: f64, y: f64, z: f64) -> SPVec3 {
+        SPVec3 { x, y, z }
+    }
+
+    #[test]
+    fn altitude_is_relative_to_sea_level() {
+        assert_vec_near!(altitude(&vec3(0.0, 1.5, 0.0)), 0.5);
+        assert_vec_near!(altitude(&Vec3::new(0.0, 0.0, -0.75)), -0.25);
+    }
+
+    #[test]
+    fn offset_along_up_moves_away_from_center() {
+        let p = vec3(3.0, 4.0, 0.0);
+
+        let moved = offset_along_up(&p, 1.0);
+
+        assert_vec_near!(moved, vec3(3.6, 4.8, 0.0));
+        assert_vec_near!(surface_normal(&moved), surface_normal(&p));
+    }
+
+    #[test]
+    fn at_altitude_ignores_current_height() {
+        let p = vec3(0.0, 0.0, 2.0);
+
+        assert_vec_near!(at_altitude(&p, 0.1), vec3(0.0, 0.0, 1.1));
+    }
+
+    #[test]
+    fn lat_long_axes() {
+        assert_vec_near!(
+            LatLong::new(0.0, 0.0).to_unit_vector::<SPVec3>(),
+            vec3(0.0, 0.0, 1.0)
+        );
+        assert_vec_near!(
+            LatLong::new(0.0, FRAC_PI_2).to_unit_vector::<SPVec3>(),
+            vec3(1.0, 0.0, 0.0)
+        );
+        assert_vec_near!(
+            LatLong::new(FRAC_PI_2, 0.0).to_unit_vector::<SPVec3>(),
+            vec3(0.0, 1.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn lat_long_round_trip() {
+        let position = LatLong::from_degrees(35.0, -120.0);
+
+        let round_tripped = LatLong::from_vector(&position.to_unit_vector::<SPVec3>().scaled(1.2));
+
+        assert_vec_near!(round_tripped.latitude_degrees(), 35.0);
+        assert_vec_near!(round_tripped.longitude_degrees(), -120.0);
+    }
+
+    #[test]
+    fn great_circle_distance_is_arc_length() {
+        let a = vec3(0.0, 0.0, 1.0);
+        let b = vec3(2.0, 0.0, 0.0);
+
+        assert_vec_near!(great_circle_distance(&a, &b), FRAC_PI_2);
+        assert_vec_near!(great_circle_distance(&a, &vec3(0.0, 0.0, -3.0)), PI);
+        assert_vec_near!(great_circle_distance(&a, &a), 0.0);
+    }
+
+    #[test]
+    fn initial_bearing_is_clockwise_from_north() {
+        let origin = LatLong::new(0.0, 0.0).to_unit_vector::<SPVec3>();
+
+        let north = initial_bearing(
+            &origin,
+            &LatLong::from_degrees(10.0, 0.0).to_unit_vector::<SPVec3>(),
+        );
+        let east = initial_bearing(
+            &origin,
+            &LatLong::from_degrees(0.0, 10.0).to_unit_vector::<SPVec3>(),
+        );
+        let west = initial_bearing(
+            &origin,
+            &LatLong::from_degrees(0.0, -10.0).to_unit_vector::<SPVec3>(),
+        );
+
+        assert_vec_near!(north, 0.0);
+        assert_vec_near!(east, FRAC_PI_2);
+        assert_vec_near!(west, 3.0 * FRAC_PI_2);
+    }
+
+    #[test]
+    fn tangent_frame_is_orthonormal() {
+        let frame = TangentFrame::<SPVec3>::at(&vec3(0.3, -0.8, 0.5));
+
+        assert_vec_near!(frame.east.length(), 1.0);
+        assert_vec_near!(frame.north.length(), 1.0);
+        assert_vec_near!(frame.east.dot(&frame.north), 0.0);
+        assert_vec_near!(frame.east.dot(&frame.up), 0.0);
+        assert_vec_near!(frame.north.dot(&frame.up), 0.0);
+        // Right handed, so that east x north = up
+        assert_vec_near!(frame.east.cross(&frame.north), frame.up);
+    }
+
+    #[test]
+    fn tangent_frame_at_pole_falls_back_to_x() {
+        let frame = TangentFrame::<SPVec3>::at(&vec3(0.0, 1.0, 0.0));
+
+        assert_vec_near!(frame.east, vec3(1.0, 0.0, 0.0));
+        assert_vec_near!(frame.east.cross(&frame.north), frame.up);
+    }
+
+    #[test]
+    fn tangent_frame_round_trip() {
+        let frame = TangentFrame::<SPVec3>::at(&vec3(1.0, 1.0, 1.0));
+        let local = vec3(0.25, -0.5, 2.0);
+
+        assert_vec_near!(frame.to_local(&frame.to_world(&local)), local);
+    }
+}
@@ -0,0 +1,189 @@
+//! Typed inputs for the rainfall hook, and a handle to the game's own rainfall function
+//!
+//! `spRainfallGet` refines the two seasons' rainfall that Sapiens has already worked out for a point. Both the inputs
+//! and the result are in the order Sapiens uses, so index `0` of `pregen_rainfall` corresponds to `outRainfall0`
+
+use crate::sp::common::WorldGenOptions;
+#[cfg(feature = "vanilla")]
+use crate::sp::common::{self, LoadVanillaError};
+use crate::sp::noise::NoiseRef;
+use sapiens_sys::{SPNoise, SPRainfallGetFunc, SPVec3, SPWorldGenOptions};
+
+/// Everything `spRainfallGet` is told about a point, apart from the noise and world-gen options
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RainfallQuery {
+    /// Unit vector from the planet's center to the point
+    pub point_normal: SPVec3,
+
+    /// Where to sample noise for this point
+    pub noise_loc: SPVec3,
+
+    /// Distance to the nearest river, in prerender units
+    pub river_distance: f64,
+
+    /// The rainfall Sapiens calculated for each season before calling the hook
+    pub pregen_rainfall: [f64; 2],
+}
+
+impl RainfallQuery {
+    /// Gathers the arguments of `spRainfallGet` which describe the point, in the same order
+    pub fn from_sp(
+        point_normal: SPVec3,
+        noise_loc: SPVec3,
+        river_distance: f64,
+        pregen_rainfall0: f64,
+        pregen_rainfall1: f64,
+    ) -> Self {
+        RainfallQuery {
+            point_normal,
+            noise_loc,
+            river_distance,
+            pregen_rainfall: [pregen_rainfall0, pregen_rainfall1],
+        }
+    }
+}
+
+type RainfallGetFn = unsafe extern "C" fn(
+    noise: *mut SPNoise,
+    point_normal: SPVec3,
+    noise_loc: SPVec3,
+    world_gen_options: SPWorldGenOptions,
+    river_distance: f64,
+    pregen_rainfall0: f64,
+    pregen_rainfall1: f64,
+    out_rainfall0: *mut f64,
+    out_rainfall1: *mut f64,
+);
+
+/// The game's own `spRainfallGet`, or a stand-in for it
+#[derive(Debug, Clone, Copy)]
+pub struct Vanilla(RainfallGetFn);
+
+impl Vanilla {
+    /// Finds the game's `spRainfallGet` in SPCommon, with the `vanilla` feature
+    #[cfg(feature = "vanilla")]
+    pub fn load() -> Result<Self, LoadVanillaError> {
+        unsafe { common::load_vanilla("spRainfallGet").map(Vanilla) }
+    }
+
+    /// Wraps a rainfall function the mod got some other way, or `None` if it's null
+    ///
+    /// # Safety
+    ///
+    /// `func` must behave like `spRainfallGet`, and be safe to call with any valid noise and options
+    pub unsafe fn from_sp(func: SPRainfallGetFunc) -> Option<Self> {
+        func.map(Vanilla)
+    }
+
+    /// A simple rainfall function for tests and offline tools, which scales the pregenerated rainfall by the rainfall
+    /// multiplier
+    pub fn stand_in() -> Self {
+        Vanilla(stand_in_rainfall)
+    }
+
+    /// Computes both seasons' rainfall at a point, the same way Sapiens would call the function
+    pub fn get(
+        &self,
+        noise: &NoiseRef,
+        query: &RainfallQuery,
+        options: &WorldGenOptions,
+    ) -> [f64; 2] {
+        let mut rainfall = query.pregen_rainfall;
+        let [rainfall0, rainfall1] = &mut rainfall;
+
+        unsafe {
+            (self.0)(
+                noise.as_sp_noise(),
+                query.point_normal,
+                query.noise_loc,
+                *options.as_sp_world_gen_options(),
+                query.river_distance,
+                query.pregen_rainfall[0],
+                query.pregen_rainfall[1],
+                rainfall0,
+                rainfall1,
+            )
+        };
+
+        rainfall
+    }
+
+    pub fn as_sp_rainfall_get_func(&self) -> SPRainfallGetFunc {
+        Some(self.0)
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+unsafe extern "C" fn stand_in_rainfall(
+    _noise: *mut SPNoise,
+    _point_normal: SPVec3,
+    _noise_loc: SPVec3,
+    world_gen_options: SPWorldGenOptions,
+    _river_distance: f64,
+    pregen_rainfall0: f64,
+    pregen_rainfall1: f64,
+    out_rainfall0: *mut f64,
+    out_rainfall1: *mut f64,
+) {
+    *out_rainfall0 = pregen_rainfall0 * world_gen_options.rainfallMultiplier;
+    *out_rainfall1 = pregen_rainfall1 * world_gen_options.rainfallMultiplier;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sp::noise::Noise;
+
+    fn query() -> RainfallQuery {
+        RainfallQuery::from_sp(
+            SPVec3 {
+                x: 1.0,
+                y: 0.0,
+                z: 0.0,
+            },
+            SPVec3::default(),
+            0.5,
+            100.0,
+            40.0,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    unsafe extern "C" fn wetter_near_rivers(
+        _noise: *mut SPNoise,
+        _point_normal: SPVec3,
+        _noise_loc: SPVec3,
+        _world_gen_options: SPWorldGenOptions,
+        river_distance: f64,
+        _pregen_rainfall0: f64,
+        _pregen_rainfall1: f64,
+        out_rainfall0: *mut f64,
+        _out_rainfall1: *mut f64,
+    ) {
+        *out_rainfall0 += 10.0 / river_distance;
+    }
+
+    #[test]
+    fn starts_from_the_pregenerated_rainfall() {
+        let noise = Noise::new(1, 0.5);
+        let vanilla = unsafe { Vanilla::from_sp(Some(wetter_near_rivers)) }.unwrap();
+
+        let rainfall = vanilla.get(&noise.handle(), &query(), &WorldGenOptions::vanilla());
+
+        assert_eq!(rainfall, [120.0, 40.0]);
+        assert!(unsafe { Vanilla::from_sp(None) }.is_none());
+    }
+
+    #[test]
+    fn stand_in_applies_the_multiplier() {
+        let noise = Noise::new(1, 0.5);
+        let options = WorldGenOptions::builder()
+            .rainfall_multiplier(2.5)
+            .build()
+            .unwrap();
+
+        let rainfall = Vanilla::stand_in().get(&noise.handle(), &query(), &options);
+
+        assert_eq!(rainfall, [250.0, 100.0]);
+    }
+}
@@ -1,5 +1,109 @@
+//! Sapiens' random number generator, and a trait for anything that can stand in for it
+//!
+//! `Rand` owns a generator created by the mod and frees it when dropped. `RandRef` borrows one that belongs to
+//! someone else, such as the generator in a particle thread state, and can never free it. Code that only needs random
+//! numbers should take a `RandomSource`, so that tests can hand it one of the generators in `sp::mock` instead
+
+use crate::sp::planet::TangentFrame;
+use crate::sp::vector::Vector3Like;
 use sapiens_sys::{spRandDelete, spRandGetValue, spRandGetVec3, spRandNew, SPRand, SPVec3};
+use std::f64::consts::PI;
+use std::marker::PhantomData;
+use std::ops::{Range, RangeInclusive};
+
+/// Anything that can produce uniformly distributed random numbers
+///
+/// Only `get_float` needs implementing; everything else is built on top of it
+pub trait RandomSource {
+    /// Gets a random float in `[0, 1)`
+    fn get_float(&self) -> f64;
+
+    /// Gets a random vector with each component in `[-1, 1)`
+    fn get_vec3(&self) -> SPVec3 {
+        SPVec3 {
+            x: self.get_float() * 2.0 - 1.0,
+            y: self.get_float() * 2.0 - 1.0,
+            z: self.get_float() * 2.0 - 1.0,
+        }
+    }
+
+    /// Gets a random value in `range`, e.g. `rand.range(0..4)` or `rand.range(0.5..=1.0)`
+    ///
+    /// Panics if the range is empty
+    fn range<R: RandomRange>(&self, range: R) -> R::Output {
+        range.pick(self.get_float())
+    }
+
+    /// Returns `true` with probability `probability`
+    fn chance(&self, probability: f64) -> bool {
+        self.get_float() < probability
+    }
+
+    /// Picks a random element of `items`, or `None` if it's empty
+    fn choose<'items, T>(&self, items: &'items [T]) -> Option<&'items T> {
+        if items.is_empty() {
+            None
+        } else {
+            items.get(self.range(0..items.len()))
+        }
+    }
+
+    /// Randomly reorders `items`
+    fn shuffle<T>(&self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.range(0..=i));
+        }
+    }
+
+    /// Gets a unit vector pointing in a uniformly random direction
+    fn unit_sphere(&self) -> SPVec3 {
+        let y = self.get_float() * 2.0 - 1.0;
+        let ring_radius = (1.0 - y * y).max(0.0).sqrt();
+        let (sin, cos) = (self.get_float() * 2.0 * PI).sin_cos();
+
+        SPVec3 {
+            x: cos * ring_radius,
+            y,
+            z: sin * ring_radius,
+        }
+    }
+
+    /// Gets a unit vector pointing in a uniformly random direction at most `angle` radians away from `normal`
+    fn on_sphere_cap<V: Vector3Like>(&self, normal: &V, angle: f64) -> SPVec3 {
+        let frame = TangentFrame::<SPVec3>::at(normal);
 
+        let cos_theta = 1.0 - self.get_float() * (1.0 - angle.cos());
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let (sin_phi, cos_phi) = (self.get_float() * 2.0 * PI).sin_cos();
+
+        frame.to_world(&SPVec3 {
+            x: cos_phi * sin_theta,
+            y: sin_phi * sin_theta,
+            z: cos_theta,
+        })
+    }
+
+    /// Gets a normally distributed value with the given mean and standard deviation
+    fn normal(&self, mean: f64, standard_deviation: f64) -> f64 {
+        // Box-Muller transform. `1 - x` keeps the logarithm's argument in (0, 1]
+        let u1 = 1.0 - self.get_float();
+        let u2 = self.get_float();
+
+        mean + standard_deviation * (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos()
+    }
+}
+
+impl<T: RandomSource> RandomSource for &T {
+    fn get_float(&self) -> f64 {
+        (**self).get_float()
+    }
+
+    fn get_vec3(&self) -> SPVec3 {
+        (**self).get_vec3()
+    }
+}
+
+/// A random number generator created by this mod
 pub struct Rand(*mut SPRand);
 
 impl Rand {
@@ -8,13 +112,13 @@ impl Rand {
         Rand(unsafe { spRandNew(seed) })
     }
 
-    pub fn from_ptr(ptr: *mut SPRand) -> Self {
-        Rand(ptr)
+    /// Borrows this generator as a `RandRef`, e.g. to hand it to code that expects one from a thread state
+    pub fn handle(&self) -> RandRef<'_> {
+        unsafe { RandRef::from_ptr(self.0) }
     }
 
-    /// Destroys the rng
-    pub fn delete(self) {
-        unsafe { spRandDelete(self.0) }
+    pub fn as_sp_rand(&self) -> *mut SPRand {
+        self.0
     }
 
     /// Gets a random Vec3 from this rng
@@ -27,3 +131,304 @@ impl Rand {
         unsafe { spRandGetValue(self.0) }
     }
 }
+
+impl Drop for Rand {
+    fn drop(&mut self) {
+        unsafe { spRandDelete(self.0) }
+    }
+}
+
+impl RandomSource for Rand {
+    fn get_float(&self) -> f64 {
+        Rand::get_float(self)
+    }
+
+    fn get_vec3(&self) -> SPVec3 {
+        Rand::get_vec3(self)
+    }
+}
+
+/// A random number generator borrowed from Sapiens or from a `Rand`
+///
+/// This isn't `Clone`, so that a thread state's generator can only be used by whoever holds the thread state
+pub struct RandRef<'a> {
+    ptr: *mut SPRand,
+    _game_rand: PhantomData<&'a SPRand>,
+}
+
+impl<'a> RandRef<'a> {
+    /// Borrows a generator owned by someone else
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must point to a valid `SPRand` which isn't freed for the whole lifetime `'a`
+    pub unsafe fn from_ptr(ptr: *mut SPRand) -> Self {
+        RandRef {
+            ptr,
+            _game_rand: PhantomData,
+        }
+    }
+
+    pub fn as_sp_rand(&self) -> *mut SPRand {
+        self.ptr
+    }
+
+    /// Gets a random Vec3 from this rng
+    pub fn get_vec3(&self) -> SPVec3 {
+        unsafe { spRandGetVec3(self.ptr) }
+    }
+
+    /// Gets a random float from this rng
+    pub fn get_float(&self) -> f64 {
+        unsafe { spRandGetValue(self.ptr) }
+    }
+}
+
+impl RandomSource for RandRef<'_> {
+    fn get_float(&self) -> f64 {
+        RandRef::get_float(self)
+    }
+
+    fn get_vec3(&self) -> SPVec3 {
+        RandRef::get_vec3(self)
+    }
+}
+
+/// A range of values that `RandomSource::range` can pick from
+pub trait RandomRange {
+    type Output;
+
+    /// Maps `t`, which is in `[0, 1)`, to a value in this range
+    fn pick(self, t: f64) -> Self::Output;
+}
+
+impl RandomRange for Range<f64> {
+    type Output = f64;
+
+    fn pick(self, t: f64) -> f64 {
+        assert!(self.start < self.end, "cannot sample empty range");
+        self.start + t * (self.end - self.start)
+    }
+}
+
+impl RandomRange for RangeInclusive<f64> {
+    type Output = f64;
+
+    fn pick(self, t: f64) -> f64 {
+        let (start, end) = self.into_inner();
+        assert!(start <= end, "cannot sample empty range");
+        start + t * (end - start)
+    }
+}
+
+macro_rules! impl_random_range_for_int {
+    ($($int:ty),*) => {
+        $(
+            impl RandomRange for Range<$int> {
+                type Output = $int;
+
+                fn pick(self, t: f64) -> $int {
+                    assert!(self.start < self.end, "cannot sample empty range");
+                    // Work in i128 so that spans wider than the type itself (e.g. -100_i8..100) don't overflow
+                    let span = self.end as i128 - self.start as i128;
+                    let offset = ((t * span as f64) as i128).min(span - 1);
+                    (self.start as i128 + offset) as $int
+                }
+            }
+
+            impl RandomRange for RangeInclusive<$int> {
+                type Output = $int;
+
+                fn pick(self, t: f64) -> $int {
+                    let (start, end) = self.into_inner();
+                    assert!(start <= end, "cannot sample empty range");
+                    let span = end as i128 - start as i128 + 1;
+                    let offset = ((t * span as f64) as i128).min(span - 1);
+                    (start as i128 + offset) as $int
+                }
+            }
+        )*
+    };
+}
+
+impl_random_range_for_int!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+/// Lets Sapiens' generators drive anything in the `rand` ecosystem, e.g. `rand::seq::SliceRandom` or `rand_distr`'s
+/// distributions
+#[cfg(feature = "rand_core")]
+macro_rules! impl_rng_core {
+    ($rand:ty) => {
+        impl rand_core::RngCore for $rand {
+            fn next_u32(&mut self) -> u32 {
+                // Sapiens hands out floats in [0, 1), so scale one up to the full range of a u32
+                (self.get_float() * 4_294_967_296.0) as u32
+            }
+
+            fn next_u64(&mut self) -> u64 {
+                rand_core::impls::next_u64_via_u32(self)
+            }
+
+            fn fill_bytes(&mut self, dest: &mut [u8]) {
+                rand_core::impls::fill_bytes_via_next(self, dest)
+            }
+
+            fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+                self.fill_bytes(dest);
+                Ok(())
+            }
+        }
+    };
+}
+
+#[cfg(feature = "rand_core")]
+impl_rng_core!(Rand);
+
+#[cfg(feature = "rand_core")]
+impl_rng_core!(RandRef<'_>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_vec_near;
+
+    const SAMPLES: usize = 500;
+
+    fn with_rand<F: FnOnce(&Rand)>(test: F) {
+        test(&Rand::new(42));
+    }
+
+    #[test]
+    fn int_ranges_stay_in_bounds() {
+        with_rand(|rand| {
+            for _ in 0..SAMPLES {
+                let value = rand.range(-3..4);
+                assert!((-3..4).contains(&value));
+
+                let value = rand.range(1_u8..=6);
+                assert!((1..=6).contains(&value));
+            }
+        });
+    }
+
+    #[test]
+    fn int_range_endpoints() {
+        assert_eq!((0..4).pick(0.0), 0);
+        assert_eq!((0..4).pick(0.999_999_999), 3);
+        assert_eq!((0..=4).pick(0.999_999_999), 4);
+        assert_eq!((7..8).pick(0.5), 7);
+        assert_eq!((-100_i8..100).pick(0.999_999_999), 99);
+        assert_eq!((i64::MIN..=i64::MAX).pick(0.0), i64::MIN);
+    }
+
+    #[test]
+    fn float_ranges_stay_in_bounds() {
+        with_rand(|rand| {
+            for _ in 0..SAMPLES {
+                let value = rand.range(2.0..2.5);
+                assert!((2.0..2.5).contains(&value));
+            }
+        });
+    }
+
+    #[test]
+    #[should_panic]
+    fn empty_range_panics() {
+        (3..3).pick(0.5);
+    }
+
+    #[test]
+    fn chance_extremes() {
+        with_rand(|rand| {
+            for _ in 0..SAMPLES {
+                assert!(!rand.chance(0.0));
+                assert!(rand.chance(1.0));
+            }
+        });
+    }
+
+    #[test]
+    fn choose_picks_an_element() {
+        with_rand(|rand| {
+            let items = ["smoke", "fire", "spark"];
+
+            for _ in 0..SAMPLES {
+                assert!(items.contains(rand.choose(&items).unwrap()));
+            }
+            assert_eq!(rand.choose::<i32>(&[]), None);
+        });
+    }
+
+    #[test]
+    fn shuffle_keeps_elements() {
+        with_rand(|rand| {
+            let mut items = [1, 2, 3, 4, 5, 6, 7, 8];
+
+            rand.shuffle(&mut items);
+            items.sort_unstable();
+
+            assert_eq!(items, [1, 2, 3, 4, 5, 6, 7, 8]);
+        });
+    }
+
+    #[test]
+    fn unit_sphere_is_unit_length() {
+        with_rand(|rand| {
+            for _ in 0..SAMPLES {
+                assert_vec_near!(rand.unit_sphere().length(), 1.0);
+            }
+        });
+    }
+
+    #[test]
+    fn sphere_cap_stays_within_angle() {
+        with_rand(|rand| {
+            let normal = SPVec3 {
+                x: 0.0,
+                y: 0.6,
+                z: 0.8,
+            };
+
+            for _ in 0..SAMPLES {
+                let direction = rand.on_sphere_cap(&normal, 0.2);
+
+                assert_vec_near!(direction.length(), 1.0);
+                assert!(direction.dot(&normal) >= 0.2_f64.cos() - 1.0e-9);
+            }
+        });
+    }
+
+    #[test]
+    fn normal_has_roughly_the_right_mean() {
+        with_rand(|rand| {
+            let mean = (0..SAMPLES).map(|_| rand.normal(10.0, 2.0)).sum::<f64>() / SAMPLES as f64;
+
+            assert_vec_near!(mean, 10.0, epsilon = 0.5);
+        });
+    }
+
+    #[cfg(feature = "rand_core")]
+    #[test]
+    fn rng_core_fills_bytes() {
+        use rand_core::RngCore;
+
+        let mut rand = Rand::new(7);
+        let mut bytes = [0_u8; 13];
+
+        rand.fill_bytes(&mut bytes);
+
+        assert!(bytes.iter().any(|&b| b != 0));
+    }
+
+    #[test]
+    fn rand_ref_borrows_without_freeing() {
+        let owner = Rand::new(3);
+
+        {
+            let borrowed = unsafe { RandRef::from_ptr(owner.as_sp_rand()) };
+            borrowed.get_float();
+        }
+
+        // Still usable after the borrow is gone
+        assert!((0.0..1.0).contains(&owner.get_float()));
+    }
+}
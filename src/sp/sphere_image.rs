@@ -0,0 +1,713 @@
+//! Images wrapped around the planet, for heightmaps and biome maps authored in an image editor
+//!
+//! An image is either equirectangular or a cube map. Equirectangular images use the same layout as
+//! `noise::preview::lat_long_at`: the left and right edges meet at the -Z meridian, with each column centered on its
+//! longitude, the top row lies on the +Y pole and the bottom row on the -Y pole. That way a preview can be painted over
+//! and loaded back in, and sampling wraps around from the right column to the left one
+//!
+//! Cube maps are a horizontal strip of six square faces, in the order +X, -X, +Y, -Y, +Z, -Z, each oriented like an
+//! OpenGL cube map face. Sampling doesn't blend across face edges
+//!
+//! Images are read from binary or plain PGM files, and from PNG files with the `png` feature
+
+use sapiens_sys::SPVec3;
+use std::error::Error;
+use std::f64::consts::{FRAC_PI_2, PI};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// How an image is wrapped around the planet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(sapiens_sys::serde::Serialize, sapiens_sys::serde::Deserialize),
+    serde(crate = "sapiens_sys::serde", rename_all = "snake_case")
+)]
+pub enum Projection {
+    /// Longitude across, latitude down
+    Equirectangular,
+
+    /// Six square faces side by side, so the image is six times as wide as it is tall
+    CubeMap,
+}
+
+/// A grid of values covering the whole planet
+#[derive(Debug, Clone, PartialEq)]
+pub struct SphereImage<T> {
+    width: usize,
+    height: usize,
+    projection: Projection,
+    values: Vec<T>,
+}
+
+impl<T: Copy> SphereImage<T> {
+    /// Wraps values given row by row from the top left
+    pub fn new(
+        width: usize,
+        height: usize,
+        projection: Projection,
+        values: Vec<T>,
+    ) -> Result<Self, ImageError> {
+        if width == 0 || height == 0 {
+            return Err(ImageError::Malformed("the image is empty".to_string()));
+        }
+        if width.checked_mul(height) != Some(values.len()) {
+            return Err(ImageError::Malformed(format!(
+                "a {}x{} image doesn't have {} values",
+                width,
+                height,
+                values.len()
+            )));
+        }
+        if projection == Projection::CubeMap && width != height * 6 {
+            return Err(ImageError::Malformed(format!(
+                "a cube map should be six square faces side by side, but it's {}x{}",
+                width, height
+            )));
+        }
+
+        Ok(SphereImage {
+            width,
+            height,
+            projection,
+            values,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn projection(&self) -> Projection {
+        self.projection
+    }
+
+    /// The value at a pixel, counting from the top left
+    pub fn get(&self, x: usize, y: usize) -> T {
+        self.values[y * self.width + x]
+    }
+
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    /// The value of the pixel closest to where `point_normal` points, e.g. for indices which can't be blended
+    pub fn nearest(&self, point_normal: &SPVec3) -> T {
+        let (x, y, columns) = self.pixel_position(point_normal);
+        self.get(columns.column(x.round()), y.round() as usize)
+    }
+
+    /// Where `point_normal` lands in pixel coordinates, and the columns it can blend across
+    ///
+    /// Pixel `(x, y)` is at `(x as f64, y as f64)`. Cube map positions are clamped to their face, and equirectangular
+    /// ones run from `-0.5` to `width - 0.5`, half a pixel either side of the seam
+    fn pixel_position(&self, point_normal: &SPVec3) -> (f64, f64, Columns) {
+        let p = point_normal;
+        let length = (p.x * p.x + p.y * p.y + p.z * p.z).sqrt();
+        let length = if length > 0.0 { length } else { 1.0 };
+
+        match self.projection {
+            Projection::Equirectangular => {
+                let latitude = (p.y / length).clamp(-1.0, 1.0).asin();
+                let longitude = p.x.atan2(p.z);
+
+                let x = (longitude + PI) / (2.0 * PI) * self.width as f64 - 0.5;
+                let y = (FRAC_PI_2 - latitude) / PI * (self.height - 1) as f64;
+                (x, y, Columns::Wrap(self.width))
+            }
+            Projection::CubeMap => {
+                let (face, s, t) = cube_face(p);
+                let size = self.height;
+                let first = face * size;
+
+                let x = (s * size as f64 - 0.5).clamp(0.0, (size - 1) as f64);
+                let y = (t * size as f64 - 0.5).clamp(0.0, (size - 1) as f64);
+                (first as f64 + x, y, Columns::Clamp(first, first + size - 1))
+            }
+        }
+    }
+}
+
+impl SphereImage<f64> {
+    /// Blends the four pixels around where `point_normal` points
+    pub fn bilinear(&self, point_normal: &SPVec3) -> f64 {
+        let (x, y, columns) = self.pixel_position(point_normal);
+
+        let left = x.floor();
+        let x0 = columns.column(left);
+        let x1 = columns.column(left + 1.0);
+        let y0 = (y.floor() as usize).min(self.height - 1);
+        let y1 = (y0 + 1).min(self.height - 1);
+        let (tx, ty) = (x - left, y - y0 as f64);
+
+        let top = self.get(x0, y0) * (1.0 - tx) + self.get(x1, y0) * tx;
+        let bottom = self.get(x0, y1) * (1.0 - tx) + self.get(x1, y1) * tx;
+        top * (1.0 - ty) + bottom * ty
+    }
+
+    /// Loads a grayscale image, with black as `0` and white as `1`
+    ///
+    /// Reads `.pgm` files, and `.png` files with the `png` feature. Color PNGs are read by their first channel, so
+    /// heightmaps are best saved as grayscale, ideally with 16 bits per pixel
+    pub fn load_grayscale(
+        path: impl AsRef<Path>,
+        projection: Projection,
+    ) -> Result<Self, ImageError> {
+        let raw = RawImage::load(path.as_ref(), false)?;
+        let max = raw.max_value as f64;
+        let values = raw.values.iter().map(|&value| value as f64 / max).collect();
+
+        SphereImage::new(raw.width, raw.height, projection, values)
+    }
+}
+
+impl SphereImage<u8> {
+    /// Loads an image of color indices
+    ///
+    /// Reads 8 bit `.pgm` files, where the gray level is the index, and with the `png` feature, indexed `.png` files
+    /// where it's the palette entry
+    pub fn load_indexed(
+        path: impl AsRef<Path>,
+        projection: Projection,
+    ) -> Result<Self, ImageError> {
+        let raw = RawImage::load(path.as_ref(), true)?;
+        if raw.max_value > 255 {
+            return Err(ImageError::Unsupported(
+                "index images need 8 bits per pixel or fewer".to_string(),
+            ));
+        }
+        let values = raw.values.iter().map(|&value| value as u8).collect();
+
+        SphereImage::new(raw.width, raw.height, projection, values)
+    }
+}
+
+/// The columns a position can be sampled from
+#[derive(Debug, Clone, Copy)]
+enum Columns {
+    /// All of an equirectangular image's columns, where the one past the right edge is the left one again
+    Wrap(usize),
+
+    /// A cube face's first and last columns, which it doesn't blend past
+    Clamp(usize, usize),
+}
+
+impl Columns {
+    /// The column a whole-number x position lands on
+    fn column(self, x: f64) -> usize {
+        match self {
+            Columns::Wrap(width) => (x as isize).rem_euclid(width as isize) as usize,
+            Columns::Clamp(first, last) => (x.max(0.0) as usize).clamp(first, last),
+        }
+    }
+}
+
+/// Which cube face a direction points at, and where on that face in `[0, 1]`, from the face's top left
+fn cube_face(p: &SPVec3) -> (usize, f64, f64) {
+    let (ax, ay, az) = (p.x.abs(), p.y.abs(), p.z.abs());
+
+    let (face, sc, tc, major) = if ax >= ay && ax >= az {
+        if p.x >= 0.0 {
+            (0, -p.z, -p.y, ax)
+        } else {
+            (1, p.z, -p.y, ax)
+        }
+    } else if ay >= az {
+        if p.y >= 0.0 {
+            (2, p.x, p.z, ay)
+        } else {
+            (3, p.x, -p.z, ay)
+        }
+    } else if p.z >= 0.0 {
+        (4, p.x, -p.y, az)
+    } else {
+        (5, -p.x, -p.y, az)
+    };
+
+    if major == 0.0 {
+        return (4, 0.5, 0.5);
+    }
+
+    (face, (sc / major + 1.0) / 2.0, (tc / major + 1.0) / 2.0)
+}
+
+/// Pixels as read from a file, before they're scaled
+struct RawImage {
+    width: usize,
+    height: usize,
+    max_value: u32,
+    values: Vec<u32>,
+}
+
+impl RawImage {
+    fn load(path: &Path, indexed: bool) -> Result<Self, ImageError> {
+        let bytes = fs::read(path).map_err(ImageError::Io)?;
+
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("pgm") => RawImage::from_pgm(&bytes),
+            Some("png") => RawImage::from_png(&bytes, indexed),
+            _ => Err(ImageError::Unsupported(format!(
+                "{} should be a .pgm or .png file",
+                path.display()
+            ))),
+        }
+    }
+
+    /// Reads a plain (`P2`) or binary (`P5`) PGM
+    fn from_pgm(bytes: &[u8]) -> Result<Self, ImageError> {
+        let mut position = 0;
+        let mut header = Vec::new();
+
+        while header.len() < 4 {
+            while position < bytes.len() && bytes[position].is_ascii_whitespace() {
+                position += 1;
+            }
+            if position < bytes.len() && bytes[position] == b'#' {
+                while position < bytes.len() && bytes[position] != b'\n' {
+                    position += 1;
+                }
+                continue;
+            }
+
+            let start = position;
+            while position < bytes.len() && !bytes[position].is_ascii_whitespace() {
+                position += 1;
+            }
+            if start == position {
+                return Err(ImageError::Malformed(
+                    "the PGM header ends early".to_string(),
+                ));
+            }
+            header.push(String::from_utf8_lossy(&bytes[start..position]).into_owned());
+        }
+
+        let number = |i: usize| {
+            header[i].parse::<usize>().map_err(|_| {
+                ImageError::Malformed(format!("bad number {} in the PGM header", header[i]))
+            })
+        };
+        let (width, height, max_value) = (number(1)?, number(2)?, number(3)?);
+        if max_value == 0 || max_value > 65535 {
+            return Err(ImageError::Malformed(format!(
+                "a PGM's maximum value should be between 1 and 65535, not {}",
+                max_value
+            )));
+        }
+        let count = width.checked_mul(height).ok_or_else(|| {
+            ImageError::Malformed(format!("a {}x{} PGM is too large", width, height))
+        })?;
+
+        let values = match header[0].as_str() {
+            "P5" => {
+                // Exactly one whitespace character separates the header from the pixels
+                let data = bytes.get(position + 1..).unwrap_or(&[]);
+                let bytes_per_value = if max_value > 255 { 2 } else { 1 };
+                if data.len() / bytes_per_value < count {
+                    return Err(ImageError::Malformed(
+                        "the PGM's pixels end early".to_string(),
+                    ));
+                }
+
+                if bytes_per_value == 2 {
+                    data.chunks_exact(2)
+                        .take(count)
+                        .map(|pair| u16::from_be_bytes([pair[0], pair[1]]) as u32)
+                        .collect()
+                } else {
+                    data[..count].iter().map(|&value| value as u32).collect()
+                }
+            }
+            "P2" => {
+                let values = String::from_utf8_lossy(&bytes[position..])
+                    .split_ascii_whitespace()
+                    .take(count)
+                    .map(|value| {
+                        value.parse::<u32>().map_err(|_| {
+                            ImageError::Malformed(format!("bad pixel value {}", value))
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                if values.len() < count {
+                    return Err(ImageError::Malformed(
+                        "the PGM's pixels end early".to_string(),
+                    ));
+                }
+                values
+            }
+            magic => {
+                return Err(ImageError::Unsupported(format!(
+                    "{} files aren't grayscale PGMs, which start with P2 or P5",
+                    magic
+                )))
+            }
+        };
+
+        Ok(RawImage {
+            width,
+            height,
+            max_value: max_value as u32,
+            values: values
+                .into_iter()
+                .map(|value| value.min(max_value as u32))
+                .collect(),
+        })
+    }
+
+    #[cfg(feature = "png")]
+    fn from_png(bytes: &[u8], indexed: bool) -> Result<Self, ImageError> {
+        let mut decoder = png::Decoder::new(bytes);
+        // Keep palette indices and 16 bit samples as they are
+        decoder.set_transformations(png::Transformations::IDENTITY);
+
+        let mut reader = decoder.read_info().map_err(png_error)?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let frame = reader.next_frame(&mut buffer).map_err(png_error)?;
+
+        let (width, height) = (frame.width as usize, frame.height as usize);
+        let bits = frame.bit_depth as u8 as usize;
+        let channels = frame.color_type.samples();
+        if indexed
+            && !matches!(
+                frame.color_type,
+                png::ColorType::Indexed | png::ColorType::Grayscale
+            )
+        {
+            return Err(ImageError::Unsupported(
+                "index images should be indexed or grayscale PNGs".to_string(),
+            ));
+        }
+        if !indexed && frame.color_type == png::ColorType::Indexed {
+            return Err(ImageError::Unsupported(
+                "grayscale images can't be indexed PNGs".to_string(),
+            ));
+        }
+
+        let mut values = Vec::with_capacity(width * height);
+        for row in buffer[..frame.buffer_size()]
+            .chunks(frame.line_size)
+            .take(height)
+        {
+            for x in 0..width {
+                // The first channel of each pixel
+                let bit = x * channels * bits;
+                let value = match bits {
+                    16 => u16::from_be_bytes([row[bit / 8], row[bit / 8 + 1]]) as u32,
+                    8 => row[bit / 8] as u32,
+                    _ => ((row[bit / 8] >> (8 - bits - bit % 8)) & ((1 << bits) - 1)) as u32,
+                };
+                values.push(value);
+            }
+        }
+
+        let max_value = if frame.color_type == png::ColorType::Indexed {
+            255
+        } else {
+            (1 << bits) - 1
+        };
+
+        Ok(RawImage {
+            width,
+            height,
+            max_value,
+            values,
+        })
+    }
+
+    #[cfg(not(feature = "png"))]
+    fn from_png(_bytes: &[u8], _indexed: bool) -> Result<Self, ImageError> {
+        Err(ImageError::Unsupported(
+            "reading PNGs needs the png feature, use a .pgm instead".to_string(),
+        ))
+    }
+}
+
+#[cfg(feature = "png")]
+fn png_error(error: png::DecodingError) -> ImageError {
+    match error {
+        png::DecodingError::IoError(error) => ImageError::Io(error),
+        error => ImageError::Malformed(error.to_string()),
+    }
+}
+
+/// Why an image couldn't be loaded
+#[derive(Debug)]
+pub enum ImageError {
+    Io(io::Error),
+
+    /// The file is a kind of image this crate can't read
+    Unsupported(String),
+
+    /// The file is damaged, or has the wrong size for its projection
+    Malformed(String),
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImageError::Io(error) => write!(f, "Could not read the image: {}", error),
+            ImageError::Unsupported(message) => write!(f, "Unsupported image: {}", message),
+            ImageError::Malformed(message) => write!(f, "Malformed image: {}", message),
+        }
+    }
+}
+
+impl Error for ImageError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ImageError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_vec_near;
+    use crate::sp::noise::preview::lat_long_at;
+    use crate::sp::planet::LatLong;
+
+    fn unit(latitude_degrees: f64, longitude_degrees: f64) -> SPVec3 {
+        let p = LatLong::from_degrees(latitude_degrees, longitude_degrees);
+        let (sin_lat, cos_lat) = p.latitude.sin_cos();
+        let (sin_long, cos_long) = p.longitude.sin_cos();
+        SPVec3 {
+            x: cos_lat * sin_long,
+            y: sin_lat,
+            z: cos_lat * cos_long,
+        }
+    }
+
+    fn gradient(width: usize, height: usize) -> SphereImage<f64> {
+        SphereImage::new(
+            width,
+            height,
+            Projection::Equirectangular,
+            (0..width * height).map(|i| (i % width) as f64).collect(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn checks_the_size() {
+        assert!(SphereImage::new(2, 2, Projection::Equirectangular, vec![0.0; 3]).is_err());
+        assert!(SphereImage::new(0, 0, Projection::Equirectangular, Vec::<f64>::new()).is_err());
+        assert!(SphereImage::new(8, 2, Projection::CubeMap, vec![0.0; 16]).is_err());
+        assert!(SphereImage::new(12, 2, Projection::CubeMap, vec![0.0; 24]).is_ok());
+    }
+
+    #[test]
+    fn equirectangular_matches_the_preview_layout() {
+        let (width, height) = (9, 5);
+        let image = SphereImage::new(
+            width,
+            height,
+            Projection::Equirectangular,
+            (0..width * height).map(|i| i as f64).collect(),
+        )
+        .unwrap();
+
+        // Skip the poles, where longitude is meaningless
+        for y in 1..height - 1 {
+            for x in 0..width {
+                let p = lat_long_at(x, y, width, height);
+                let normal = unit(p.latitude_degrees(), p.longitude_degrees());
+
+                assert_vec_near!(
+                    image.bilinear(&normal),
+                    (y * width + x) as f64,
+                    epsilon = 1e-6
+                );
+                assert_eq!(image.nearest(&normal), (y * width + x) as f64);
+            }
+        }
+    }
+
+    #[test]
+    fn bilinear_blends_between_pixels() {
+        let image = gradient(9, 5);
+
+        // Halfway between the columns centered on -120 and -80 degrees
+        assert_vec_near!(image.bilinear(&unit(0.0, -100.0)), 1.5, epsilon = 1e-9);
+        assert_vec_near!(image.bilinear(&unit(0.0, 0.0)), 4.0, epsilon = 1e-9);
+        assert_eq!(image.nearest(&unit(0.0, -85.0)), 2.0);
+    }
+
+    #[test]
+    fn equirectangular_wraps_at_the_seam() {
+        let image = gradient(9, 5);
+
+        // The seam is halfway between the right column and the left one
+        assert_vec_near!(image.bilinear(&unit(0.0, 180.0)), 4.0, epsilon = 1e-9);
+        assert_vec_near!(image.bilinear(&unit(0.0, -170.0)), 2.0, epsilon = 1e-9);
+        assert_eq!(image.nearest(&unit(0.0, 175.0)), 8.0);
+        assert_eq!(image.nearest(&unit(0.0, -175.0)), 0.0);
+    }
+
+    #[test]
+    fn cube_faces_cover_each_axis() {
+        let image = SphereImage::new(
+            12,
+            2,
+            Projection::CubeMap,
+            (0..24).map(|i| (i % 12 / 2) as f64).collect(),
+        )
+        .unwrap();
+        let axes = [
+            SPVec3 {
+                x: 1.0,
+                y: 0.0,
+                z: 0.0,
+            },
+            SPVec3 {
+                x: -1.0,
+                y: 0.0,
+                z: 0.0,
+            },
+            SPVec3 {
+                x: 0.0,
+                y: 1.0,
+                z: 0.0,
+            },
+            SPVec3 {
+                x: 0.0,
+                y: -1.0,
+                z: 0.0,
+            },
+            SPVec3 {
+                x: 0.0,
+                y: 0.0,
+                z: 1.0,
+            },
+            SPVec3 {
+                x: 0.0,
+                y: 0.0,
+                z: -1.0,
+            },
+        ];
+
+        for (face, axis) in axes.iter().enumerate() {
+            assert_eq!(image.bilinear(axis), face as f64);
+            assert_eq!(image.nearest(axis), face as f64);
+        }
+    }
+
+    #[test]
+    fn cube_faces_are_oriented_like_opengl() {
+        // +Z face: +X is to the right and +Y is up
+        assert_eq!(
+            cube_face(&SPVec3 {
+                x: 0.5,
+                y: 0.5,
+                z: 1.0
+            }),
+            (4, 0.75, 0.25)
+        );
+        // +Y face: +X is to the right and +Z is down
+        assert_eq!(
+            cube_face(&SPVec3 {
+                x: 0.5,
+                y: 1.0,
+                z: 0.5
+            }),
+            (2, 0.75, 0.75)
+        );
+    }
+
+    #[test]
+    fn reads_binary_and_plain_pgm() {
+        let mut binary = b"P5\n# made by hand\n3 2\n255\n".to_vec();
+        binary.extend_from_slice(&[0, 51, 255, 102, 153, 204]);
+        let raw = RawImage::from_pgm(&binary).unwrap();
+        assert_eq!((raw.width, raw.height, raw.max_value), (3, 2, 255));
+        assert_eq!(raw.values, [0, 51, 255, 102, 153, 204]);
+
+        let mut wide = b"P5 2 1 65535\n".to_vec();
+        wide.extend_from_slice(&[0x12, 0x34, 0xff, 0xff]);
+        assert_eq!(RawImage::from_pgm(&wide).unwrap().values, [0x1234, 0xffff]);
+
+        let plain = RawImage::from_pgm(b"P2\n2 2\n15\n0 5\n10 15\n").unwrap();
+        assert_eq!(plain.values, [0, 5, 10, 15]);
+
+        assert!(RawImage::from_pgm(b"P5\n2 2\n255\n\x01").is_err());
+        assert!(matches!(
+            RawImage::from_pgm(b"P5\n4294967296 4294967296\n255\n\x01"),
+            Err(ImageError::Malformed(_))
+        ));
+        assert!(matches!(
+            RawImage::from_pgm(b"P6\n1 1\n255\n\x01\x02\x03"),
+            Err(ImageError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn loads_grayscale_and_indexed_files() {
+        let path = std::env::temp_dir().join(format!(
+            "sapiens-rs-sphere-image-{}.pgm",
+            std::process::id()
+        ));
+        let mut bytes = b"P5 4 2 255\n".to_vec();
+        bytes.extend_from_slice(&[0, 255, 0, 255, 3, 3, 3, 3]);
+        fs::write(&path, &bytes).unwrap();
+
+        let grayscale = SphereImage::load_grayscale(&path, Projection::Equirectangular);
+        let indexed = SphereImage::load_indexed(&path, Projection::Equirectangular);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(grayscale.unwrap().get(1, 0), 1.0);
+        assert_eq!(indexed.unwrap().get(2, 1), 3);
+        assert!(matches!(
+            SphereImage::load_grayscale("heights.tiff", Projection::Equirectangular),
+            Err(ImageError::Io(_))
+        ));
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn reads_png() {
+        let encode = |color: png::ColorType, depth: png::BitDepth, palette: bool, data: &[u8]| {
+            let mut bytes = Vec::new();
+            {
+                let mut encoder = png::Encoder::new(&mut bytes, 2, 2);
+                encoder.set_color(color);
+                encoder.set_depth(depth);
+                if palette {
+                    encoder.set_palette(vec![0u8; 3 * 4]);
+                }
+                let mut writer = encoder.write_header().unwrap();
+                writer.write_image_data(data).unwrap();
+            }
+            bytes
+        };
+
+        let gray16 = encode(
+            png::ColorType::Grayscale,
+            png::BitDepth::Sixteen,
+            false,
+            &[0, 0, 0x80, 0, 0xff, 0xff, 0x40, 0],
+        );
+        let raw = RawImage::from_png(&gray16, false).unwrap();
+        assert_eq!(raw.max_value, 65535);
+        assert_eq!(raw.values, [0, 0x8000, 0xffff, 0x4000]);
+
+        // Two bit palette indices, with each row padded to a byte
+        let indexed = encode(
+            png::ColorType::Indexed,
+            png::BitDepth::Two,
+            true,
+            &[0b0111_0000, 0b1011_0000],
+        );
+        let raw = RawImage::from_png(&indexed, true).unwrap();
+        assert_eq!(raw.values, [1, 3, 2, 3]);
+
+        let rgb = encode(png::ColorType::Rgb, png::BitDepth::Eight, false, &[9; 12]);
+        assert_eq!(RawImage::from_png(&rgb, false).unwrap().values, [9; 4]);
+        assert!(RawImage::from_png(&rgb, true).is_err());
+    }
+}
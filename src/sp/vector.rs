@@ -0,0 +1,479 @@
+//! Traits which unify the different ways a mod can represent a vector
+//!
+//! Mods usually end up with a mix of raw `SPVec3`s from Sapiens, `Vec3`s from `sp::math`, and plain tuples or arrays.
+//! Writing helpers against these traits lets the helper accept whichever representation the call site already has
+
+use crate::sp;
+use crate::sp::math::{Vec2, Vec3, Vec4};
+use sapiens_sys::{SPVec2, SPVec3, SPVec4};
+
+/// Anything that can be viewed as a two-component vector
+pub trait Vector2Like: Sized {
+    fn x(&self) -> f64;
+
+    fn y(&self) -> f64;
+
+    /// Constructs this vector type from its components
+    fn from_xy(x: f64, y: f64) -> Self;
+
+    fn to_sp_vec2(&self) -> SPVec2 {
+        SPVec2 {
+            x: self.x(),
+            y: self.y(),
+        }
+    }
+
+    fn from_sp_vec2(v: SPVec2) -> Self {
+        Self::from_xy(v.x, v.y)
+    }
+
+    /// Converts this vector into any other two-component vector type
+    fn convert<V: Vector2Like>(&self) -> V {
+        V::from_xy(self.x(), self.y())
+    }
+
+    /// Adds `other` to this vector
+    fn plus<V: Vector2Like>(&self, other: &V) -> Self {
+        Self::from_sp_vec2(sp::vec2_add(&self.to_sp_vec2(), &other.to_sp_vec2()))
+    }
+
+    /// Subtracts `other` from this vector
+    fn minus<V: Vector2Like>(&self, other: &V) -> Self {
+        Self::from_sp_vec2(sp::vec2_sub(&self.to_sp_vec2(), &other.to_sp_vec2()))
+    }
+
+    /// Multiplies every component of this vector by `scale`
+    fn scaled(&self, scale: f64) -> Self {
+        Self::from_sp_vec2(sp::vec2_mul(&self.to_sp_vec2(), scale))
+    }
+}
+
+/// Anything that can be viewed as a three-component vector
+pub trait Vector3Like: Sized {
+    fn x(&self) -> f64;
+
+    fn y(&self) -> f64;
+
+    fn z(&self) -> f64;
+
+    /// Constructs this vector type from its components
+    fn from_xyz(x: f64, y: f64, z: f64) -> Self;
+
+    fn to_sp_vec3(&self) -> SPVec3 {
+        SPVec3 {
+            x: self.x(),
+            y: self.y(),
+            z: self.z(),
+        }
+    }
+
+    fn from_sp_vec3(v: SPVec3) -> Self {
+        Self::from_xyz(v.x, v.y, v.z)
+    }
+
+    /// Converts this vector into any other three-component vector type
+    fn convert<V: Vector3Like>(&self) -> V {
+        V::from_xyz(self.x(), self.y(), self.z())
+    }
+
+    /// Adds `other` to this vector
+    fn plus<V: Vector3Like>(&self, other: &V) -> Self {
+        Self::from_sp_vec3(sp::vec3_add(&self.to_sp_vec3(), &other.to_sp_vec3()))
+    }
+
+    /// Subtracts `other` from this vector
+    fn minus<V: Vector3Like>(&self, other: &V) -> Self {
+        Self::from_sp_vec3(sp::vec3_sub(&self.to_sp_vec3(), &other.to_sp_vec3()))
+    }
+
+    /// Multiplies every component of this vector by `scale`
+    fn scaled(&self, scale: f64) -> Self {
+        Self::from_sp_vec3(sp::vec3_mul(&self.to_sp_vec3(), scale))
+    }
+
+    /// Calculates the dot product of this vector and `other`
+    fn dot<V: Vector3Like>(&self, other: &V) -> f64 {
+        sp::vec3_dot(&self.to_sp_vec3(), &other.to_sp_vec3())
+    }
+
+    /// Calculates the cross product of this vector and `other`
+    fn cross<V: Vector3Like>(&self, other: &V) -> Self {
+        Self::from_sp_vec3(sp::vec3_cross(&self.to_sp_vec3(), &other.to_sp_vec3()))
+    }
+
+    /// Calculates the length of this vector
+    fn length(&self) -> f64 {
+        sp::vec3_length(&self.to_sp_vec3())
+    }
+
+    /// Calculates the squared length of this vector
+    ///
+    /// This method avoids a square root and is thus faster in many algorithms
+    fn length2(&self) -> f64 {
+        sp::vec3_length2(&self.to_sp_vec3())
+    }
+
+    /// Calculates the distance between this point and `other`
+    fn distance<V: Vector3Like>(&self, other: &V) -> f64 {
+        sp::vec3_distance(&self.to_sp_vec3(), &other.to_sp_vec3())
+    }
+
+    /// Calculates the squared distance between this point and `other`
+    ///
+    /// This method avoids a square root and is thus faster in many algorithms
+    fn distance2<V: Vector3Like>(&self, other: &V) -> f64 {
+        sp::vec3_distance2(&self.to_sp_vec3(), &other.to_sp_vec3())
+    }
+
+    /// Returns a vector with the same direction as this one and a length of one
+    fn normalized(&self) -> Self {
+        Self::from_sp_vec3(sp::vec3_normalize(&self.to_sp_vec3()))
+    }
+}
+
+/// Anything that can be viewed as a four-component vector
+pub trait Vector4Like: Sized {
+    fn x(&self) -> f64;
+
+    fn y(&self) -> f64;
+
+    fn z(&self) -> f64;
+
+    fn w(&self) -> f64;
+
+    /// Constructs this vector type from its components
+    fn from_xyzw(x: f64, y: f64, z: f64, w: f64) -> Self;
+
+    fn to_sp_vec4(&self) -> SPVec4 {
+        SPVec4 {
+            x: self.x(),
+            y: self.y(),
+            z: self.z(),
+            w: self.w(),
+        }
+    }
+
+    fn from_sp_vec4(v: SPVec4) -> Self {
+        Self::from_xyzw(v.x, v.y, v.z, v.w)
+    }
+
+    /// Converts this vector into any other four-component vector type
+    fn convert<V: Vector4Like>(&self) -> V {
+        V::from_xyzw(self.x(), self.y(), self.z(), self.w())
+    }
+
+    /// Adds `other` to this vector
+    fn plus<V: Vector4Like>(&self, other: &V) -> Self {
+        Self::from_sp_vec4(sp::vec4_add(&self.to_sp_vec4(), &other.to_sp_vec4()))
+    }
+
+    /// Subtracts `other` from this vector
+    fn minus<V: Vector4Like>(&self, other: &V) -> Self {
+        Self::from_sp_vec4(sp::vec4_sub(&self.to_sp_vec4(), &other.to_sp_vec4()))
+    }
+
+    /// Multiplies every component of this vector by `scale`
+    fn scaled(&self, scale: f64) -> Self {
+        Self::from_sp_vec4(sp::vec4_mul(&self.to_sp_vec4(), scale))
+    }
+}
+
+impl Vector2Like for SPVec2 {
+    fn x(&self) -> f64 {
+        self.x
+    }
+
+    fn y(&self) -> f64 {
+        self.y
+    }
+
+    fn from_xy(x: f64, y: f64) -> Self {
+        SPVec2 { x, y }
+    }
+}
+
+impl Vector2Like for Vec2 {
+    fn x(&self) -> f64 {
+        self.as_sp_vec().x
+    }
+
+    fn y(&self) -> f64 {
+        self.as_sp_vec().y
+    }
+
+    fn from_xy(x: f64, y: f64) -> Self {
+        Vec2::new(x, y)
+    }
+}
+
+impl Vector2Like for (f64, f64) {
+    fn x(&self) -> f64 {
+        self.0
+    }
+
+    fn y(&self) -> f64 {
+        self.1
+    }
+
+    fn from_xy(x: f64, y: f64) -> Self {
+        (x, y)
+    }
+}
+
+impl Vector2Like for [f64; 2] {
+    fn x(&self) -> f64 {
+        self[0]
+    }
+
+    fn y(&self) -> f64 {
+        self[1]
+    }
+
+    fn from_xy(x: f64, y: f64) -> Self {
+        [x, y]
+    }
+}
+
+impl Vector3Like for SPVec3 {
+    fn x(&self) -> f64 {
+        self.x
+    }
+
+    fn y(&self) -> f64 {
+        self.y
+    }
+
+    fn z(&self) -> f64 {
+        self.z
+    }
+
+    fn from_xyz(x: f64, y: f64, z: f64) -> Self {
+        SPVec3 { x, y, z }
+    }
+
+    fn to_sp_vec3(&self) -> SPVec3 {
+        *self
+    }
+
+    fn from_sp_vec3(v: SPVec3) -> Self {
+        v
+    }
+}
+
+impl Vector3Like for Vec3 {
+    fn x(&self) -> f64 {
+        self.as_sp_vec().x
+    }
+
+    fn y(&self) -> f64 {
+        self.as_sp_vec().y
+    }
+
+    fn z(&self) -> f64 {
+        self.as_sp_vec().z
+    }
+
+    fn from_xyz(x: f64, y: f64, z: f64) -> Self {
+        Vec3::new(x, y, z)
+    }
+
+    fn to_sp_vec3(&self) -> SPVec3 {
+        self.as_sp_vec()
+    }
+
+    fn from_sp_vec3(v: SPVec3) -> Self {
+        Vec3::from(v)
+    }
+}
+
+impl Vector3Like for (f64, f64, f64) {
+    fn x(&self) -> f64 {
+        self.0
+    }
+
+    fn y(&self) -> f64 {
+        self.1
+    }
+
+    fn z(&self) -> f64 {
+        self.2
+    }
+
+    fn from_xyz(x: f64, y: f64, z: f64) -> Self {
+        (x, y, z)
+    }
+}
+
+impl Vector3Like for [f64; 3] {
+    fn x(&self) -> f64 {
+        self[0]
+    }
+
+    fn y(&self) -> f64 {
+        self[1]
+    }
+
+    fn z(&self) -> f64 {
+        self[2]
+    }
+
+    fn from_xyz(x: f64, y: f64, z: f64) -> Self {
+        [x, y, z]
+    }
+}
+
+impl Vector4Like for SPVec4 {
+    fn x(&self) -> f64 {
+        self.x
+    }
+
+    fn y(&self) -> f64 {
+        self.y
+    }
+
+    fn z(&self) -> f64 {
+        self.z
+    }
+
+    fn w(&self) -> f64 {
+        self.w
+    }
+
+    fn from_xyzw(x: f64, y: f64, z: f64, w: f64) -> Self {
+        SPVec4 { x, y, z, w }
+    }
+}
+
+impl Vector4Like for Vec4 {
+    fn x(&self) -> f64 {
+        self.as_sp_vec().x
+    }
+
+    fn y(&self) -> f64 {
+        self.as_sp_vec().y
+    }
+
+    fn z(&self) -> f64 {
+        self.as_sp_vec().z
+    }
+
+    fn w(&self) -> f64 {
+        self.as_sp_vec().w
+    }
+
+    fn from_xyzw(x: f64, y: f64, z: f64, w: f64) -> Self {
+        Vec4::new(x, y, z, w)
+    }
+}
+
+impl Vector4Like for (f64, f64, f64, f64) {
+    fn x(&self) -> f64 {
+        self.0
+    }
+
+    fn y(&self) -> f64 {
+        self.1
+    }
+
+    fn z(&self) -> f64 {
+        self.2
+    }
+
+    fn w(&self) -> f64 {
+        self.3
+    }
+
+    fn from_xyzw(x: f64, y: f64, z: f64, w: f64) -> Self {
+        (x, y, z, w)
+    }
+}
+
+impl Vector4Like for [f64; 4] {
+    fn x(&self) -> f64 {
+        self[0]
+    }
+
+    fn y(&self) -> f64 {
+        self[1]
+    }
+
+    fn z(&self) -> f64 {
+        self[2]
+    }
+
+    fn w(&self) -> f64 {
+        self[3]
+    }
+
+    fn from_xyzw(x: f64, y: f64, z: f64, w: f64) -> Self {
+        [x, y, z, w]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A helper written once that should accept every representation
+    fn height_above_origin<V: Vector3Like>(pos: &V) -> f64 {
+        pos.length()
+    }
+
+    #[test]
+    fn generic_helper_accepts_every_representation() {
+        let sp_vec = SPVec3 {
+            x: 3.0,
+            y: 4.0,
+            z: 0.0,
+        };
+        let math_vec = Vec3::new(3.0, 4.0, 0.0);
+        let tuple = (3.0, 4.0, 0.0);
+        let array = [3.0, 4.0, 0.0];
+
+        assert_eq!(height_above_origin(&sp_vec), 5.0);
+        assert_eq!(height_above_origin(&math_vec), 5.0);
+        assert_eq!(height_above_origin(&tuple), 5.0);
+        assert_eq!(height_above_origin(&array), 5.0);
+    }
+
+    #[test]
+    fn convert_between_representations() {
+        let tuple = (1.0, 2.0, 3.0);
+
+        let sp_vec: SPVec3 = tuple.convert();
+        let array: [f64; 3] = sp_vec.convert();
+
+        assert_eq!(
+            sp_vec,
+            SPVec3 {
+                x: 1.0,
+                y: 2.0,
+                z: 3.0
+            }
+        );
+        assert_eq!(array, [1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn mixed_representation_arithmetic() {
+        let a = [1.0, 0.0, 0.0];
+        let b = SPVec3 {
+            x: 0.0,
+            y: 1.0,
+            z: 0.0,
+        };
+
+        assert_eq!(a.cross(&b), [0.0, 0.0, 1.0]);
+        assert_eq!(a.plus(&b), [1.0, 1.0, 0.0]);
+        assert_eq!(a.dot(&b), 0.0);
+    }
+
+    #[test]
+    fn vec2_and_vec4_conversions() {
+        let vec2: SPVec2 = (1.0, 2.0).convert();
+        let vec4: (f64, f64, f64, f64) = Vec4::new(1.0, 2.0, 3.0, 4.0).convert();
+
+        assert_eq!(vec2, SPVec2 { x: 1.0, y: 2.0 });
+        assert_eq!(vec4, (1.0, 2.0, 3.0, 4.0));
+    }
+}
@@ -0,0 +1,308 @@
+//! Numbers describing what a mod's hooks generate, to check a change doesn't reshape the world by accident
+//!
+//! ```ignore
+//! let sampler = Sampler::new(my_hooks, WorldGenOptions::vanilla(), 1234);
+//! let report = analyze(&sampler, 20_000);
+//!
+//! assert!((28.0..=32.0).contains(&report.land_percent()), "{}", report);
+//! ```
+
+use crate::sp::noise::preview::Stats;
+use crate::sp::worldgen::{PointSample, Sampler};
+use sapiens_sys::SPVec3;
+use std::collections::BTreeMap;
+use std::f64::consts::PI;
+use std::fmt;
+use std::os::raw::c_int;
+
+/// How many bins the altitude and steepness histograms have
+pub const HISTOGRAM_BINS: usize = 16;
+
+/// What a world looks like overall, from evenly spread samples
+///
+/// Every sample covers the same area of the planet, so fractions of samples are fractions of its surface
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorldGenReport {
+    pub sample_count: usize,
+
+    /// How much of the surface is at or above sea level, from `0` to `1`
+    pub land_fraction: f64,
+
+    /// Altitude in prerender units, everywhere including under the sea
+    pub altitude: Stats,
+
+    /// Steepness as `PointSample::steepness` measures it, everywhere including under the sea
+    pub steepness: Stats,
+
+    /// The percentage of the surface with each climate type
+    pub climate_coverage: BTreeMap<c_int, f64>,
+
+    /// The percentage of the surface with each biome type
+    pub biome_coverage: BTreeMap<c_int, f64>,
+
+    /// Summer and winter rainfall, without histograms
+    pub rainfall: [Stats; 2],
+
+    /// Summer and winter temperature in degrees Celsius, without histograms
+    pub temperature: [Stats; 2],
+}
+
+impl WorldGenReport {
+    /// Summarizes samples which each cover the same area
+    ///
+    /// # Panics
+    ///
+    /// If there are no samples
+    pub fn from_samples(samples: &[PointSample]) -> Self {
+        assert!(!samples.is_empty(), "a report needs at least one sample");
+
+        let values = |value: fn(&PointSample) -> f64| samples.iter().map(value).collect::<Vec<_>>();
+        let coverage = |category: fn(&PointSample) -> c_int| {
+            let mut counts = BTreeMap::new();
+            for sample in samples {
+                *counts.entry(category(sample)).or_insert(0usize) += 1;
+            }
+            counts
+                .into_iter()
+                .map(|(key, count)| (key, percent(count, samples.len())))
+                .collect()
+        };
+
+        let land = samples.iter().filter(|sample| sample.is_land()).count();
+
+        WorldGenReport {
+            sample_count: samples.len(),
+            land_fraction: land as f64 / samples.len() as f64,
+            altitude: Stats::of(&values(|s| s.altitude), HISTOGRAM_BINS),
+            steepness: Stats::of(&values(|s| s.steepness as f64), HISTOGRAM_BINS),
+            climate_coverage: coverage(|s| s.climate_type),
+            biome_coverage: coverage(|s| s.biome_type),
+            rainfall: [
+                Stats::of(&values(|s| s.rainfall[0]), 0),
+                Stats::of(&values(|s| s.rainfall[1]), 0),
+            ],
+            temperature: [
+                Stats::of(&values(|s| s.temperature[0]), 0),
+                Stats::of(&values(|s| s.temperature[1]), 0),
+            ],
+        }
+    }
+
+    pub fn land_percent(&self) -> f64 {
+        self.land_fraction * 100.0
+    }
+
+    pub fn ocean_percent(&self) -> f64 {
+        100.0 - self.land_percent()
+    }
+
+    /// The percentage of the surface with a climate type, which is `0` for ones that never came up
+    pub fn climate_percent(&self, climate_type: c_int) -> f64 {
+        self.climate_coverage
+            .get(&climate_type)
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    /// The percentage of the surface with a biome type, which is `0` for ones that never came up
+    pub fn biome_percent(&self, biome_type: c_int) -> f64 {
+        self.biome_coverage.get(&biome_type).copied().unwrap_or(0.0)
+    }
+}
+
+fn percent(count: usize, total: usize) -> f64 {
+    count as f64 * 100.0 / total as f64
+}
+
+impl fmt::Display for WorldGenReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} samples", self.sample_count)?;
+        writeln!(
+            f,
+            "land {:.2}%  ocean {:.2}%",
+            self.land_percent(),
+            self.ocean_percent()
+        )?;
+
+        write_histogram(f, "altitude", &self.altitude)?;
+        write_histogram(f, "steepness", &self.steepness)?;
+
+        for (name, coverage) in [
+            ("climate", &self.climate_coverage),
+            ("biome", &self.biome_coverage),
+        ]
+        .iter()
+        {
+            writeln!(f, "{} coverage", name)?;
+            for (category, percent) in coverage.iter() {
+                writeln!(f, "{:>8} {:>6.2}%", category, percent)?;
+            }
+        }
+
+        for (name, stats) in [
+            ("rainfall", &self.rainfall),
+            ("temperature", &self.temperature),
+        ]
+        .iter()
+        {
+            for (season, stats) in ["summer", "winter"].iter().zip(stats.iter()) {
+                writeln!(
+                    f,
+                    "{} {}: min {:.4}  max {:.4}  mean {:.4}",
+                    name, season, stats.min, stats.max, stats.mean
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn write_histogram(f: &mut fmt::Formatter<'_>, name: &str, stats: &Stats) -> fmt::Result {
+    writeln!(
+        f,
+        "{}: min {:.6}  max {:.6}  mean {:.6}",
+        name, stats.min, stats.max, stats.mean
+    )?;
+
+    let total = stats.histogram.iter().sum::<usize>().max(1);
+    for (i, count) in stats.histogram.iter().enumerate() {
+        let low = stats.min + (stats.max - stats.min) * i as f64 / stats.histogram.len() as f64;
+        writeln!(f, "{:>12.6} {:>6.2}%", low, percent(*count, total))?;
+    }
+
+    Ok(())
+}
+
+/// `count` points spread evenly over the unit sphere, along a Fibonacci spiral
+///
+/// Each point covers the same area, and the same count always gives the same points, so reports can be compared
+pub fn uniform_points(count: usize) -> Vec<SPVec3> {
+    let golden_angle = PI * (3.0 - 5.0f64.sqrt());
+
+    (0..count)
+        .map(|i| {
+            // Latitude bands of equal area, with the points in the middle of them
+            let y = 1.0 - (2.0 * i as f64 + 1.0) / count as f64;
+            let radius = (1.0 - y * y).sqrt();
+            let (sin, cos) = (golden_angle * i as f64).sin_cos();
+
+            SPVec3 {
+                x: radius * sin,
+                y,
+                z: radius * cos,
+            }
+        })
+        .collect()
+}
+
+/// Runs the hooks at `sample_count` evenly spread points, and summarizes what they generate
+pub fn analyze(sampler: &Sampler, sample_count: usize) -> WorldGenReport {
+    let samples: Vec<_> = uniform_points(sample_count)
+        .into_iter()
+        .map(|point_normal| sampler.sample(point_normal))
+        .collect();
+
+    WorldGenReport::from_samples(&samples)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_vec_near;
+    use crate::sp::common::{TerrainType, WorldGenOptions};
+    use crate::sp::height;
+    use crate::sp::vector::Vector3Like;
+    use crate::sp::worldgen::WorldGenHooks;
+    use sapiens_sys::{SPNoise, SPVec4, SPWorldGenOptions};
+
+    /// Land wherever the point is more than halfway to the +Y pole
+    unsafe extern "C" fn polar_cap(
+        _noise1: *mut SPNoise,
+        _noise2: *mut SPNoise,
+        point_normal: SPVec3,
+        _noise_loc: SPVec3,
+        _world_gen_options: SPWorldGenOptions,
+        _river_value: f64,
+        _river_distance: f64,
+    ) -> SPVec4 {
+        SPVec4 {
+            x: point_normal.y - 0.5,
+            y: 0.0,
+            z: 0.0,
+            w: 0.0,
+        }
+    }
+
+    fn sample(altitude: f64, climate_type: c_int) -> PointSample {
+        PointSample {
+            point_normal: SPVec3::default(),
+            altitude,
+            steepness: altitude.abs() as f32,
+            rainfall: [altitude * 10.0, 0.0],
+            temperature: [20.0, -5.0],
+            climate_type,
+            biome_type: climate_type * 2,
+            terrain_type: Some(TerrainType::from_id(0)),
+        }
+    }
+
+    #[test]
+    fn uniform_points_cover_the_sphere_evenly() {
+        let points = uniform_points(10_000);
+        assert_eq!(points.len(), 10_000);
+
+        for point in &points {
+            assert_vec_near!(point.length(), 1.0);
+        }
+
+        // Equal area means a quarter of the points lie above y = 0.5, and about a quarter on each side of each axis
+        let above = points.iter().filter(|p| p.y > 0.5).count();
+        let east = points.iter().filter(|p| p.x > 0.0).count();
+        assert!((above as i64 - 2500).abs() <= 1);
+        assert!((east as i64 - 5000).abs() < 50);
+    }
+
+    #[test]
+    fn summarizes_samples() {
+        let report = WorldGenReport::from_samples(&[
+            sample(-1.0, 0),
+            sample(-0.5, 0),
+            sample(0.0, 1),
+            sample(2.0, 2),
+        ]);
+
+        assert_eq!(report.sample_count, 4);
+        assert_vec_near!(report.land_percent(), 50.0);
+        assert_vec_near!(report.ocean_percent(), 50.0);
+        assert_vec_near!(report.altitude.min, -1.0);
+        assert_vec_near!(report.altitude.max, 2.0);
+        assert_eq!(report.altitude.histogram.len(), HISTOGRAM_BINS);
+        assert_eq!(report.steepness.histogram.iter().sum::<usize>(), 4);
+
+        assert_vec_near!(report.climate_percent(0), 50.0);
+        assert_vec_near!(report.climate_percent(2), 25.0);
+        assert_vec_near!(report.climate_percent(7), 0.0);
+        assert_vec_near!(report.biome_percent(4), 25.0);
+
+        assert_vec_near!(report.rainfall[0].max, 20.0);
+        assert_vec_near!(report.temperature[1].mean, -5.0);
+        assert!(report.rainfall[0].histogram.is_empty());
+
+        let text = report.to_string();
+        assert!(text.contains("land 50.00%  ocean 50.00%"));
+        assert!(text.contains("temperature winter"));
+    }
+
+    #[test]
+    fn measures_land_coverage() {
+        let hooks = WorldGenHooks::stand_in()
+            .with_height(unsafe { height::Vanilla::from_sp(Some(polar_cap)) }.unwrap());
+        let sampler = Sampler::new(hooks, WorldGenOptions::vanilla(), 1234);
+
+        let report = analyze(&sampler, 2000);
+
+        assert_eq!(report.sample_count, 2000);
+        assert!((24.0..=26.0).contains(&report.land_percent()), "{}", report);
+    }
+}
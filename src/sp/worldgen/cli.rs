@@ -0,0 +1,266 @@
+//! The `sapiens-preview` command line, which runs a world-gen mod's hooks over the whole planet and writes what they
+//! generate as equirectangular images
+//!
+//! The `sapiens-preview` binary opens mods built as a DLL or shared library. A mod linked in as an rlib gets the same
+//! command line from a binary of its own:
+//!
+//! ```ignore
+//! fn main() {
+//!     let hooks = WorldGenHooks::stand_in()
+//!         .with_height(unsafe { height::Vanilla::from_sp(Some(my_mod::spHeightGet)) }.unwrap());
+//!
+//!     sapiens_rs::sp::worldgen::cli::main(Some(hooks));
+//! }
+//! ```
+
+use crate::sp::common::WorldGenOptions;
+use crate::sp::noise::preview::Image;
+use crate::sp::worldgen::preview::Previews;
+use crate::sp::worldgen::{analyze, Sampler, WorldGenHooks};
+use std::env;
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::process;
+
+/// What `main` prints when the arguments don't make sense
+pub const USAGE: &str = "usage: sapiens-preview [<mod library>] [options]
+
+options:
+    --vanilla                      use the game's hooks from SPCommon where the mod has none (default stand-ins)
+    --seed <n>                     world seed (default 1234)
+    --size <w>x<h>                 image size (default 1024x512)
+    --preset <name>                vanilla, archipelago or arid (default vanilla)
+    --height-offset <v>            world-gen options, applied on top of the preset
+    --rainfall-multiplier <v>
+    --temperature-offset <v>
+    --continent-scale <v>
+    --mountain-scale <v>
+    --hill-scale <v>
+    --continent-influence <v>
+    --mountain-influence <v>
+    --hill-influence <v>
+    --out <dir>                    where to write the images (default .)
+    --pnm                          write PGM/PPM images, even with the png feature
+    --analyze <n>                  also print land coverage, histograms and ranges from n evenly spread samples
+
+writes height, rainfall, temperature, climate, biome and surface images. A preview with its mod linked in takes no
+library, and no --vanilla";
+
+struct Options {
+    library: Option<PathBuf>,
+    vanilla: bool,
+    seed: i32,
+    width: usize,
+    height: usize,
+    world_gen_options: WorldGenOptions,
+    out: PathBuf,
+    png: bool,
+    analyze: Option<usize>,
+}
+
+fn parse_options(args: impl IntoIterator<Item = String>) -> Result<Options, Box<dyn Error>> {
+    let mut args = args.into_iter();
+    let mut options = Options {
+        library: None,
+        vanilla: false,
+        seed: 1234,
+        width: 1024,
+        height: 512,
+        world_gen_options: WorldGenOptions::vanilla(),
+        out: PathBuf::from("."),
+        png: cfg!(feature = "png"),
+        analyze: None,
+    };
+    // Individual options go on top of the preset, wherever they were given
+    let mut overrides: Vec<(String, f64)> = Vec::new();
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+
+        match arg.as_str() {
+            "--vanilla" => options.vanilla = true,
+            "--seed" => options.seed = value()?.parse()?,
+            "--size" => {
+                let size = value()?;
+                let (width, height) = size
+                    .split_once('x')
+                    .ok_or("size should look like 1024x512")?;
+                options.width = width.parse()?;
+                options.height = height.parse()?;
+            }
+            "--preset" => {
+                options.world_gen_options = match value()?.as_str() {
+                    "vanilla" => WorldGenOptions::vanilla(),
+                    "archipelago" => WorldGenOptions::archipelago(),
+                    "arid" => WorldGenOptions::arid(),
+                    other => return Err(format!("unknown preset {}", other).into()),
+                }
+            }
+            "--height-offset"
+            | "--rainfall-multiplier"
+            | "--temperature-offset"
+            | "--continent-scale"
+            | "--mountain-scale"
+            | "--hill-scale"
+            | "--continent-influence"
+            | "--mountain-influence"
+            | "--hill-influence" => {
+                let parsed = value()?.parse()?;
+                overrides.push((arg, parsed));
+            }
+            "--out" => options.out = PathBuf::from(value()?),
+            "--pnm" => options.png = false,
+            "--analyze" => options.analyze = Some(value()?.parse()?),
+            _ if arg.starts_with("--") => return Err(format!("unknown option {}", arg).into()),
+            _ if options.library.is_none() => options.library = Some(PathBuf::from(arg)),
+            _ => return Err(format!("unexpected argument {}", arg).into()),
+        }
+    }
+
+    let world_gen_options = &mut options.world_gen_options;
+    for (name, value) in overrides {
+        match name.as_str() {
+            "--height-offset" => world_gen_options.set_height_offset(value),
+            "--rainfall-multiplier" => world_gen_options.set_rainfall_multiplier(value),
+            "--temperature-offset" => world_gen_options.set_temperature_offset(value),
+            "--continent-scale" => world_gen_options.set_continent_scale(value),
+            "--mountain-scale" => world_gen_options.set_mountain_scale(value),
+            "--hill-scale" => world_gen_options.set_hill_scale(value),
+            "--continent-influence" => world_gen_options.set_continent_influence(value),
+            "--mountain-influence" => world_gen_options.set_mountain_influence(value),
+            _ => world_gen_options.set_hill_influence(value),
+        }
+    }
+    world_gen_options.validate()?;
+    for unusual in world_gen_options.unusual_options() {
+        eprintln!("warning: {}", unusual);
+    }
+
+    if options.width == 0 || options.height == 0 {
+        return Err("images need at least one pixel".into());
+    }
+    if options.analyze == Some(0) {
+        return Err("--analyze needs at least one sample".into());
+    }
+
+    Ok(options)
+}
+
+/// Renders the previews `args` ask for, without the program name
+///
+/// `linked` are the hooks of a mod linked in as an rlib, which replace the stand-ins and the library argument
+pub fn run(
+    linked: Option<WorldGenHooks>,
+    args: impl IntoIterator<Item = String>,
+) -> Result<(), Box<dyn Error>> {
+    let options = parse_options(args)?;
+
+    if linked.is_some() && (options.vanilla || options.library.is_some()) {
+        return Err("the mod is linked in, so there's no library or vanilla to load".into());
+    }
+    let fallback = match linked {
+        Some(hooks) => hooks,
+        None if options.vanilla => WorldGenHooks::vanilla()?,
+        None => WorldGenHooks::stand_in(),
+    };
+    let hooks = match &options.library {
+        Some(library) => unsafe { WorldGenHooks::load_library(library, fallback)? },
+        None => fallback,
+    };
+
+    let sampler = Sampler::new(hooks, options.world_gen_options, options.seed);
+    let previews = Previews::render(&sampler, options.width, options.height);
+
+    fs::create_dir_all(&options.out)?;
+    for (name, image) in previews.images().iter() {
+        let path = write_image(image, &options.out, name, options.png)?;
+        println!("wrote {}", path.display());
+    }
+
+    if let Some(sample_count) = options.analyze {
+        print!("\n{}", analyze(&sampler, sample_count));
+    }
+
+    Ok(())
+}
+
+fn write_image(
+    image: &Image,
+    dir: &Path,
+    name: &str,
+    png: bool,
+) -> Result<PathBuf, Box<dyn Error>> {
+    let extension = if png {
+        "png"
+    } else if image.channels == 1 {
+        "pgm"
+    } else {
+        "ppm"
+    };
+    let path = dir.join(format!("{}.{}", name, extension));
+
+    let mut file = BufWriter::new(File::create(&path)?);
+    if png {
+        write_png(image, &mut file)?;
+    } else {
+        image.write_pnm(&mut file)?;
+    }
+
+    Ok(path)
+}
+
+#[cfg(feature = "png")]
+fn write_png(image: &Image, file: &mut BufWriter<File>) -> Result<(), Box<dyn Error>> {
+    Ok(image.write_png(file)?)
+}
+
+#[cfg(not(feature = "png"))]
+fn write_png(_image: &Image, _file: &mut BufWriter<File>) -> Result<(), Box<dyn Error>> {
+    Err("PNG output needs the png feature".into())
+}
+
+/// Runs the command line with the process's arguments, and exits with the usage if it fails
+///
+/// `linked` is as for `run`
+pub fn main(linked: Option<WorldGenHooks>) {
+    if let Err(error) = run(linked, env::args().skip(1)) {
+        eprintln!("{}\n\n{}", error, USAGE);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn renders_linked_hooks() {
+        let out = env::temp_dir().join(format!("sapiens-rs-preview-{}", process::id()));
+        let out_arg = out.to_str().unwrap();
+
+        run(
+            Some(WorldGenHooks::stand_in()),
+            args(&["--size", "8x4", "--pnm", "--out", out_arg]),
+        )
+        .unwrap();
+
+        let height = fs::read(out.join("height.ppm")).unwrap();
+        assert!(height.starts_with(b"P6\n8 4\n"));
+        assert!(out.join("surface.ppm").exists());
+        fs::remove_dir_all(&out).unwrap();
+    }
+
+    #[test]
+    fn linked_hooks_take_no_library() {
+        let error = run(Some(WorldGenHooks::stand_in()), args(&["libmy_mod.so"])).unwrap_err();
+        assert!(error.to_string().contains("linked in"));
+
+        assert!(run(Some(WorldGenHooks::stand_in()), args(&["--vanilla"])).is_err());
+    }
+}
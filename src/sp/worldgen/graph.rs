@@ -0,0 +1,885 @@
+//! Height layers and surface rules described in a RON, JSON or TOML file instead of Rust
+//!
+//! A graph file names the noise generators it uses, lists `height` layers in the order a `Pipeline` runs them, and
+//! lists `surface` rules in the order `SurfaceRules` checks them. Altitudes and distances are in meters, and are
+//! converted to prerender units on load
+//!
+//! ```text
+//! (
+//!     noises: {
+//!         "mountains": perlin(seed: 7),
+//!     },
+//!     height: [
+//!         base(noise: "noise1", frequency: 1.0, octaves: 6, amplitude: 2000.0),
+//!         add_layers([base(noise: "mountains", frequency: 4.0, octaves: 4, amplitude: 600.0)]),
+//!         height_offset(scale: 1000.0),
+//!         ocean_floor(depth: 3000.0),
+//!     ],
+//!     surface: [
+//!         (surface_type: rock, min_steepness: 0.6),
+//!         (surface_type: beach, min_altitude: 0.0, max_altitude: 2.0),
+//!     ],
+//! )
+//! ```
+//!
+//! `noise1` and `noise2` are the generators Sapiens passes to `spHeightGet`. Surface rules sample the generator passed
+//! to `spBiomeGetSurfaceType`
+//!
+//! A mod usually loads its graph once, from next to its library, and falls back to a graph built in Rust if the file
+//! is missing or broken. Why the file wasn't used is handed back, for the mod to report however it reports things
+//!
+//! ```ignore
+//! static GRAPH: OnceLock<WorldGenGraph> = OnceLock::new();
+//!
+//! fn graph() -> &'static WorldGenGraph {
+//!     GRAPH.get_or_init(|| {
+//!         let (graph, error) = WorldGenGraph::load_next_to_module_or("worldgen.ron", default_graph);
+//!         if let Some(error) = error {
+//!             log_warning(&format!("{}, using the built-in world-gen graph", error));
+//!         }
+//!         graph
+//!     })
+//! }
+//!
+//! #[export_to_sapiens]
+//! fn height_get(noise1: &NoiseRef, noise2: &NoiseRef, query: &HeightQuery, options: &WorldGenOptions) -> SPVec4 {
+//!     graph().height.get(noise1, noise2, query, options)
+//! }
+//!
+//! #[export_to_sapiens]
+//! fn biome_get_surface_type(noise: &NoiseRef, _biome_types: &BiomeTypes, query: &SurfaceQuery) -> SurfaceType {
+//!     graph().surface.get(noise, query)
+//! }
+//! ```
+
+use crate::sp::biome::{SurfaceRule, SurfaceRules, SurfaceType};
+use crate::sp::height::pipeline::{Layer, NoiseInput, Operand, Pipeline};
+use crate::sp::noise::{NoiseSource, OpenSimplex, Perlin, Worley, WorleyFeature};
+use crate::sp::worldgen;
+use crate::sp_meters_to_prerender;
+use sapiens_sys::serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::os::raw::c_int;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// The contents of a graph file, as written
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "sapiens_sys::serde", default, deny_unknown_fields)]
+pub struct GraphFile {
+    /// Noise generators the layers can refer to by name
+    pub noises: BTreeMap<String, NoiseSpec>,
+
+    pub height: Vec<LayerSpec>,
+
+    pub surface: Vec<SurfaceRuleSpec>,
+
+    /// The surface type for points that match no rule, `normal` if left out
+    pub default_surface: Option<SurfaceType>,
+}
+
+/// A noise generator in a graph file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(
+    crate = "sapiens_sys::serde",
+    rename_all = "snake_case",
+    deny_unknown_fields
+)]
+pub enum NoiseSpec {
+    Perlin {
+        seed: i32,
+        #[serde(default = "default_persistance")]
+        persistance: f64,
+    },
+    OpenSimplex {
+        seed: i32,
+        #[serde(default = "default_persistance")]
+        persistance: f64,
+    },
+    Worley {
+        seed: i32,
+        #[serde(default = "default_persistance")]
+        persistance: f64,
+        #[serde(default)]
+        feature: Option<WorleyFeature>,
+    },
+}
+
+fn default_persistance() -> f64 {
+    0.5
+}
+
+/// A height layer in a graph file. See `Layer` for what each one does
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(
+    crate = "sapiens_sys::serde",
+    rename_all = "snake_case",
+    deny_unknown_fields
+)]
+pub enum LayerSpec {
+    Base {
+        noise: String,
+        frequency: f64,
+        octaves: i32,
+        /// In meters
+        amplitude: f64,
+    },
+
+    /// Adds a constant, in meters
+    Add(f64),
+
+    /// Adds the result of these layers, run from sea level
+    AddLayers(Vec<LayerSpec>),
+
+    Multiply(f64),
+
+    /// Multiplies by the result of these layers, run from sea level
+    MultiplyLayers(Vec<LayerSpec>),
+
+    Terrace {
+        /// In meters
+        step: f64,
+        sharpness: f64,
+    },
+
+    /// In meters
+    Clamp {
+        min: f64,
+        max: f64,
+    },
+
+    RidgeMask {
+        noise: String,
+        frequency: f64,
+        octaves: i32,
+        sharpness: f64,
+    },
+
+    /// In meters
+    RiverCarve {
+        depth: f64,
+        width: f64,
+    },
+
+    /// In meters
+    OceanFloor {
+        depth: f64,
+    },
+
+    /// Meters per unit of the world's height offset option
+    HeightOffset {
+        scale: f64,
+    },
+}
+
+/// A surface rule in a graph file. See `SurfaceRule` for what each condition does
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "sapiens_sys::serde", deny_unknown_fields)]
+pub struct SurfaceRuleSpec {
+    pub surface_type: SurfaceType,
+
+    /// In meters
+    #[serde(default)]
+    pub min_altitude: Option<f64>,
+
+    /// In meters
+    #[serde(default)]
+    pub max_altitude: Option<f64>,
+
+    #[serde(default)]
+    pub min_steepness: Option<f32>,
+
+    #[serde(default)]
+    pub max_steepness: Option<f32>,
+
+    /// In meters
+    #[serde(default)]
+    pub max_river_distance: Option<f32>,
+
+    /// The raw vegetation state, as Sapiens passes it
+    #[serde(default)]
+    pub vegetation_state: Option<c_int>,
+
+    #[serde(default)]
+    pub noise: Option<NoiseThresholdSpec>,
+}
+
+/// The noise condition of a surface rule
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(crate = "sapiens_sys::serde", deny_unknown_fields)]
+pub struct NoiseThresholdSpec {
+    pub frequency: f64,
+    pub octaves: i32,
+    pub min: f64,
+}
+
+/// The languages a graph file can be written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Ron,
+    Json,
+    Toml,
+}
+
+impl Format {
+    /// Picks the format from a file's extension: `.ron`, `.json` or `.toml`
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "ron" => Some(Format::Ron),
+            "json" => Some(Format::Json),
+            "toml" => Some(Format::Toml),
+            _ => None,
+        }
+    }
+
+    /// Parses a graph file without checking it
+    pub fn parse(self, text: &str) -> Result<GraphFile, GraphError> {
+        let parsed = match self {
+            Format::Ron => ron::Options::default()
+                .with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME)
+                .from_str(text)
+                .map_err(|error| error.to_string()),
+            Format::Json => serde_json::from_str(text).map_err(|error| error.to_string()),
+            Format::Toml => toml::from_str(text).map_err(|error| error.to_string()),
+        };
+
+        parsed.map_err(|message| GraphError::Parse {
+            format: self,
+            message,
+        })
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Format::Ron => write!(f, "RON"),
+            Format::Json => write!(f, "JSON"),
+            Format::Toml => write!(f, "TOML"),
+        }
+    }
+}
+
+/// A problem with one value in a graph file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// Where the value is, e.g. `height[1].add_layers[0].noise`
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+impl Error for ValidationError {}
+
+/// Why a graph couldn't be loaded
+#[derive(Debug)]
+pub enum GraphError {
+    /// The file couldn't be read
+    Io { path: PathBuf, error: io::Error },
+
+    /// The file's extension isn't one of the supported formats
+    UnknownFormat(PathBuf),
+
+    /// The file isn't valid RON, JSON or TOML, or doesn't have the shape of a graph
+    Parse { format: Format, message: String },
+
+    /// The file parsed, but some values don't make sense. Every problem found is listed
+    Invalid(Vec<ValidationError>),
+
+    /// The platform couldn't say where the mod's library is, so a file next to it couldn't be found
+    NoModuleDir,
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphError::Io { path, error } => {
+                write!(f, "Could not read {}: {}", path.display(), error)
+            }
+            GraphError::UnknownFormat(path) => write!(
+                f,
+                "Could not tell the format of {}, it should end in .ron, .json or .toml",
+                path.display()
+            ),
+            GraphError::Parse { format, message } => {
+                write!(f, "Could not parse the {} graph: {}", format, message)
+            }
+            GraphError::Invalid(errors) => {
+                write!(f, "The world-gen graph has {} problem(s):", errors.len())?;
+                for error in errors {
+                    write!(f, "\n    {}", error)?;
+                }
+                Ok(())
+            }
+            GraphError::NoModuleDir => write!(f, "Could not find the mod's directory"),
+        }
+    }
+}
+
+impl Error for GraphError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            GraphError::Io { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// A checked graph, ready to run inside the world-gen hooks
+#[derive(Debug, Clone, Default)]
+pub struct WorldGenGraph {
+    pub height: Pipeline,
+    pub surface: SurfaceRules,
+}
+
+impl WorldGenGraph {
+    /// Parses and checks a graph file's contents
+    pub fn parse(text: &str, format: Format) -> Result<Self, GraphError> {
+        format.parse(text)?.compile()
+    }
+
+    /// Reads, parses and checks a graph file, picking the format from its extension
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, GraphError> {
+        let path = path.as_ref();
+        let format =
+            Format::from_path(path).ok_or_else(|| GraphError::UnknownFormat(path.to_path_buf()))?;
+        let text = fs::read_to_string(path).map_err(|error| GraphError::Io {
+            path: path.to_path_buf(),
+            error,
+        })?;
+
+        WorldGenGraph::parse(&text, format)
+    }
+
+    /// Loads a graph file, or uses `fallback` instead if it can't, along with why
+    pub fn load_or(
+        path: impl AsRef<Path>,
+        fallback: impl FnOnce() -> Self,
+    ) -> (Self, Option<GraphError>) {
+        match WorldGenGraph::load(path) {
+            Ok(graph) => (graph, None),
+            Err(error) => (fallback(), Some(error)),
+        }
+    }
+
+    /// Like `load_or`, for a file next to the mod's library
+    pub fn load_next_to_module_or(
+        file_name: impl AsRef<Path>,
+        fallback: impl FnOnce() -> Self,
+    ) -> (Self, Option<GraphError>) {
+        match worldgen::next_to_module(file_name) {
+            Some(path) => WorldGenGraph::load_or(path, fallback),
+            None => (fallback(), Some(GraphError::NoModuleDir)),
+        }
+    }
+}
+
+impl GraphFile {
+    /// Checks every value, and builds the pipeline and rules the file describes
+    pub fn compile(&self) -> Result<WorldGenGraph, GraphError> {
+        let mut compiler = Compiler {
+            noises: HashMap::new(),
+            errors: Vec::new(),
+        };
+
+        for (name, spec) in &self.noises {
+            compiler.add_noise(name, spec);
+        }
+
+        let height = compiler.pipeline("height", &self.height);
+        let mut surface = SurfaceRules::new();
+        for (i, rule) in self.surface.iter().enumerate() {
+            surface
+                .rules
+                .push(compiler.surface_rule(&format!("surface[{}]", i), rule));
+        }
+        if let Some(default_surface) = self.default_surface {
+            surface.fallback = default_surface;
+        }
+
+        if compiler.errors.is_empty() {
+            Ok(WorldGenGraph { height, surface })
+        } else {
+            Err(GraphError::Invalid(compiler.errors))
+        }
+    }
+}
+
+struct Compiler {
+    noises: HashMap<String, Arc<dyn NoiseSource + Send + Sync>>,
+    errors: Vec<ValidationError>,
+}
+
+impl Compiler {
+    fn error(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.errors.push(ValidationError {
+            path: path.into(),
+            message: message.into(),
+        });
+    }
+
+    fn check(&mut self, ok: bool, path: impl FnOnce() -> String, message: &str) {
+        if !ok {
+            self.error(path(), message);
+        }
+    }
+
+    fn finite(&mut self, path: &str, field: &str, value: f64) {
+        self.check(
+            value.is_finite(),
+            || format!("{}.{}", path, field),
+            "must be a finite number",
+        );
+    }
+
+    fn positive(&mut self, path: &str, field: &str, value: f64) {
+        self.check(
+            value.is_finite() && value > 0.0,
+            || format!("{}.{}", path, field),
+            "must be greater than 0",
+        );
+    }
+
+    fn non_negative(&mut self, path: &str, field: &str, value: f64) {
+        self.check(
+            value.is_finite() && value >= 0.0,
+            || format!("{}.{}", path, field),
+            "must not be negative",
+        );
+    }
+
+    fn octaves(&mut self, path: &str, octaves: i32) {
+        self.check(
+            octaves >= 1,
+            || format!("{}.octaves", path),
+            "must be at least 1",
+        );
+    }
+
+    fn add_noise(&mut self, name: &str, spec: &NoiseSpec) {
+        let path = format!("noises.{}", name);
+        if name == "noise1" || name == "noise2" {
+            self.error(path, "is reserved for the noise Sapiens passes in");
+            return;
+        }
+
+        let (seed, persistance) = match spec {
+            NoiseSpec::Perlin { seed, persistance }
+            | NoiseSpec::OpenSimplex { seed, persistance }
+            | NoiseSpec::Worley {
+                seed, persistance, ..
+            } => (*seed, *persistance),
+        };
+        self.check(
+            persistance > 0.0 && persistance <= 1.0,
+            || format!("{}.persistance", path),
+            "must be greater than 0 and at most 1",
+        );
+
+        let noise: Arc<dyn NoiseSource + Send + Sync> = match spec {
+            NoiseSpec::Perlin { .. } => Arc::new(Perlin::new(seed, persistance)),
+            NoiseSpec::OpenSimplex { .. } => Arc::new(OpenSimplex::new(seed, persistance)),
+            NoiseSpec::Worley { feature, .. } => Arc::new(
+                Worley::new(seed, persistance)
+                    .with_feature(feature.unwrap_or(WorleyFeature::Nearest)),
+            ),
+        };
+        self.noises.insert(name.to_string(), noise);
+    }
+
+    fn noise(&mut self, path: &str, name: &str) -> NoiseInput {
+        match name {
+            "noise1" => NoiseInput::Noise1,
+            "noise2" => NoiseInput::Noise2,
+            _ => match self.noises.get(name) {
+                Some(noise) => NoiseInput::Custom(noise.clone()),
+                None => {
+                    self.error(
+                        format!("{}.noise", path),
+                        format!(
+                            "unknown noise `{}`, expected noise1, noise2 or one from `noises`",
+                            name
+                        ),
+                    );
+                    NoiseInput::Noise1
+                }
+            },
+        }
+    }
+
+    fn pipeline(&mut self, path: &str, layers: &[LayerSpec]) -> Pipeline {
+        let mut pipeline = Pipeline::new();
+        for (i, layer) in layers.iter().enumerate() {
+            let layer = self.layer(&format!("{}[{}]", path, i), layer);
+            pipeline = pipeline.then(layer);
+        }
+        pipeline
+    }
+
+    fn layer(&mut self, path: &str, spec: &LayerSpec) -> Layer {
+        match spec {
+            LayerSpec::Base {
+                noise,
+                frequency,
+                octaves,
+                amplitude,
+            } => {
+                self.positive(path, "frequency", *frequency);
+                self.octaves(path, *octaves);
+                self.finite(path, "amplitude", *amplitude);
+                Layer::Base {
+                    noise: self.noise(path, noise),
+                    frequency: *frequency,
+                    octaves: *octaves,
+                    amplitude: sp_meters_to_prerender!(*amplitude),
+                }
+            }
+            LayerSpec::Add(value) => {
+                self.finite(path, "add", *value);
+                Layer::Add(Operand::Constant(sp_meters_to_prerender!(*value)))
+            }
+            LayerSpec::AddLayers(layers) => {
+                let pipeline = self.pipeline(&format!("{}.add_layers", path), layers);
+                Layer::Add(Operand::Pipeline(pipeline))
+            }
+            LayerSpec::Multiply(value) => {
+                self.finite(path, "multiply", *value);
+                Layer::Multiply(Operand::Constant(*value))
+            }
+            LayerSpec::MultiplyLayers(layers) => {
+                let pipeline = self.pipeline(&format!("{}.multiply_layers", path), layers);
+                Layer::Multiply(Operand::Pipeline(pipeline))
+            }
+            LayerSpec::Terrace { step, sharpness } => {
+                self.positive(path, "step", *step);
+                self.positive(path, "sharpness", *sharpness);
+                Layer::Terrace {
+                    step: sp_meters_to_prerender!(*step),
+                    sharpness: *sharpness,
+                }
+            }
+            LayerSpec::Clamp { min, max } => {
+                self.finite(path, "min", *min);
+                self.finite(path, "max", *max);
+                self.check(
+                    min <= max,
+                    || format!("{}.min", path),
+                    "must not be above max",
+                );
+                Layer::Clamp {
+                    min: sp_meters_to_prerender!(*min),
+                    max: sp_meters_to_prerender!(*max),
+                }
+            }
+            LayerSpec::RidgeMask {
+                noise,
+                frequency,
+                octaves,
+                sharpness,
+            } => {
+                self.positive(path, "frequency", *frequency);
+                self.octaves(path, *octaves);
+                self.non_negative(path, "sharpness", *sharpness);
+                Layer::RidgeMask {
+                    noise: self.noise(path, noise),
+                    frequency: *frequency,
+                    octaves: *octaves,
+                    sharpness: *sharpness,
+                }
+            }
+            LayerSpec::RiverCarve { depth, width } => {
+                self.non_negative(path, "depth", *depth);
+                self.positive(path, "width", *width);
+                Layer::RiverCarve {
+                    depth: sp_meters_to_prerender!(*depth),
+                    width: sp_meters_to_prerender!(*width),
+                }
+            }
+            LayerSpec::OceanFloor { depth } => {
+                self.positive(path, "depth", *depth);
+                Layer::OceanFloor {
+                    depth: sp_meters_to_prerender!(*depth),
+                }
+            }
+            LayerSpec::HeightOffset { scale } => {
+                self.finite(path, "scale", *scale);
+                Layer::HeightOffset {
+                    scale: sp_meters_to_prerender!(*scale),
+                }
+            }
+        }
+    }
+
+    fn surface_rule(&mut self, path: &str, spec: &SurfaceRuleSpec) -> SurfaceRule {
+        let mut rule = SurfaceRule::new(spec.surface_type);
+
+        if let (Some(min), Some(max)) = (spec.min_altitude, spec.max_altitude) {
+            self.check(
+                min <= max,
+                || format!("{}.min_altitude", path),
+                "must not be above max_altitude",
+            );
+        }
+        if let (Some(min), Some(max)) = (spec.min_steepness, spec.max_steepness) {
+            self.check(
+                min <= max,
+                || format!("{}.min_steepness", path),
+                "must not be above max_steepness",
+            );
+        }
+        if let Some(distance) = spec.max_river_distance {
+            self.non_negative(path, "max_river_distance", distance as f64);
+        }
+
+        rule.min_altitude = spec
+            .min_altitude
+            .map(|altitude| sp_meters_to_prerender!(altitude));
+        rule.max_altitude = spec
+            .max_altitude
+            .map(|altitude| sp_meters_to_prerender!(altitude));
+        rule.min_steepness = spec.min_steepness;
+        rule.max_steepness = spec.max_steepness;
+        rule.max_river_distance = spec
+            .max_river_distance
+            .map(|distance| sp_meters_to_prerender!(distance as f64) as f32);
+        rule.vegetation_state = spec.vegetation_state;
+
+        if let Some(threshold) = spec.noise {
+            let path = format!("{}.noise", path);
+            self.positive(&path, "frequency", threshold.frequency);
+            self.octaves(&path, threshold.octaves);
+            self.finite(&path, "min", threshold.min);
+            rule = rule.noise(threshold.frequency, threshold.octaves, threshold.min);
+        }
+
+        rule
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_vec_near;
+    use crate::sp::biome::SurfaceQuery;
+    use crate::sp::common::WorldGenOptions;
+    use crate::sp::height::HeightQuery;
+    use crate::sp::mock::ConstantNoise;
+    use sapiens_sys::SPVec3;
+
+    const RON_GRAPH: &str = r#"(
+        noises: {
+            "mountains": perlin(seed: 7),
+        },
+        height: [
+            base(noise: "noise1", frequency: 1.0, octaves: 6, amplitude: 2000.0),
+            add_layers([base(noise: "noise2", frequency: 4.0, octaves: 4, amplitude: 600.0)]),
+            multiply(1.1),
+            height_offset(scale: 1000.0),
+        ],
+        surface: [
+            (surface_type: rock, min_steepness: 0.6),
+            (surface_type: beach, min_altitude: 0.0, max_altitude: 2.0),
+        ],
+        default_surface: secondary,
+    )"#;
+
+    const JSON_GRAPH: &str = r#"{
+        "noises": { "mountains": { "perlin": { "seed": 7 } } },
+        "height": [
+            { "base": { "noise": "noise1", "frequency": 1.0, "octaves": 6, "amplitude": 2000.0 } },
+            { "add_layers": [{ "base": { "noise": "noise2", "frequency": 4.0, "octaves": 4, "amplitude": 600.0 } }] },
+            { "multiply": 1.1 },
+            { "height_offset": { "scale": 1000.0 } }
+        ],
+        "surface": [
+            { "surface_type": "rock", "min_steepness": 0.6 },
+            { "surface_type": "beach", "min_altitude": 0.0, "max_altitude": 2.0 }
+        ],
+        "default_surface": "secondary"
+    }"#;
+
+    const TOML_GRAPH: &str = r#"
+        default_surface = "secondary"
+
+        [noises.mountains]
+        perlin = { seed = 7 }
+
+        [[height]]
+        base = { noise = "noise1", frequency = 1.0, octaves = 6, amplitude = 2000.0 }
+
+        [[height]]
+        add_layers = [{ base = { noise = "noise2", frequency = 4.0, octaves = 4, amplitude = 600.0 } }]
+
+        [[height]]
+        multiply = 1.1
+
+        [[height]]
+        height_offset = { scale = 1000.0 }
+
+        [[surface]]
+        surface_type = "rock"
+        min_steepness = 0.6
+
+        [[surface]]
+        surface_type = "beach"
+        min_altitude = 0.0
+        max_altitude = 2.0
+    "#;
+
+    fn surface_query(altitude_meters: f64, steepness: f32) -> SurfaceQuery {
+        SurfaceQuery::from_sp(
+            SPVec3::default(),
+            SPVec3::default(),
+            0,
+            0,
+            sp_meters_to_prerender!(altitude_meters),
+            steepness,
+            1.0,
+        )
+    }
+
+    #[test]
+    fn formats_agree() {
+        let ron = Format::Ron.parse(RON_GRAPH).unwrap();
+        let json = Format::Json.parse(JSON_GRAPH).unwrap();
+        let toml = Format::Toml.parse(TOML_GRAPH).unwrap();
+
+        assert_eq!(ron, json);
+        assert_eq!(ron, toml);
+        assert_eq!(ron.height.len(), 4);
+        assert_eq!(
+            ron.noises["mountains"],
+            NoiseSpec::Perlin {
+                seed: 7,
+                persistance: 0.5
+            }
+        );
+    }
+
+    #[test]
+    fn graph_runs_like_the_same_pipeline() {
+        let graph = WorldGenGraph::parse(RON_GRAPH, Format::Ron).unwrap();
+        let query = HeightQuery::at(SPVec3 {
+            x: 0.0,
+            y: 1.0,
+            z: 0.0,
+        });
+        let options = WorldGenOptions::builder()
+            .height_offset(0.5)
+            .build()
+            .unwrap();
+
+        let height = graph
+            .height
+            .get(&ConstantNoise(0.5), &ConstantNoise(-0.25), &query, &options);
+
+        let meters = (0.5 * 2000.0 - 0.25 * 600.0) * 1.1 + 0.5 * 1000.0;
+        assert_vec_near!(height.x, sp_meters_to_prerender!(meters));
+    }
+
+    #[test]
+    fn surface_rules_use_meters() {
+        let graph = WorldGenGraph::parse(RON_GRAPH, Format::Ron).unwrap();
+        let noise = ConstantNoise(0.0);
+
+        assert_eq!(
+            graph.surface.get(&noise, &surface_query(1.0, 0.7)),
+            SurfaceType::Rock
+        );
+        assert_eq!(
+            graph.surface.get(&noise, &surface_query(1.0, 0.1)),
+            SurfaceType::Beach
+        );
+        assert_eq!(
+            graph.surface.get(&noise, &surface_query(3.0, 0.1)),
+            SurfaceType::Secondary
+        );
+    }
+
+    #[test]
+    fn reports_every_problem() {
+        let broken = r#"(
+            noises: { "noise1": perlin(seed: 1, persistance: 2.0) },
+            height: [
+                base(noise: "hills", frequency: 0.0, octaves: 0, amplitude: 10.0),
+                add_layers([clamp(min: 5.0, max: 1.0)]),
+            ],
+            surface: [(surface_type: rock, min_steepness: 0.8, max_steepness: 0.2)],
+        )"#;
+
+        let errors = match WorldGenGraph::parse(broken, Format::Ron) {
+            Err(GraphError::Invalid(errors)) => errors,
+            other => panic!("expected validation errors, got {:?}", other.map(|_| ())),
+        };
+        let paths: Vec<&str> = errors.iter().map(|error| error.path.as_str()).collect();
+
+        assert_eq!(
+            paths,
+            [
+                "noises.noise1",
+                "height[0].frequency",
+                "height[0].octaves",
+                "height[0].noise",
+                "height[1].add_layers[0].min",
+                "surface[0].min_steepness",
+            ]
+        );
+        assert!(errors[3].to_string().contains("unknown noise `hills`"));
+    }
+
+    #[test]
+    fn rejects_unknown_fields() {
+        let error = Format::Json
+            .parse(r#"{ "height": [{ "base": { "noise": "noise1", "frequncy": 1.0 } }] }"#)
+            .unwrap_err();
+
+        assert!(matches!(
+            error,
+            GraphError::Parse {
+                format: Format::Json,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn falls_back_when_the_file_is_missing() {
+        let (graph, error) =
+            WorldGenGraph::load_or("/does/not/exist/worldgen.ron", || WorldGenGraph {
+                height: Pipeline::new().add(1.0),
+                surface: SurfaceRules::new(),
+            });
+
+        assert_eq!(graph.height.layers().len(), 1);
+        assert!(matches!(error, Some(GraphError::Io { .. })));
+        assert!(matches!(
+            WorldGenGraph::load("worldgen.yaml"),
+            Err(GraphError::UnknownFormat(_))
+        ));
+        assert!(matches!(
+            WorldGenGraph::load("/does/not/exist/worldgen.toml"),
+            Err(GraphError::Io { .. })
+        ));
+    }
+
+    #[test]
+    fn loads_from_a_file() {
+        let path =
+            std::env::temp_dir().join(format!("sapiens-rs-graph-{}.toml", std::process::id()));
+        fs::write(&path, TOML_GRAPH).unwrap();
+
+        let graph = WorldGenGraph::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(graph.unwrap().surface.rules.len(), 2);
+    }
+}
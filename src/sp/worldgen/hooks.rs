@@ -0,0 +1,180 @@
+//! A world-gen mod's hooks, gathered so tools can call them the way Sapiens would
+
+#[cfg(feature = "vanilla")]
+use crate::sp::common::LoadVanillaError;
+use crate::sp::{biome, climate, height, rainfall};
+#[cfg(feature = "vanilla")]
+use libloading::Library;
+#[cfg(feature = "vanilla")]
+use sapiens_sys::{
+    SPBiomeGetBiomeTypeFunc, SPBiomeGetSurfaceTypeFunc, SPClimateGetFunc, SPHeightGetFunc,
+    SPRainfallGetFunc,
+};
+#[cfg(feature = "vanilla")]
+use std::error::Error;
+#[cfg(feature = "vanilla")]
+use std::fmt;
+#[cfg(feature = "vanilla")]
+use std::path::{Path, PathBuf};
+
+/// The height, rainfall, climate and biome hooks of a world-gen mod
+///
+/// A mod linked into the tool as an rlib can hand over its exported functions directly:
+///
+/// ```ignore
+/// let hooks = WorldGenHooks::stand_in()
+///     .with_height(unsafe { height::Vanilla::from_sp(Some(my_mod::spHeightGet)) }.unwrap());
+/// ```
+///
+/// Passing those to `cli::main` gives it the `sapiens-preview` command line. A mod built as a DLL or shared library is
+/// opened with `load_library` instead, with the `vanilla` feature
+#[derive(Debug, Clone, Copy)]
+pub struct WorldGenHooks {
+    pub height: height::Vanilla,
+    pub rainfall: rainfall::Vanilla,
+    pub climate: climate::Vanilla,
+    pub biome: biome::Vanilla,
+}
+
+impl WorldGenHooks {
+    /// The game's own hooks, from SPCommon, with the `vanilla` feature
+    #[cfg(feature = "vanilla")]
+    pub fn vanilla() -> Result<Self, LoadVanillaError> {
+        Ok(WorldGenHooks {
+            height: height::Vanilla::load()?,
+            rainfall: rainfall::Vanilla::load()?,
+            climate: climate::Vanilla::load()?,
+            biome: biome::Vanilla::load()?,
+        })
+    }
+
+    /// The stand-ins for every hook, for tools which don't have the game to call into
+    pub fn stand_in() -> Self {
+        WorldGenHooks {
+            height: height::Vanilla::stand_in(),
+            rainfall: rainfall::Vanilla::stand_in(),
+            climate: climate::Vanilla::stand_in(),
+            biome: biome::Vanilla::stand_in(),
+        }
+    }
+
+    pub fn with_height(mut self, height: height::Vanilla) -> Self {
+        self.height = height;
+        self
+    }
+
+    pub fn with_rainfall(mut self, rainfall: rainfall::Vanilla) -> Self {
+        self.rainfall = rainfall;
+        self
+    }
+
+    pub fn with_climate(mut self, climate: climate::Vanilla) -> Self {
+        self.climate = climate;
+        self
+    }
+
+    pub fn with_biome(mut self, biome: biome::Vanilla) -> Self {
+        self.biome = biome;
+        self
+    }
+
+    /// Opens a mod's DLL or shared library and takes the hooks it exports, keeping `fallback`'s for the rest
+    ///
+    /// The library stays loaded until the process exits, as the hooks point into it. It's an error if it exports none
+    /// of the hooks
+    ///
+    /// # Safety
+    ///
+    /// Opening a library runs its initialization code, and its exports are trusted to have the signatures Sapiens
+    /// expects
+    #[cfg(feature = "vanilla")]
+    pub unsafe fn load_library(
+        path: impl AsRef<Path>,
+        fallback: WorldGenHooks,
+    ) -> Result<Self, LoadHooksError> {
+        let path = path.as_ref();
+        let error = |message: String| LoadHooksError {
+            path: path.to_path_buf(),
+            message,
+        };
+
+        let library = Library::new(path).map_err(|e| error(e.to_string()))?;
+        // The hooks are used for the rest of the process, so the library must never be unloaded
+        let library: &'static Library = Box::leak(Box::new(library));
+
+        let height: SPHeightGetFunc = export(library, b"spHeightGet\0");
+        let rainfall: SPRainfallGetFunc = export(library, b"spRainfallGet\0");
+        let climate: SPClimateGetFunc = export(library, b"spClimateGet\0");
+        let biome_type: SPBiomeGetBiomeTypeFunc = export(library, b"spBiomeGetBiomeType\0");
+        let surface_type: SPBiomeGetSurfaceTypeFunc = export(library, b"spBiomeGetSurfaceType\0");
+
+        if height.is_none()
+            && rainfall.is_none()
+            && climate.is_none()
+            && biome_type.is_none()
+            && surface_type.is_none()
+        {
+            return Err(error("it doesn't export any world-gen hooks".to_string()));
+        }
+
+        let biome_type = biome_type.or_else(|| fallback.biome.as_sp_biome_get_biome_type_func());
+        let surface_type =
+            surface_type.or_else(|| fallback.biome.as_sp_biome_get_surface_type_func());
+
+        Ok(WorldGenHooks {
+            height: height::Vanilla::from_sp(height).unwrap_or(fallback.height),
+            rainfall: rainfall::Vanilla::from_sp(rainfall).unwrap_or(fallback.rainfall),
+            climate: climate::Vanilla::from_sp(climate).unwrap_or(fallback.climate),
+            biome: biome::Vanilla::from_sp(biome_type, surface_type).unwrap_or(fallback.biome),
+        })
+    }
+}
+
+/// One of the `SP*Func` types, holding the function the library exports, or `None` if it doesn't
+#[cfg(feature = "vanilla")]
+unsafe fn export<F: Copy + Default>(library: &Library, symbol: &[u8]) -> F {
+    library
+        .get::<F>(symbol)
+        .map(|func| *func)
+        .unwrap_or_default()
+}
+
+/// Why a mod's library couldn't be used
+#[cfg(feature = "vanilla")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadHooksError {
+    pub path: PathBuf,
+    pub message: String,
+}
+
+#[cfg(feature = "vanilla")]
+impl fmt::Display for LoadHooksError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "can't load hooks from {}: {}",
+            self.path.display(),
+            self.message
+        )
+    }
+}
+
+#[cfg(feature = "vanilla")]
+impl Error for LoadHooksError {}
+
+#[cfg(all(test, feature = "vanilla"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_libraries_are_errors() {
+        let result =
+            unsafe { WorldGenHooks::load_library("no-such-mod.so", WorldGenHooks::stand_in()) };
+        let error = result.unwrap_err();
+
+        assert_eq!(error.path, Path::new("no-such-mod.so"));
+        assert!(error
+            .to_string()
+            .starts_with("can't load hooks from no-such-mod.so"));
+    }
+}
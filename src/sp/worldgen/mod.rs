@@ -0,0 +1,135 @@
+//! Helpers for world-gen mods as a whole, rather than for a single hook
+//!
+//! `WorldGenHooks` gathers a mod's hooks so `Sampler` can run them anywhere on the planet, and `preview` draws what
+//! they generate, and `analyze` sums it up in numbers. With the `worldgen-graph` feature, `graph` loads height and
+//! surface rules from a data file, and with the `preview` feature `cli` is the `sapiens-preview` command line
+
+mod analyze;
+#[cfg(feature = "preview")]
+pub mod cli;
+#[cfg(feature = "worldgen-graph")]
+pub mod graph;
+mod hooks;
+pub mod preview;
+mod sample;
+
+pub use analyze::{analyze, uniform_points, WorldGenReport, HISTOGRAM_BINS};
+#[cfg(feature = "vanilla")]
+pub use hooks::LoadHooksError;
+pub use hooks::WorldGenHooks;
+pub use sample::{estimate_pregen_rainfall, estimate_temperature, PointSample, Sampler};
+
+use std::path::{Path, PathBuf};
+
+/// The directory of the DLL or shared library this crate is linked into, i.e. the mod's own directory
+///
+/// Returns `None` if the platform can't say where the code was loaded from
+pub fn module_dir() -> Option<PathBuf> {
+    module_path()?.parent().map(Path::to_path_buf)
+}
+
+/// The path of a file which ships next to the mod's library, e.g. a config file
+pub fn next_to_module(file_name: impl AsRef<Path>) -> Option<PathBuf> {
+    Some(module_dir()?.join(file_name))
+}
+
+#[cfg(unix)]
+fn module_path() -> Option<PathBuf> {
+    use std::ffi::{CStr, OsStr};
+    use std::os::raw::{c_char, c_int, c_void};
+    use std::os::unix::ffi::OsStrExt;
+    use std::ptr;
+
+    #[repr(C)]
+    struct DlInfo {
+        dli_fname: *const c_char,
+        dli_fbase: *mut c_void,
+        dli_sname: *const c_char,
+        dli_saddr: *mut c_void,
+    }
+
+    extern "C" {
+        fn dladdr(addr: *const c_void, info: *mut DlInfo) -> c_int;
+    }
+
+    let mut info = DlInfo {
+        dli_fname: ptr::null(),
+        dli_fbase: ptr::null_mut(),
+        dli_sname: ptr::null(),
+        dli_saddr: ptr::null_mut(),
+    };
+
+    // Any function in this crate lives in the mod's library
+    let found = unsafe { dladdr(module_path as *const c_void, &mut info) };
+    if found == 0 || info.dli_fname.is_null() {
+        return None;
+    }
+
+    let name = unsafe { CStr::from_ptr(info.dli_fname) };
+    Some(PathBuf::from(OsStr::from_bytes(name.to_bytes())))
+}
+
+#[cfg(windows)]
+fn module_path() -> Option<PathBuf> {
+    use std::ffi::OsString;
+    use std::os::raw::c_void;
+    use std::os::windows::ffi::OsStringExt;
+    use std::ptr;
+
+    const GET_MODULE_HANDLE_EX_FLAG_UNCHANGED_REFCOUNT: u32 = 0x2;
+    const GET_MODULE_HANDLE_EX_FLAG_FROM_ADDRESS: u32 = 0x4;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetModuleHandleExW(flags: u32, module_name: *const u16, module: *mut *mut c_void)
+            -> i32;
+        fn GetModuleFileNameW(module: *mut c_void, file_name: *mut u16, size: u32) -> u32;
+    }
+
+    let mut module = ptr::null_mut();
+    // Any function in this crate lives in the mod's DLL
+    let found = unsafe {
+        GetModuleHandleExW(
+            GET_MODULE_HANDLE_EX_FLAG_FROM_ADDRESS | GET_MODULE_HANDLE_EX_FLAG_UNCHANGED_REFCOUNT,
+            module_path as *const u16,
+            &mut module,
+        )
+    };
+    if found == 0 {
+        return None;
+    }
+
+    let mut file_name = vec![0u16; 32768];
+    let len = unsafe { GetModuleFileNameW(module, file_name.as_mut_ptr(), file_name.len() as u32) };
+    if len == 0 {
+        return None;
+    }
+
+    Some(PathBuf::from(OsString::from_wide(
+        &file_name[..len as usize],
+    )))
+}
+
+#[cfg(not(any(unix, windows)))]
+fn module_path() -> Option<PathBuf> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn finds_the_test_binary() {
+        // Tests are linked into an executable, so that's the module
+        let exe = env::current_exe().unwrap().canonicalize().unwrap();
+        let dir = module_dir().unwrap().canonicalize().unwrap();
+
+        assert_eq!(Some(dir.as_path()), exe.parent());
+        assert_eq!(
+            next_to_module("worldgen.ron").unwrap().file_name(),
+            Some("worldgen.ron".as_ref())
+        );
+    }
+}
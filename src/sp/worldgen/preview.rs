@@ -0,0 +1,195 @@
+//! Equirectangular images of what a mod's hooks generate, to look at a world without starting the game
+//!
+//! ```ignore
+//! let sampler = Sampler::new(WorldGenHooks::stand_in(), WorldGenOptions::vanilla(), 1234);
+//! let previews = Previews::render(&sampler, 1024, 512);
+//!
+//! for (name, image) in previews.images().iter() {
+//!     image.write_pnm(&mut File::create(format!("{}.ppm", name))?)?;
+//! }
+//! ```
+
+use crate::sp::common::TerrainType;
+use crate::sp::noise::preview::{lat_long_at, Colormap, Grid, Image, ValueRange};
+use crate::sp::worldgen::{PointSample, Sampler};
+use std::os::raw::c_int;
+
+/// The coldest and warmest temperatures the temperature image tells apart, in degrees Celsius
+const TEMPERATURE_RANGE: (f64, f64) = (-40.0, 40.0);
+
+/// Colors for climate, biome and terrain types, which repeat after this many
+const CATEGORY_COLORS: [[u8; 3]; 12] = [
+    [230, 25, 75],
+    [60, 180, 75],
+    [255, 225, 25],
+    [0, 130, 200],
+    [245, 130, 48],
+    [145, 30, 180],
+    [70, 240, 240],
+    [240, 50, 230],
+    [210, 245, 60],
+    [250, 190, 212],
+    [0, 128, 128],
+    [170, 110, 40],
+];
+
+/// One image per thing the hooks decide, all the same size
+#[derive(Debug, Clone, PartialEq)]
+pub struct Previews {
+    /// Altitude, with the coast in the middle of the terrain colormap
+    pub height: Image,
+
+    /// Rainfall averaged over both seasons, from none in black to the most anywhere in white
+    pub rainfall: Image,
+
+    /// Temperature averaged over both seasons, from -40°C in black to 40°C in white
+    pub temperature: Image,
+
+    /// A color per climate type
+    pub climate: Image,
+
+    /// A color per biome type
+    pub biome: Image,
+
+    /// A color per terrain type from the surface hook, with invalid ones in black
+    pub surface: Image,
+}
+
+impl Previews {
+    /// Samples the whole planet, with the poles along the top and bottom edges and the -Z meridian down the sides
+    pub fn render(sampler: &Sampler, width: usize, height: usize) -> Self {
+        let mut samples = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let point_normal = lat_long_at(x, y, width, height).to_unit_vector();
+                samples.push(sampler.sample(point_normal));
+            }
+        }
+
+        Previews::from_samples(&samples, width, height)
+    }
+
+    /// Draws samples taken row by row from the top left
+    pub fn from_samples(samples: &[PointSample], width: usize, height: usize) -> Self {
+        assert_eq!(samples.len(), width * height, "one sample per pixel");
+
+        let grid = |value: fn(&PointSample) -> f64| {
+            Grid::from_fn(width, height, |x, y| value(&samples[y * width + x]))
+        };
+
+        let deepest = samples.iter().fold(0.0, |m, s| s.altitude.abs().max(m));
+        let height_image =
+            grid(|s| s.altitude).to_image(ValueRange::Fixed(-deepest, deepest), Colormap::Terrain);
+
+        let rainfall = grid(|s| (s.rainfall[0] + s.rainfall[1]) / 2.0);
+        let wettest = rainfall.stats(0).max.max(0.0);
+        let rainfall_image =
+            rainfall.to_image(ValueRange::Fixed(0.0, wettest), Colormap::Grayscale);
+
+        let (coldest, warmest) = TEMPERATURE_RANGE;
+        let temperature_image = grid(|s| (s.temperature[0] + s.temperature[1]) / 2.0)
+            .to_image(ValueRange::Fixed(coldest, warmest), Colormap::Heat);
+
+        Previews {
+            height: height_image,
+            rainfall: rainfall_image,
+            temperature: temperature_image,
+            climate: categories(samples, width, height, |s| category_color(s.climate_type)),
+            biome: categories(samples, width, height, |s| category_color(s.biome_type)),
+            surface: categories(samples, width, height, |s| terrain_color(s.terrain_type)),
+        }
+    }
+
+    /// Every image with a short name for it, e.g. to build file names from
+    pub fn images(&self) -> [(&'static str, &Image); 6] {
+        [
+            ("height", &self.height),
+            ("rainfall", &self.rainfall),
+            ("temperature", &self.temperature),
+            ("climate", &self.climate),
+            ("biome", &self.biome),
+            ("surface", &self.surface),
+        ]
+    }
+}
+
+fn categories(
+    samples: &[PointSample],
+    width: usize,
+    height: usize,
+    color: impl Fn(&PointSample) -> [u8; 3],
+) -> Image {
+    Image {
+        width,
+        height,
+        channels: 3,
+        pixels: samples.iter().flat_map(color).collect(),
+    }
+}
+
+/// The color for a climate or biome type, or black for a negative one
+pub fn category_color(category: c_int) -> [u8; 3] {
+    if category < 0 {
+        [0, 0, 0]
+    } else {
+        CATEGORY_COLORS[category as usize % CATEGORY_COLORS.len()]
+    }
+}
+
+/// The color for a terrain type, or black for `None`
+pub fn terrain_color(terrain_type: Option<TerrainType>) -> [u8; 3] {
+    terrain_type.map_or([0, 0, 0], |terrain_type| {
+        category_color(terrain_type.id() as c_int)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sapiens_sys::SPVec3;
+
+    fn sample(
+        altitude: f64,
+        climate_type: c_int,
+        terrain_type: Option<TerrainType>,
+    ) -> PointSample {
+        PointSample {
+            point_normal: SPVec3::default(),
+            altitude,
+            steepness: 0.0,
+            rainfall: [10.0, 30.0],
+            temperature: [0.0, 0.0],
+            climate_type,
+            biome_type: climate_type + 1,
+            terrain_type,
+        }
+    }
+
+    #[test]
+    fn draws_one_pixel_per_sample() {
+        let samples = [
+            sample(-2.0, 0, Some(TerrainType::from_id(4))),
+            sample(1.0, -1, None),
+        ];
+        let previews = Previews::from_samples(&samples, 2, 1);
+
+        for (_, image) in previews.images().iter() {
+            assert_eq!((image.width, image.height), (2, 1));
+        }
+
+        // The deepest point sets the range, so sea level stays in the middle
+        assert_eq!(&previews.height.pixels[..3], &[8, 24, 88]);
+        assert_eq!(previews.rainfall.pixels, vec![255, 255]);
+        assert_eq!(&previews.climate.pixels[..3], &CATEGORY_COLORS[0]);
+        assert_eq!(&previews.climate.pixels[3..], &[0, 0, 0]);
+        assert_eq!(&previews.biome.pixels[3..], &CATEGORY_COLORS[0]);
+        assert_eq!(&previews.surface.pixels[..3], &CATEGORY_COLORS[4]);
+        assert_eq!(&previews.surface.pixels[3..], &[0, 0, 0]);
+    }
+
+    #[test]
+    fn category_colors_repeat() {
+        assert_eq!(category_color(3), category_color(15));
+        assert_ne!(category_color(3), category_color(4));
+    }
+}
@@ -0,0 +1,374 @@
+//! Running a mod's hooks at a point, chained the way Sapiens chains them
+
+use crate::sp::biome::SurfaceQuery;
+use crate::sp::common::{BiomeType, TerrainType, WorldGenOptions};
+use crate::sp::height::HeightQuery;
+use crate::sp::noise::Noise;
+use crate::sp::planet::{self, TangentFrame};
+use crate::sp::rainfall::RainfallQuery;
+use crate::sp::worldgen::WorldGenHooks;
+use sapiens_sys::SPVec3;
+use std::cell::RefCell;
+use std::os::raw::c_int;
+
+/// Prerender units per meter
+const METER: f64 = crate::sp_meters_to_prerender!(1.0);
+
+/// How far apart, in radians, the heights that `steepness` compares are
+const STEEPNESS_STEP: f64 = 1.0e-4;
+
+/// What a mod's hooks make of one point on the planet
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PointSample {
+    /// Unit vector from the planet's center to the point
+    pub point_normal: SPVec3,
+
+    /// From `spHeightGet`, in prerender units. Negative under the sea
+    pub altitude: f64,
+
+    /// Rise over run of the height field around the point, so flat ground is `0` and a 45° slope is `1`
+    pub steepness: f32,
+
+    /// Summer and winter rainfall from `spRainfallGet`
+    pub rainfall: [f64; 2],
+
+    /// Summer and winter temperature in degrees Celsius, from `estimate_temperature`
+    pub temperature: [f64; 2],
+
+    /// From `spClimateGet`
+    pub climate_type: c_int,
+
+    /// From `spBiomeGetBiomeType`
+    pub biome_type: c_int,
+
+    /// From `spBiomeGetSurfaceType`, or `None` if it didn't answer with a valid terrain type ID or the point has no biome
+    /// type to ask it about
+    pub terrain_type: Option<TerrainType>,
+}
+
+impl PointSample {
+    pub fn is_land(&self) -> bool {
+        self.altitude >= 0.0
+    }
+}
+
+/// Calls a mod's hooks for any point, with its own noise and a fixed set of world-gen options
+///
+/// Sapiens works out temperature and the rainfall it passes to `spRainfallGet` itself, and it isn't exposed to mods,
+/// so those come from `estimate_temperature` and `estimate_pregen_rainfall`. Rivers aren't simulated either, so every
+/// point is infinitely far from one. `noise1` goes to the rainfall and surface hooks as well as the height hook
+pub struct Sampler {
+    hooks: WorldGenHooks,
+    options: WorldGenOptions,
+    noise1: Noise,
+    noise2: Noise,
+
+    /// Grown as the biome type hook returns new indices, and passed to the surface hook as is
+    biome_types: RefCell<Vec<BiomeType>>,
+}
+
+impl Sampler {
+    /// Creates the noise from `seed`, like a world with that seed would
+    pub fn new(hooks: WorldGenHooks, options: WorldGenOptions, seed: i32) -> Self {
+        Sampler {
+            hooks,
+            options,
+            noise1: Noise::new(seed, 0.5),
+            noise2: Noise::new(seed.wrapping_add(1), 0.5),
+            biome_types: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// The biome type array passed to `spBiomeGetSurfaceType`
+    ///
+    /// It's padded with default biome types the first time the biome type hook returns an index past its end. The
+    /// same array is passed for every sample, so changes the surface hook makes to it carry over
+    pub fn with_biome_types(mut self, biome_types: Vec<BiomeType>) -> Self {
+        self.biome_types = RefCell::new(biome_types);
+        self
+    }
+
+    pub fn hooks(&self) -> &WorldGenHooks {
+        &self.hooks
+    }
+
+    pub fn options(&self) -> &WorldGenOptions {
+        &self.options
+    }
+
+    /// The altitude from `spHeightGet` alone, in prerender units
+    pub fn altitude(&self, point_normal: SPVec3) -> f64 {
+        self.hooks
+            .height
+            .get(
+                &self.noise1.handle(),
+                &self.noise2.handle(),
+                &HeightQuery::at(point_normal),
+                &self.options,
+            )
+            .x
+    }
+
+    /// How steep the height field is at a point, comparing `altitude` with the heights a short step east and north
+    pub fn steepness(&self, point_normal: SPVec3, altitude: f64) -> f32 {
+        let frame = TangentFrame::<SPVec3>::at(&point_normal);
+        let run = STEEPNESS_STEP * planet::PLANET_RADIUS;
+        let neighbor = |east: f64, north: f64| {
+            let offset = frame.to_world(&SPVec3 {
+                x: east,
+                y: north,
+                z: 1.0,
+            });
+            self.altitude(planet::surface_normal(&offset))
+        };
+
+        let east = neighbor(STEEPNESS_STEP, 0.0) - altitude;
+        let north = neighbor(0.0, STEEPNESS_STEP) - altitude;
+        ((east * east + north * north).sqrt() / run) as f32
+    }
+
+    /// Runs every hook for a point
+    pub fn sample(&self, point_normal: SPVec3) -> PointSample {
+        let altitude = self.altitude(point_normal);
+        let steepness = self.steepness(point_normal, altitude);
+
+        let pregen_rainfall = estimate_pregen_rainfall(&point_normal);
+        let rainfall = self.hooks.rainfall.get(
+            &self.noise1.handle(),
+            &RainfallQuery::from_sp(
+                point_normal,
+                point_normal,
+                f64::INFINITY,
+                pregen_rainfall[0],
+                pregen_rainfall[1],
+            ),
+            &self.options,
+        );
+
+        let temperature = estimate_temperature(&point_normal, altitude, &self.options);
+        let climate_type = self.hooks.climate.get(
+            temperature[0] as f32,
+            temperature[1] as f32,
+            rainfall[0] as f32,
+            rainfall[1] as f32,
+        );
+        let biome_type = self.hooks.biome.biome_type(climate_type);
+
+        let query = SurfaceQuery {
+            point_normal,
+            noise_loc: point_normal,
+            biome_type,
+            vegetation_state: 0,
+            altitude,
+            steepness,
+            river_distance: f32::INFINITY,
+        };
+        // A negative biome type isn't an index, so there's nothing to ask the surface hook about
+        let terrain_type = if biome_type < 0 {
+            None
+        } else {
+            let mut biome_types = self.biome_types.borrow_mut();
+            if biome_types.len() <= biome_type as usize {
+                biome_types.resize(biome_type as usize + 1, BiomeType::default());
+            }
+            self.hooks
+                .biome
+                .terrain_type(&self.noise1.handle(), &mut biome_types, &query)
+        };
+
+        PointSample {
+            point_normal,
+            altitude,
+            steepness,
+            rainfall,
+            temperature,
+            climate_type,
+            biome_type,
+            terrain_type,
+        }
+    }
+}
+
+/// A rough summer and winter temperature in degrees Celsius, standing in for the game's own climate model
+///
+/// It's about 28°C at the equator and -17°C at the poles on average, with seasons that grow stronger away from the
+/// equator, cools by 6.5°C per kilometer above sea level, and adds the world's temperature offset
+pub fn estimate_temperature(
+    point_normal: &SPVec3,
+    altitude: f64,
+    options: &WorldGenOptions,
+) -> [f64; 2] {
+    let sin_latitude = point_normal.y.clamp(-1.0, 1.0);
+    let mean = 28.0 - 45.0 * sin_latitude * sin_latitude;
+    let season = 18.0 * sin_latitude.abs();
+    let lapse = 6.5 * (altitude.max(0.0) / METER) / 1000.0;
+
+    let base = mean - lapse + options.temperature_offset();
+    [base + season, base - season]
+}
+
+/// A rough summer and winter rainfall, standing in for what Sapiens works out before calling `spRainfallGet`
+///
+/// It's wet at the equator and around 55° latitude, and dry around 30°, with more rain in summer than in winter
+pub fn estimate_pregen_rainfall(point_normal: &SPVec3) -> [f64; 2] {
+    let latitude = point_normal.y.clamp(-1.0, 1.0).asin().abs().to_degrees();
+    let band = |center: f64, width: f64| (-((latitude - center) / width).powi(2)).exp();
+    let rainfall = 10.0 + 160.0 * band(0.0, 12.0) + 70.0 * band(55.0, 12.0);
+
+    [rainfall * 1.25, rainfall * 0.75]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_vec_near;
+    use crate::sp::biome::Vanilla;
+    use crate::sp::height;
+    use sapiens_sys::{SPNoise, SPVec4, SPWorldGenOptions};
+
+    /// A plateau 1km high everywhere
+    unsafe extern "C" fn plateau(
+        _noise1: *mut SPNoise,
+        _noise2: *mut SPNoise,
+        _point_normal: SPVec3,
+        _noise_loc: SPVec3,
+        _world_gen_options: SPWorldGenOptions,
+        _river_value: f64,
+        _river_distance: f64,
+    ) -> SPVec4 {
+        SPVec4 {
+            x: 1000.0 * METER,
+            y: 0.0,
+            z: 0.0,
+            w: 0.0,
+        }
+    }
+
+    /// Rises 1m for every meter travelled towards +X
+    unsafe extern "C" fn ramp(
+        _noise1: *mut SPNoise,
+        _noise2: *mut SPNoise,
+        point_normal: SPVec3,
+        _noise_loc: SPVec3,
+        _world_gen_options: SPWorldGenOptions,
+        _river_value: f64,
+        _river_distance: f64,
+    ) -> SPVec4 {
+        SPVec4 {
+            x: point_normal.x * planet::PLANET_RADIUS,
+            y: 0.0,
+            z: 0.0,
+            w: 0.0,
+        }
+    }
+
+    fn at_latitude(degrees: f64) -> SPVec3 {
+        planet::LatLong::from_degrees(degrees, 0.0).to_unit_vector()
+    }
+
+    fn sampler(height_get: sapiens_sys::SPHeightGetFunc) -> Sampler {
+        let hooks = WorldGenHooks::stand_in()
+            .with_height(unsafe { height::Vanilla::from_sp(height_get) }.unwrap());
+        // Normal ground is terrain type 1 and rock is 3, in every biome
+        let biome_type = BiomeType::new(
+            TerrainType::from_id(1),
+            TerrainType::from_id(2),
+            TerrainType::from_id(3),
+            TerrainType::from_id(4),
+            TerrainType::from_id(5),
+        );
+        Sampler::new(hooks, WorldGenOptions::vanilla(), 1234).with_biome_types(vec![biome_type; 8])
+    }
+
+    #[test]
+    fn chains_the_hooks() {
+        let sampler = sampler(Some(plateau));
+        let sample = sampler.sample(at_latitude(0.0));
+
+        assert!(sample.is_land());
+        assert_vec_near!(sample.altitude, 1000.0 * METER);
+        assert_vec_near!(sample.steepness as f64, 0.0);
+        assert_vec_near!(sample.temperature[0], 28.0 - 6.5);
+        assert_eq!(sample.rainfall, estimate_pregen_rainfall(&at_latitude(0.0)));
+
+        // The stand-ins make a tropical climate, whose biome type is the climate type
+        assert_eq!(sample.climate_type, 3);
+        assert_eq!(sample.biome_type, 3);
+        assert_eq!(sample.terrain_type, Some(TerrainType::from_id(1)));
+    }
+
+    #[test]
+    fn steepness_is_rise_over_run() {
+        let sampler = sampler(Some(ramp));
+
+        // Facing +Z, east is +X, so the ramp climbs at 45°
+        let sample = sampler.sample(at_latitude(0.0));
+        assert_vec_near!(sample.steepness as f64, 1.0, epsilon = 1.0e-3);
+        assert_eq!(sample.terrain_type, Some(TerrainType::from_id(3)));
+    }
+
+    unsafe extern "C" fn no_biome(_climate_type: c_int) -> c_int {
+        -1
+    }
+
+    #[test]
+    fn points_without_a_biome_type_have_no_terrain() {
+        let surface_type = Vanilla::stand_in().as_sp_biome_get_surface_type_func();
+        let hooks = WorldGenHooks::stand_in()
+            .with_biome(unsafe { Vanilla::from_sp(Some(no_biome), surface_type) }.unwrap());
+        let sampler = Sampler::new(hooks, WorldGenOptions::vanilla(), 1234);
+
+        let sample = sampler.sample(at_latitude(0.0));
+        assert_eq!(sample.biome_type, -1);
+        assert_eq!(sample.terrain_type, None);
+        assert!(sampler.biome_types.borrow().is_empty());
+    }
+
+    #[test]
+    fn biome_types_grow_to_fit() {
+        let sampler = Sampler::new(WorldGenHooks::stand_in(), WorldGenOptions::vanilla(), 1234);
+
+        // The stand-ins give the equator biome type 3, so it's padded with defaults up to there
+        let sample = sampler.sample(at_latitude(0.0));
+        assert_eq!(sample.terrain_type, Some(TerrainType::from_id(0)));
+        assert_eq!(sampler.biome_types.borrow().len(), 4);
+
+        sampler.sample(at_latitude(0.0));
+        assert_eq!(sampler.biome_types.borrow().len(), 4);
+    }
+
+    #[test]
+    fn temperature_falls_towards_the_poles_and_uphill() {
+        let options = WorldGenOptions::vanilla();
+        let equator = estimate_temperature(&at_latitude(0.0), 0.0, &options);
+        let pole = estimate_temperature(&at_latitude(90.0), 0.0, &options);
+        let mountain = estimate_temperature(&at_latitude(0.0), 2000.0 * METER, &options);
+
+        assert_vec_near!(equator[0], 28.0);
+        assert_vec_near!(equator[1], 28.0);
+        assert!(pole[0] > pole[1]);
+        assert_vec_near!((pole[0] + pole[1]) / 2.0, -17.0);
+        assert_vec_near!(mountain[0], 15.0);
+
+        let warmer = WorldGenOptions::builder()
+            .temperature_offset(5.0)
+            .build()
+            .unwrap();
+        assert_vec_near!(
+            estimate_temperature(&at_latitude(0.0), 0.0, &warmer)[0],
+            33.0
+        );
+    }
+
+    #[test]
+    fn rainfall_has_wet_and_dry_bands() {
+        let equator = estimate_pregen_rainfall(&at_latitude(0.0));
+        let subtropics = estimate_pregen_rainfall(&at_latitude(-30.0));
+        let temperate = estimate_pregen_rainfall(&at_latitude(55.0));
+
+        assert!(equator[0] > equator[1]);
+        assert!(subtropics[0] < 25.0);
+        assert!(temperate[0] > subtropics[0] * 3.0);
+        assert!(equator[0] > temperate[0]);
+    }
+}
@@ -0,0 +1,82 @@
+//! Exports a height pipeline as `spHeightGet` and checks that the game would see the same heights as an offline run
+
+extern crate sapiens_rs;
+extern crate sapiens_rs_macros;
+extern crate sapiens_sys;
+
+use sapiens_rs::sp::common::WorldGenOptions;
+use sapiens_rs::sp::height::pipeline::{NoiseInput, Pipeline};
+use sapiens_rs::sp::height::HeightQuery;
+use sapiens_rs::sp::noise::{Noise, NoiseRef};
+use sapiens_rs::sp_meters_to_prerender;
+use sapiens_rs_macros::export_to_sapiens;
+use sapiens_sys::*;
+use std::sync::OnceLock;
+
+static TERRAIN: OnceLock<Pipeline> = OnceLock::new();
+
+fn terrain() -> Pipeline {
+    let hills = Pipeline::new().base(NoiseInput::Noise2, 4.0, 4, sp_meters_to_prerender!(300.0));
+
+    Pipeline::new()
+        .base(NoiseInput::Noise1, 1.0, 6, sp_meters_to_prerender!(2000.0))
+        .add(hills)
+        .multiply(1.1)
+        .river_carve(
+            sp_meters_to_prerender!(20.0),
+            sp_meters_to_prerender!(200.0),
+        )
+        .ocean_floor(sp_meters_to_prerender!(3000.0))
+}
+
+#[export_to_sapiens]
+fn height_get(
+    noise1: &NoiseRef,
+    noise2: &NoiseRef,
+    query: &HeightQuery,
+    options: &WorldGenOptions,
+) -> SPVec4 {
+    TERRAIN
+        .get_or_init(terrain)
+        .get(noise1, noise2, query, options)
+}
+
+#[test]
+fn export_matches_offline_evaluation() {
+    let noise1 = Noise::new(11, 0.5);
+    let noise2 = Noise::new(12, 0.5);
+    let options = WorldGenOptions::archipelago();
+
+    for i in 0..32 {
+        let angle = i as f64 * 0.2;
+        let query = HeightQuery::from_sp(
+            SPVec3 {
+                x: angle.cos(),
+                y: 0.0,
+                z: angle.sin(),
+            },
+            SPVec3 {
+                x: angle.cos() * 3.0,
+                y: 1.0,
+                z: angle.sin() * 3.0,
+            },
+            0.5,
+            sp_meters_to_prerender!(i as f64 * 10.0),
+        );
+
+        let exported = unsafe {
+            spHeightGet(
+                noise1.as_sp_noise(),
+                noise2.as_sp_noise(),
+                query.point_normal,
+                query.noise_loc,
+                *options.as_sp_world_gen_options(),
+                query.river_value,
+                query.river_distance,
+            )
+        };
+        let offline = terrain().get(&noise1, &noise2, &query, &options);
+
+        assert_eq!(exported.x, offline.x);
+    }
+}
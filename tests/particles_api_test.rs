@@ -106,7 +106,7 @@ fn get_render_group_types() -> Vec<RenderGroupInfo<VanillaRenderType>> {
 
 #[export_to_sapiens]
 fn emitter_was_added(
-    thread_state: &ThreadState,
+    thread_state: &mut ThreadState,
     emitter_state: &mut EmitterState,
     emitter_type: VanillaEmitterType,
 ) -> bool {
@@ -121,12 +121,12 @@ fn emitter_was_added(
             let normalized_pos = sp::vec3_div(&emitter_state.p, pos_length);
             let gravity = sp::vec3_mul(&normalized_pos, sp_meters_to_prerender!(-10.0));
 
-            for _ in 0..32 {
-                let rand_pos_vec =
-                    sp::vec3_mul(&thread_state.rand.get_vec3(), sp_meters_to_prerender!(0.2));
-                let rand_vel_vec = thread_state.rand.get_vec3();
+            let rand = &thread_state.rand;
+            let particles = (0..32).map(|_| {
+                let rand_pos_vec = sp::vec3_mul(&rand.get_vec3(), sp_meters_to_prerender!(0.2));
+                let rand_vel_vec = rand.get_vec3();
 
-                let mut state = ParticleState {
+                ParticleState {
                     p: sp::vec3_add(&sp::vec3_mul(&normalized_pos, pos_length), &rand_pos_vec),
                     v: sp::vec3_mul(
                         &sp::vec3_add(&normalized_pos, &rand_vel_vec),
@@ -135,13 +135,19 @@ fn emitter_was_added(
                     gravity,
                     lifeLeft: 1.0,
                     scale: 1.0,
-                    randomValueA: thread_state.rand.get_float(),
+                    randomValueA: rand.get_float(),
                     randomValueB: 0.0,
                     userData: SPVec4::default(),
                     particleTextureType: 3,
-                };
-
-                thread_state.add_particle(emitter_state, VanillaRenderType::Standard, &mut state);
+                }
+            });
+
+            if let Err(error) = thread_state.particles.add_particles(
+                emitter_state,
+                VanillaRenderType::Standard,
+                particles,
+            ) {
+                log_add_error(error);
             }
         }
         VanillaEmitterType::Feathers => {
@@ -150,12 +156,12 @@ fn emitter_was_added(
             let normalized_pos = sp::vec3_div(&emitter_state.p, pos_length);
             let gravity = sp::vec3_mul(&normalized_pos, sp_meters_to_prerender!(-2.0));
 
-            for _ in 0..32 {
-                let rand_pos_vec =
-                    sp::vec3_mul(&thread_state.rand.get_vec3(), sp_meters_to_prerender!(0.2));
-                let rand_vel_vec = thread_state.rand.get_vec3();
+            let rand = &thread_state.rand;
+            let particles = (0..32).map(|_| {
+                let rand_pos_vec = sp::vec3_mul(&rand.get_vec3(), sp_meters_to_prerender!(0.2));
+                let rand_vel_vec = rand.get_vec3();
 
-                let mut state = ParticleState {
+                ParticleState {
                     p: sp::vec3_add(
                         &sp::vec3_mul(&normalized_pos, pos_length + sp_meters_to_prerender!(0.25)),
                         &rand_pos_vec,
@@ -167,13 +173,19 @@ fn emitter_was_added(
                     gravity,
                     lifeLeft: 1.0,
                     scale: 1.0,
-                    randomValueA: thread_state.rand.get_float(),
+                    randomValueA: rand.get_float(),
                     randomValueB: 0.0,
                     userData: SPVec4::default(),
                     particleTextureType: 3,
-                };
-
-                thread_state.add_particle(emitter_state, VanillaRenderType::Standard, &mut state);
+                }
+            });
+
+            if let Err(error) = thread_state.particles.add_particles(
+                emitter_state,
+                VanillaRenderType::Standard,
+                particles,
+            ) {
+                log_add_error(error);
             }
         }
     }
@@ -199,8 +211,6 @@ fn update_emitter(
     emitter_type: VanillaEmitterType,
     delta_time: f64,
 ) {
-    let rand = &thread_state.rand;
-
     emitter_state.timeAccumulatorA += delta_time;
 
     // Run particle simulations at a fixed time step
@@ -248,8 +258,8 @@ fn update_emitter(
                         ),
                         particleTextureType: 2,
                         lifeLeft: 1.0,
-                        scale: 0.2 + rand.get_float() * 0.2,
-                        randomValueA: rand.get_float(),
+                        scale: 0.2 + thread_state.rand.get_float() * 0.2,
+                        randomValueA: thread_state.rand.get_float(),
                         randomValueB: 0.0,
                         gravity: sp::vec3_add(
                             &intermediate_gravity,
@@ -261,7 +271,13 @@ fn update_emitter(
                         userData: Default::default(),
                     };
 
-                    thread_state.add_particle(emitter_state, VanillaRenderType::Smoke, &mut state);
+                    if let Err(error) = thread_state.add_particle(
+                        emitter_state,
+                        VanillaRenderType::Smoke,
+                        &mut state,
+                    ) {
+                        log_add_error(error);
+                    }
 
                     emitter_state.counters[0] = (1.0 + (20.0 * (1.0 - noise_value_c))) as u8;
                 } else {
@@ -271,12 +287,13 @@ fn update_emitter(
                 // Flame 1
                 if emitter_state.counters[1] == 0 {
                     let rand_pos_vec =
-                        sp::vec3_mul(&rand.get_vec3(), sp_meters_to_prerender!(0.04));
+                        sp::vec3_mul(&thread_state.rand.get_vec3(), sp_meters_to_prerender!(0.04));
                     let scale_average = 0.5;
 
                     emit_fire_particle(thread_state, emitter_state, scale_average, &rand_pos_vec);
 
-                    emitter_state.counters[1] = (5.0 + (20.0 * rand.get_float())) as u8;
+                    emitter_state.counters[1] =
+                        (5.0 + (20.0 * thread_state.rand.get_float())) as u8;
                 } else {
                     emitter_state.counters[1] -= 1;
                 }
@@ -284,7 +301,7 @@ fn update_emitter(
                 // Flame 2
                 if emitter_state.counters[2] == 0 {
                     let rand_pos_vec =
-                        sp::vec3_mul(&rand.get_vec3(), sp_meters_to_prerender!(0.04));
+                        sp::vec3_mul(&thread_state.rand.get_vec3(), sp_meters_to_prerender!(0.04));
                     let rand_pos_vec = sp::vec3_add(
                         &rand_pos_vec,
                         &sp::vec3_mul(
@@ -297,7 +314,8 @@ fn update_emitter(
 
                     emit_fire_particle(thread_state, emitter_state, scale_average, &rand_pos_vec);
 
-                    emitter_state.counters[2] = (5.0 + (20.0 * rand.get_float())) as u8;
+                    emitter_state.counters[2] =
+                        (5.0 + (20.0 * thread_state.rand.get_float())) as u8;
                 } else {
                     emitter_state.counters[2] -= 1;
                 }
@@ -305,7 +323,7 @@ fn update_emitter(
                 // Flame 3
                 if emitter_state.counters[3] == 0 {
                     let rand_pos_vec =
-                        sp::vec3_mul(&rand.get_vec3(), sp_meters_to_prerender!(0.04));
+                        sp::vec3_mul(&thread_state.rand.get_vec3(), sp_meters_to_prerender!(0.04));
                     let rand_pos_vec = sp::vec3_add(
                         &rand_pos_vec,
                         &sp::vec3_mul(
@@ -317,14 +335,15 @@ fn update_emitter(
 
                     emit_fire_particle(thread_state, emitter_state, scale_average, &rand_pos_vec);
 
-                    emitter_state.counters[3] = (5.0 + (20.0 * rand.get_float())) as u8;
+                    emitter_state.counters[3] =
+                        (5.0 + (20.0 * thread_state.rand.get_float())) as u8;
                 } else {
                     if emitter_state.counters[3] == 18 {
                         // Spark
                         let pos_length = sp::vec3_length(&emitter_state.p);
                         let normalized_pos = sp::vec3_div(&emitter_state.p, pos_length);
 
-                        let rand_vec = rand.get_vec3();
+                        let rand_vec = thread_state.rand.get_vec3();
                         let rand_pos_vec = sp::vec3_mul(&rand_vec, sp_meters_to_prerender!(0.1));
                         let rand_vel_vec = sp::vec3_mul(&rand_vec, sp_meters_to_prerender!(0.4));
 
@@ -338,22 +357,27 @@ fn update_emitter(
                             ),
                             v: sp::vec3_mul(
                                 &sp::vec3_add(&normalized_pos, &rand_vel_vec),
-                                sp_meters_to_prerender!(2.0 + rand.get_float() * 0.5),
+                                sp_meters_to_prerender!(2.0 + thread_state.rand.get_float() * 0.5),
+                            ),
+                            gravity: sp::vec3_mul(
+                                &thread_state.rand.get_vec3(),
+                                sp_meters_to_prerender!(1.0),
                             ),
-                            gravity: sp::vec3_mul(&rand.get_vec3(), sp_meters_to_prerender!(1.0)),
                             lifeLeft: 1.0,
-                            scale: 0.01 + rand.get_float() * 0.02,
-                            randomValueA: rand.get_float(),
-                            randomValueB: rand.get_float(),
+                            scale: 0.01 + thread_state.rand.get_float() * 0.02,
+                            randomValueA: thread_state.rand.get_float(),
+                            randomValueB: thread_state.rand.get_float(),
                             userData: Default::default(),
                             particleTextureType: 3,
                         };
 
-                        thread_state.add_particle(
+                        if let Err(error) = thread_state.add_particle(
                             emitter_state,
                             VanillaRenderType::Spark,
                             &mut state,
-                        );
+                        ) {
+                            log_add_error(error);
+                        }
                     }
 
                     emitter_state.counters[3] -= 1;
@@ -366,7 +390,7 @@ fn update_emitter(
 }
 
 fn emit_fire_particle(
-    thread_state: &ThreadState,
+    thread_state: &mut ThreadState,
     emitter_state: &mut EmitterState,
     scale_average: f64,
     rand_pos_vec: &SPVec3,
@@ -397,7 +421,16 @@ fn emit_fire_particle(
         },
     };
 
-    thread_state.add_particle(emitter_state, VanillaRenderType::Fire, &mut state);
+    if let Err(error) =
+        thread_state.add_particle(emitter_state, VanillaRenderType::Fire, &mut state)
+    {
+        log_add_error(error);
+    }
+}
+
+/// The hooks are called through `extern "C"`, where a panic would abort the game, so failures are logged instead
+fn log_add_error(error: AddParticleError) {
+    eprintln!("couldn't add particles: {}", error);
 }
 
 #[cfg(test)]
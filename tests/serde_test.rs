@@ -0,0 +1,204 @@
+#![cfg(feature = "serde")]
+
+use num_derive::{FromPrimitive, ToPrimitive};
+use sapiens_rs::sp::common::WorldGenOptions;
+use sapiens_rs::sp::particles::{EmitterTypeInfo, RenderGroupInfo, VertexAttributeType};
+use sapiens_sys::serde::de::DeserializeOwned;
+use sapiens_sys::serde::{Deserialize, Serialize};
+use sapiens_sys::*;
+use std::fmt::Debug;
+
+#[derive(Debug, PartialEq, FromPrimitive, ToPrimitive, Serialize, Deserialize)]
+#[serde(crate = "sapiens_sys::serde")]
+enum RenderGroupId {
+    Smoke,
+    Fire,
+}
+
+#[derive(Debug, PartialEq, FromPrimitive, ToPrimitive, Serialize, Deserialize)]
+#[serde(crate = "sapiens_sys::serde")]
+enum EmitterTypeId {
+    Campfire,
+}
+
+fn json_round_trip<T: Serialize + DeserializeOwned>(value: &T) -> T {
+    let json = serde_json::to_string(value).unwrap();
+    serde_json::from_str(&json).unwrap()
+}
+
+fn ron_round_trip<T: Serialize + DeserializeOwned>(value: &T) -> T {
+    let ron = ron::to_string(value).unwrap();
+    ron::from_str(&ron).unwrap()
+}
+
+fn assert_round_trips<T: Serialize + DeserializeOwned + PartialEq + Debug>(value: T) {
+    assert_eq!(json_round_trip(&value), value);
+    assert_eq!(ron_round_trip(&value), value);
+}
+
+fn particle_state() -> SPParticleState {
+    SPParticleState {
+        p: SPVec3 {
+            x: 1.0,
+            y: 2.0,
+            z: 3.0,
+        },
+        v: SPVec3 {
+            x: 0.5,
+            y: 0.0,
+            z: -0.5,
+        },
+        gravity: SPVec3 {
+            x: 0.0,
+            y: -10.0,
+            z: 0.0,
+        },
+        lifeLeft: 1.0,
+        scale: 0.25,
+        randomValueA: 0.1,
+        randomValueB: 0.9,
+        userData: SPVec4 {
+            x: 1.0,
+            y: 2.0,
+            z: 3.0,
+            w: 4.0,
+        },
+        particleTextureType: 3,
+    }
+}
+
+#[test]
+fn math_types_round_trip() {
+    assert_round_trips(SPVec2 { x: 1.0, y: -2.0 });
+    assert_round_trips(SPVec3 {
+        x: 1.0,
+        y: -2.0,
+        z: 3.5,
+    });
+    assert_round_trips(SPVec4 {
+        x: 1.0,
+        y: -2.0,
+        z: 3.5,
+        w: 0.25,
+    });
+    assert_round_trips(SPMat3 {
+        m0: 1.0,
+        m1: 2.0,
+        m2: 3.0,
+        m3: 4.0,
+        m4: 5.0,
+        m5: 6.0,
+        m6: 7.0,
+        m7: 8.0,
+        m8: 9.0,
+    });
+}
+
+#[test]
+fn world_gen_options_round_trip() {
+    let options = SPWorldGenOptions {
+        heightOffset: 0.5,
+        rainfallMultiplier: 1.5,
+        temperatureOffset: -2.0,
+        scales: SPVec3 {
+            x: 1.0,
+            y: 1.0,
+            z: 1.0,
+        },
+        influences: SPVec3 {
+            x: 1.0,
+            y: 0.5,
+            z: 0.25,
+        },
+    };
+
+    assert_round_trips(options);
+}
+
+#[test]
+fn world_gen_options_use_snake_case_fields() {
+    let json = serde_json::to_value(SPWorldGenOptions::default()).unwrap();
+
+    assert!(json.get("height_offset").is_some());
+    assert!(json.get("rainfall_multiplier").is_some());
+    assert!(json.get("temperature_offset").is_some());
+}
+
+#[test]
+fn world_gen_options_serialize_like_sp_world_gen_options() {
+    let options = WorldGenOptions::archipelago();
+
+    assert_eq!(
+        serde_json::to_value(options).unwrap(),
+        serde_json::to_value(*options.as_sp_world_gen_options()).unwrap()
+    );
+    assert_round_trips(options);
+}
+
+#[test]
+fn particle_states_round_trip() {
+    assert_round_trips(particle_state());
+    assert_round_trips(SPParticleEmitterState {
+        p: SPVec3 {
+            x: 1.0,
+            y: 2.0,
+            z: 3.0,
+        },
+        rot: SPMat3 {
+            m0: 1.0,
+            m4: 1.0,
+            m8: 1.0,
+            ..Default::default()
+        },
+        timeAccumulatorA: 0.25,
+        timeAccumulatorB: 10.0,
+        userData: SPVec4::default(),
+        globalType: 7,
+        counters: [1, 2, 3, 4],
+    });
+}
+
+#[test]
+fn vertex_attribute_types_are_readable_strings() {
+    assert_eq!(
+        serde_json::to_string(&VertexAttributeType::Vec3).unwrap(),
+        "\"vec3\""
+    );
+    assert_eq!(
+        serde_json::from_str::<VertexAttributeType>("\"float\"").unwrap(),
+        VertexAttributeType::Float
+    );
+    assert_eq!(ron::to_string(&VertexAttributeType::Vec2).unwrap(), "vec2");
+}
+
+#[test]
+fn render_group_info_round_trips() {
+    let info = RenderGroupInfo {
+        shader_name: "fireParticle".to_string(),
+        id: RenderGroupId::Fire,
+        vertex_descriptions: vec![
+            VertexAttributeType::Vec3,
+            VertexAttributeType::Vec2,
+            VertexAttributeType::Vec4,
+        ],
+    };
+
+    for round_tripped in [json_round_trip(&info), ron_round_trip(&info)] {
+        assert_eq!(round_tripped.shader_name, info.shader_name);
+        assert_eq!(round_tripped.id, info.id);
+        assert_eq!(round_tripped.vertex_descriptions, info.vertex_descriptions);
+    }
+}
+
+#[test]
+fn emitter_type_info_round_trips() {
+    let info = EmitterTypeInfo {
+        name: "campfire".to_string(),
+        id: EmitterTypeId::Campfire,
+    };
+
+    for round_tripped in [json_round_trip(&info), ron_round_trip(&info)] {
+        assert_eq!(round_tripped.name, info.name);
+        assert_eq!(round_tripped.id, info.id);
+    }
+}
@@ -0,0 +1,55 @@
+//! Links an exported `spHeightGet` in as a hook and checks the world it makes with `sp::worldgen::analyze`, the way a
+//! mod would guard its land coverage
+
+extern crate sapiens_rs;
+extern crate sapiens_rs_macros;
+extern crate sapiens_sys;
+
+use sapiens_rs::sp::common::WorldGenOptions;
+use sapiens_rs::sp::height::{self, HeightQuery};
+use sapiens_rs::sp::noise::NoiseRef;
+use sapiens_rs::sp::worldgen::{analyze, Sampler, WorldGenHooks};
+use sapiens_rs_macros::export_to_sapiens;
+use sapiens_sys::*;
+
+/// Land north of 0.4 on the Y axis, which is 30% of the planet's surface. The height offset moves the coast
+#[export_to_sapiens]
+fn height_get(
+    _noise1: &NoiseRef,
+    _noise2: &NoiseRef,
+    query: &HeightQuery,
+    options: &WorldGenOptions,
+) -> SPVec4 {
+    SPVec4 {
+        x: query.point_normal.y - 0.4 + options.height_offset(),
+        y: 0.0,
+        z: 0.0,
+        w: 0.0,
+    }
+}
+
+fn sampler(options: WorldGenOptions) -> Sampler {
+    let height = unsafe { height::Vanilla::from_sp(Some(spHeightGet)) }.unwrap();
+    Sampler::new(WorldGenHooks::stand_in().with_height(height), options, 1234)
+}
+
+#[test]
+fn land_coverage_stays_in_range() {
+    let report = analyze(&sampler(WorldGenOptions::vanilla()), 5000);
+
+    assert!((28.0..=32.0).contains(&report.land_percent()), "{}", report);
+    assert!(report.altitude.max > 0.0 && report.altitude.min < 0.0);
+    assert!(report.temperature[0].max > report.temperature[1].min);
+}
+
+#[test]
+fn height_offset_grows_the_land() {
+    let options = WorldGenOptions::builder()
+        .height_offset(0.2)
+        .build()
+        .unwrap();
+    let report = analyze(&sampler(options), 5000);
+
+    // The coast moves down to 0.2 on the Y axis, so 40% of the surface is land
+    assert!((39.0..=41.0).contains(&report.land_percent()), "{}", report);
+}
@@ -0,0 +1,123 @@
+//! Exports a world-gen graph's height and surface rules, and calls them the way Sapiens would
+
+#![cfg(feature = "worldgen-graph")]
+
+extern crate sapiens_rs;
+extern crate sapiens_rs_macros;
+extern crate sapiens_sys;
+
+use sapiens_rs::sp::biome::{SurfaceQuery, SurfaceType};
+use sapiens_rs::sp::common::{
+    register_terrain_type, BiomeType, BiomeTypes, TerrainType, WorldGenOptions,
+};
+use sapiens_rs::sp::height::HeightQuery;
+use sapiens_rs::sp::noise::{Noise, NoiseRef};
+use sapiens_rs::sp::worldgen::graph::{Format, WorldGenGraph};
+use sapiens_rs::sp_meters_to_prerender;
+use sapiens_rs_macros::export_to_sapiens;
+use sapiens_sys::*;
+use std::sync::OnceLock;
+
+const GRAPH: &str = r#"(
+    height: [
+        base(noise: "noise1", frequency: 1.0, octaves: 4, amplitude: 1000.0),
+        add(250.0),
+    ],
+    surface: [
+        (surface_type: rock, min_steepness: 0.5),
+        (surface_type: secondary, max_river_distance: 10.0),
+    ],
+)"#;
+
+static WORLD_GEN: OnceLock<WorldGenGraph> = OnceLock::new();
+
+fn graph() -> &'static WorldGenGraph {
+    WORLD_GEN.get_or_init(|| WorldGenGraph::parse(GRAPH, Format::Ron).unwrap())
+}
+
+#[export_to_sapiens]
+fn height_get(
+    noise1: &NoiseRef,
+    noise2: &NoiseRef,
+    query: &HeightQuery,
+    options: &WorldGenOptions,
+) -> SPVec4 {
+    graph().height.get(noise1, noise2, query, options)
+}
+
+#[export_to_sapiens]
+fn biome_get_surface_type(
+    noise: &NoiseRef,
+    biome_types: &BiomeTypes,
+    query: &SurfaceQuery,
+) -> SurfaceType {
+    match biome_types.own() {
+        Some(biome) if biome.rock().name().as_deref() == Some("snow") => SurfaceType::Beach,
+        _ => graph().surface.get(noise, query),
+    }
+}
+
+#[test]
+fn height_comes_from_the_graph() {
+    let noise = Noise::new(5, 0.5);
+    let point = SPVec3 {
+        x: 0.6,
+        y: 0.0,
+        z: 0.8,
+    };
+
+    let height = unsafe {
+        spHeightGet(
+            noise.as_sp_noise(),
+            noise.as_sp_noise(),
+            point,
+            point,
+            *WorldGenOptions::vanilla().as_sp_world_gen_options(),
+            0.0,
+            1.0,
+        )
+    };
+
+    let expected = noise.get(&point, 4) * 1000.0 + 250.0;
+    assert!((height.x - sp_meters_to_prerender!(expected)).abs() < 1e-12);
+}
+
+#[test]
+fn surface_comes_from_the_graph() {
+    let noise = Noise::new(5, 0.5);
+    let grass = TerrainType::from_id(1);
+    let clay = TerrainType::from_id(2);
+    let stone = TerrainType::from_id(3);
+    let sand = TerrainType::from_id(4);
+    let snow = register_terrain_type("snow", 6).unwrap();
+
+    let meadow = BiomeType::new(grass, clay, stone, sand, clay);
+    let mut tundra = meadow;
+    tundra.set_rock(snow);
+    let mut biome_types = [meadow, tundra];
+
+    let mut terrain_type = |biome_type: i32, steepness: f32, river_distance_meters: f64| unsafe {
+        let sp_biome_types = biome_types.as_mut_ptr() as *mut SPBiomeType;
+        let terrain_type = spBiomeGetSurfaceType(
+            noise.as_sp_noise(),
+            sp_biome_types,
+            SPVec3::default(),
+            SPVec3::default(),
+            biome_type,
+            0,
+            0.1,
+            steepness,
+            sp_meters_to_prerender!(river_distance_meters) as f32,
+        );
+        TerrainType::from_id(terrain_type as i16)
+    };
+
+    // The hook answers with the terrain type the biome uses for the surface type it picked
+    assert_eq!(terrain_type(0, 0.8, 100.0), stone);
+    assert_eq!(terrain_type(0, 0.1, 5.0), clay);
+    assert_eq!(terrain_type(0, 0.1, 100.0), grass);
+    assert_eq!(terrain_type(1, 0.8, 100.0), sand);
+
+    // Without a biome there's nothing to pick from, so the point gets terrain type 0
+    assert_eq!(terrain_type(-1, 0.1, 100.0), TerrainType::from_id(0));
+}